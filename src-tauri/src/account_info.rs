@@ -0,0 +1,209 @@
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::accounts::{self, AccountFolder};
+use crate::image_host;
+
+/// Краткие данные аккаунта из файлов папки — для сортировки и фильтра
+/// списка без открытия каждого аккаунта. Поля, которых нет в файлах, — None.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountSummary {
+    pub login: Option<String>,
+    pub server: Option<String>,
+    pub level: Option<u32>,
+    pub rank: Option<String>,
+    pub champions_count: Option<u32>,
+    pub skins_count: Option<u32>,
+    /// Есть локальный скриншот или ссылка на скриншот в тексте аккаунта.
+    pub has_screenshot: bool,
+    /// Ссылка на профиль op.gg.
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Стоимость покупки из строки "Purchase price:" / "Bought for:" / "Cost:"
+    /// (как записана, вместе с валютой).
+    #[serde(default)]
+    pub purchase_cost: Option<String>,
+    pub offer_id: Option<String>,
+    /// "Status:" и "Created:" из файла оффера `G17*.txt`.
+    pub offer_status: Option<String>,
+    pub offer_created: Option<String>,
+    /// Время последнего изменения файлов папки.
+    pub modified: Option<DateTime<Local>>,
+}
+
+// Те же шаблоны, что у парсера аккаунта во фронтенде (parser.ts).
+static REGION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)Region:\s*([^\n\r]+)").expect("valid region regex"));
+static ACCOUNT_SERVER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)Account\s*\(\s*Server\s*-\s*([^)\n\r]+)\s*\)").expect("valid account server regex")
+});
+static OPGG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)op\.gg/summoners/([a-z0-9]+)/").expect("valid op.gg regex"));
+static SERVER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)Server\s*[-:]\s*([^\n\r]+)").expect("valid server regex"));
+static LEVEL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)Level\s*[-:]\s*(\d+)").expect("valid level regex"));
+static CHAMPIONS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)Champions\s*[-:]\s*(\d+)").expect("valid champions regex"));
+static SKINS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)Skins\s*[-:]\s*(\d+)").expect("valid skins regex"));
+static RANK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)Previous Rank\s*-\s*([^\n\r]+)").expect("valid rank regex"));
+static PROFILE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)((?:https?://)?(?:www\.)?op\.gg/summoners/[^\s"'<>]+)"#).expect("valid profile regex")
+});
+// Только в начале строки: "Cost" встречается и в списках скинов.
+static PURCHASE_COST: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?im)^\s*(?:Purchase\s+(?:price|cost)|Bought\s+for|Cost)\s*[-:]\s*([^\n\r]+)")
+        .expect("valid purchase cost regex")
+});
+
+/// Код сервера из названия региона ("Europe West", "euw1" → "EUW").
+pub fn normalize_server(server: &str) -> String {
+    let normalized = server.trim().to_lowercase();
+    let code = match normalized.as_str() {
+        "brazil" | "br" | "br1" => "BR",
+        "euw" | "euw1" | "europe west" => "EUW",
+        "eune" | "eune1" | "europe nordic & east" | "europe nordic east" => "EUNE",
+        "na" | "na1" | "north america" => "NA",
+        "oce" | "oce1" | "oc1" | "oceania" => "OCE",
+        "las" | "las1" | "la2" | "latin america south" => "LAS",
+        "lan" | "lan1" | "la1" | "latin america north" => "LAN",
+        "tr" | "tr1" | "turkey" => "TR",
+        "ru" | "ru1" | "russia" => "RU",
+        "jp" | "jp1" | "japan" => "JP",
+        "kr" | "korea" => "KR",
+        "sea" | "sg2" | "singapore" | "southeast asia" => "SEA",
+        _ => return server.trim().to_uppercase(),
+    };
+    code.to_string()
+}
+
+fn capture<'a>(regex: &Regex, text: &'a str) -> Option<&'a str> {
+    regex
+        .captures(text)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().trim())
+        .filter(|s| !s.is_empty())
+}
+
+fn extract_server(text: &str) -> Option<String> {
+    [&*REGION, &*ACCOUNT_SERVER, &*OPGG, &*SERVER]
+        .iter()
+        .find_map(|regex| capture(regex, text))
+        .map(normalize_server)
+}
+
+/// Сводка по тексту аккаунта (все .txt папки, кроме шаблона и файла оффера).
+pub fn summarize_text(text: &str) -> AccountSummary {
+    let number = |regex: &Regex| capture(regex, text).and_then(|n| n.parse().ok());
+    AccountSummary {
+        login: accounts::extract_login(text),
+        server: extract_server(text),
+        level: number(&LEVEL),
+        rank: capture(&RANK, text).map(String::from),
+        champions_count: number(&CHAMPIONS),
+        skins_count: number(&SKINS),
+        has_screenshot: accounts::has_screenshot_url(text),
+        profile: capture(&PROFILE, text).map(String::from),
+        purchase_cost: capture(&PURCHASE_COST, text).map(String::from),
+        ..Default::default()
+    }
+}
+
+// Поля "Status:" и "Created:" файла оффера (см. accounts::save_offer_id_to_file).
+fn offer_file_field(content: &str, key: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(key))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Сводка по папке аккаунта: данные из текстовых файлов, скриншоты,
+/// файл оффера и время последнего изменения.
+pub fn summarize_folder(account_path: &Path) -> AccountSummary {
+    let mut text = String::new();
+    let mut modified: Option<std::time::SystemTime> = None;
+
+    if let Ok(entries) = fs::read_dir(account_path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if let Ok(time) = entry.metadata().and_then(|m| m.modified()) {
+                modified = Some(modified.map_or(time, |m| m.max(time)));
+            }
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            if !name.ends_with(".txt") || name == "info.txt" || name.starts_with("g17") {
+                continue;
+            }
+            if let Ok(content) = fs::read_to_string(&path) {
+                text.push_str(&content);
+                text.push_str("\n\n");
+            }
+        }
+    }
+
+    let mut summary = summarize_text(&text);
+    summary.has_screenshot |= !image_host::find_local_images(account_path).is_empty();
+    summary.modified = modified.map(DateTime::<Local>::from);
+
+    let path_str = account_path.to_string_lossy();
+    if let Some(offer_id) = accounts::find_offer_id(&path_str) {
+        if let Ok(content) = fs::read_to_string(account_path.join(format!("{}.txt", offer_id))) {
+            summary.offer_status = offer_file_field(&content, "Status:");
+            summary.offer_created = offer_file_field(&content, "Created:");
+        }
+        summary.offer_id = Some(offer_id);
+    }
+    summary
+}
+
+/// Заполняет сводку для всех папок параллельно (по числу ядер): на сетевом
+/// диске с сотнями аккаунтов последовательное чтение заметно медленнее.
+pub fn summarize_all(folders: &mut [AccountFolder]) {
+    if folders.is_empty() {
+        return;
+    }
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let chunk_size = folders.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        for chunk in folders.chunks_mut(chunk_size) {
+            scope.spawn(move || {
+                for folder in chunk {
+                    folder.summary = summarize_folder(Path::new(&folder.path));
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_text() {
+        let text = "Hi there,\nLogin: summary_login\nRegion: Europe West\n\
+            Level - 143\nChampions - 87\nSkins - 31\nPrevious Rank - Gold IV\n\
+            Skin Cost - 1350 RP\nPurchase price: $12.50\n\
+            https://www.op.gg/summoners/euw/Name-EUW\n\
+            Screenshot URL: https://i.imgur.com/abc.png\n";
+        let summary = summarize_text(text);
+        assert_eq!(summary.login.as_deref(), Some("summary_login"));
+        assert_eq!(summary.server.as_deref(), Some("EUW"));
+        assert_eq!(summary.level, Some(143));
+        assert_eq!(summary.champions_count, Some(87));
+        assert_eq!(summary.skins_count, Some(31));
+        assert_eq!(summary.rank.as_deref(), Some("Gold IV"));
+        assert!(summary.has_screenshot);
+        assert_eq!(summary.purchase_cost.as_deref(), Some("$12.50"));
+        assert_eq!(summary.profile.as_deref(), Some("https://www.op.gg/summoners/euw/Name-EUW"));
+
+        let summary = summarize_text("Account(Server - na1)\n");
+        assert_eq!(summary.server.as_deref(), Some("NA"));
+        assert_eq!(summary.level, None);
+        assert!(!summary.has_screenshot);
+    }
+}
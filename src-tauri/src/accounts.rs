@@ -0,0 +1,630 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, info, warn};
+
+use crate::account_info::{self, AccountSummary};
+use crate::config::AppSettings;
+use crate::image_host;
+
+/// Глубина поиска папок аккаунтов по умолчанию: хватает для
+/// RESELL/16.01.26/<аккаунт> и ещё одного уровня вложенности.
+pub const DEFAULT_SCAN_DEPTH: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountFolder {
+    pub name: String,
+    pub path: String,
+    pub is_listed: bool,
+    /// Родительская папка относительно выбранной ("16.01.26",
+    /// "RESELL/16.01.26"); пустая строка — аккаунт лежит прямо в ней.
+    #[serde(default)]
+    pub group: String,
+    #[serde(default)]
+    pub summary: AccountSummary,
+}
+
+/// Папка, в которой лежат аккаунты (партия/дата закупки).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountGroup {
+    pub name: String,
+    pub path: String,
+    pub total: usize,
+    pub listed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountsData {
+    pub accounts: Vec<AccountFolder>,
+    pub base_path: String,
+    #[serde(default)]
+    pub groups: Vec<AccountGroup>,
+}
+
+// Извлекает логин аккаунта (строка "Login:") из текста файла аккаунта.
+pub fn extract_login(text: &str) -> Option<String> {
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with("Login:") {
+            let login = line.replace("Login:", "").trim().to_string();
+            if !login.is_empty() {
+                return Some(login);
+            }
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountRef {
+    pub name: String,
+    pub path: String,
+}
+
+/// Корневая папка с аккаунтами: одна подпапка — один аккаунт
+/// (`{name}.txt` с данными, скриншоты, `G17*.txt` после выставления).
+#[derive(Debug, Clone)]
+pub struct AccountStore {
+    root: PathBuf,
+    max_depth: usize,
+}
+
+impl AccountStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            max_depth: DEFAULT_SCAN_DEPTH,
+        }
+    }
+
+    /// Глубина поиска аккаунтов во вложенных папках (1 — только подпапки корня).
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Папки аккаунтов с признаком выставления, по группам и имени.
+    pub fn folders(&self) -> Result<AccountsData, String> {
+        load_account_folders(&self.root.to_string_lossy(), self.max_depth)
+    }
+
+    /// Только структура папок, без сводки по файлам аккаунтов
+    /// (см. [`scan_account_folders`]).
+    pub fn scan(&self) -> Result<Vec<AccountFolder>, String> {
+        scan_account_folders(&self.root.to_string_lossy(), self.max_depth)
+    }
+
+    pub fn refs(&self) -> Result<Vec<AccountRef>, String> {
+        Ok(self
+            .folders()?
+            .accounts
+            .into_iter()
+            .map(|a| AccountRef { name: a.name, path: a.path })
+            .collect())
+    }
+
+    /// Выставленные аккаунты и их offer_id.
+    pub fn listed(&self) -> Result<Vec<(AccountRef, String)>, String> {
+        Ok(self
+            .refs()?
+            .into_iter()
+            .filter_map(|account| {
+                let offer_id = find_offer_id(&account.path)?;
+                Some((account, offer_id))
+            })
+            .collect())
+    }
+
+    /// Проверка всех аккаунтов папки (см. [`validate_account`]).
+    pub fn validate(&self) -> Result<Vec<AccountValidation>, String> {
+        Ok(self.refs()?.iter().map(|a| validate_account(&a.path)).collect())
+    }
+}
+
+// Читает логин аккаунта из файла {name}.txt в папке аккаунта.
+// Если файла или строки "Login:" нет — fallback на имя папки (как при выставлении).
+pub fn read_account_login(account: &AccountRef) -> String {
+    let file_path = PathBuf::from(&account.path).join(format!("{}.txt", account.name));
+    if let Ok(content) = fs::read_to_string(&file_path) {
+        if let Some(login) = extract_login(&content) {
+            return login;
+        }
+    }
+    account.name.clone()
+}
+
+pub fn save_offer_id_to_file(account_path: &str, offer_id: &str) -> Result<(), String> {
+    info!("Saving offer_id to file...");
+
+    let path = PathBuf::from(account_path);
+    let file_path = path.join(format!("{}.txt", offer_id));
+
+    let content = format!(
+        "Offer ID: {}\nCreated: {}\nStatus: Live\n",
+        offer_id,
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+
+    fs::write(&file_path, content)
+        .map_err(|e| format!("Failed to save offer_id file: {}", e))?;
+
+    info!("Offer ID saved to: {:?}", file_path);
+    Ok(())
+}
+
+/// Offer id из файла `G17*.txt` в папке аккаунта (если аккаунт уже выставлен).
+pub fn find_offer_id(account_path: &str) -> Option<String> {
+    if let Ok(entries) = fs::read_dir(account_path) {
+        for entry in entries.flatten() {
+            if let Some(filename) = entry.file_name().to_str() {
+                if filename.starts_with("G17") && filename.ends_with(".txt") {
+                    debug!("Found offer file: {}", filename);
+                    return Some(filename.trim_end_matches(".txt").to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+pub fn check_if_listed(account_path: &str) -> bool {
+    find_offer_id(account_path).is_some()
+}
+
+// Хосты, прямые ссылки на которые считаются скриншотами даже без подписи.
+pub const SCREENSHOT_HOSTS: [&str; 4] = ["imgur.com", "gyazo.com", "prnt.sc", "i.postimg.cc"];
+
+// Ссылки на скриншоты в одной строке текста аккаунта.
+fn screenshot_candidates(line: &str) -> Vec<&str> {
+    if line.to_lowercase().contains("screenshot url") {
+        // В строке "Screenshot URL:" может быть несколько ссылок подряд
+        match line.find("http") {
+            Some(http_pos) => line[http_pos..]
+                .split_whitespace()
+                .filter(|part| part.starts_with("http"))
+                .collect(),
+            None => Vec::new(),
+        }
+    } else if line.starts_with("http") && SCREENSHOT_HOSTS.iter().any(|host| line.contains(host)) {
+        line.split_whitespace().take(1).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Есть ли в тексте аккаунта хотя бы одна ссылка на скриншот (без логов).
+pub fn has_screenshot_url(text: &str) -> bool {
+    text.lines().any(|line| !screenshot_candidates(line.trim()).is_empty())
+}
+
+// Собирает все ссылки на скриншоты из текста аккаунта (без дублей, в порядке появления).
+pub fn extract_screenshot_urls(text: &str) -> Vec<String> {
+    debug!("Searching for screenshot URLs in text...");
+
+    let mut urls: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        for url in screenshot_candidates(line.trim()) {
+            let url = url.trim_end_matches([',', ';']);
+            if !urls.iter().any(|u| u == url) {
+                info!("Found screenshot URL: {}", url);
+                urls.push(url.to_string());
+            }
+        }
+    }
+
+    if urls.is_empty() {
+        warn!("No screenshot URL found in text");
+    }
+    urls
+}
+
+// Ссылки из текста аккаунта плюс локальные скриншоты из папки аккаунта,
+// загруженные на image-хост (если он настроен).
+pub async fn collect_offer_images(text: &str, account_path: &str) -> Vec<String> {
+    let mut urls = extract_screenshot_urls(text);
+
+    let host_settings = AppSettings::load().ok().and_then(|s| s.image_host);
+    match host_settings {
+        Some(host) if !host.upload_url.trim().is_empty() => {
+            for url in image_host::upload_account_images(&host, &PathBuf::from(account_path)).await {
+                if !urls.contains(&url) {
+                    urls.push(url);
+                }
+            }
+        }
+        _ => info!("Image host не настроен — локальные скриншоты не загружаются"),
+    }
+
+    info!("Total images for offer: {}", urls.len());
+    urls
+}
+
+/// Папка аккаунта определяется по содержимому: в ней есть `{имя папки}.txt`
+/// со строкой "Login:".
+pub fn is_account_folder(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => name,
+        None => return false,
+    };
+    fs::read_to_string(path.join(format!("{}.txt", name)))
+        .map(|content| extract_login(&content).is_some())
+        .unwrap_or(false)
+}
+
+// Путь папки относительно base через "/" (одинаково на всех ОС).
+fn relative_group(base: &Path, dir: &Path) -> String {
+    dir.strip_prefix(base)
+        .map(|rel| {
+            rel.components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .unwrap_or_default()
+}
+
+// Обходит dir (уровень depth) в поисках папок аккаунтов. В найденные
+// аккаунты не спускается; скрытые папки (".git" и т.п.) пропускает.
+fn scan_account_dirs(base: &Path, dir: &Path, depth: usize, max_depth: usize, accounts: &mut Vec<AccountFolder>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Не удалось прочитать папку {:?}: {}", dir, e);
+            return;
+        }
+    };
+
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                error!("Ошибка чтения записи: {}", e);
+                continue;
+            }
+        };
+        let entry_path = entry.path();
+        let name = match entry_path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if !name.starts_with('.') => name.to_string(),
+            _ => continue,
+        };
+        if !entry_path.is_dir() {
+            continue;
+        }
+
+        if is_account_folder(&entry_path) {
+            let path_str = entry_path.to_string_lossy().to_string();
+            accounts.push(AccountFolder {
+                name,
+                is_listed: check_if_listed(&path_str),
+                path: path_str,
+                group: relative_group(base, dir),
+                summary: AccountSummary::default(),
+            });
+        } else if depth < max_depth {
+            scan_account_dirs(base, &entry_path, depth + 1, max_depth, accounts);
+        }
+    }
+}
+
+/// Папки аккаунтов в folder_path и вложенных папках до глубины max_depth
+/// без чтения их файлов (summary пустая), отсортированные по группе и имени.
+pub fn scan_account_folders(folder_path: &str, max_depth: usize) -> Result<Vec<AccountFolder>, String> {
+    let path = PathBuf::from(folder_path);
+
+    if !path.exists() {
+        return Err("Указанная папка не существует".to_string());
+    }
+
+    if !path.is_dir() {
+        return Err("Указанный путь не является папкой".to_string());
+    }
+
+    fs::read_dir(&path).map_err(|e| format!("Не удалось прочитать содержимое папки: {}", e))?;
+
+    let mut accounts = Vec::new();
+    scan_account_dirs(&path, &path, 1, max_depth.max(1), &mut accounts);
+
+    accounts.sort_by(|a, b| a.group.cmp(&b.group).then_with(|| a.name.cmp(&b.name)));
+    Ok(accounts)
+}
+
+/// Папки аккаунтов в folder_path и вложенных папках до глубины max_depth
+/// (1 — только непосредственные подпапки), сгруппированные по родительской папке.
+pub fn load_account_folders(folder_path: &str, max_depth: usize) -> Result<AccountsData, String> {
+    info!("Loading account folders from: {} (depth {})", folder_path, max_depth);
+
+    let mut accounts = scan_account_folders(folder_path, max_depth)?;
+    account_info::summarize_all(&mut accounts);
+
+    let mut groups: Vec<AccountGroup> = Vec::new();
+    for account in &accounts {
+        if groups.last().map(|g| g.name != account.group).unwrap_or(true) {
+            let group_path = PathBuf::from(&account.path)
+                .parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            groups.push(AccountGroup {
+                name: account.group.clone(),
+                path: group_path,
+                total: 0,
+                listed: 0,
+            });
+        }
+        if let Some(group) = groups.last_mut() {
+            group.total += 1;
+            group.listed += account.is_listed as usize;
+        }
+    }
+
+    let listed_count = accounts.iter().filter(|a| a.is_listed).count();
+    info!(
+        "Найдено {} аккаунтов ({} в продаже) в {} папках",
+        accounts.len(),
+        listed_count,
+        groups.len()
+    );
+
+    Ok(AccountsData {
+        accounts,
+        base_path: folder_path.to_string(),
+        groups,
+    })
+}
+
+pub fn list_account_files(account_path: &str) -> Result<Vec<String>, String> {
+    info!("Getting files from account: {}", account_path);
+
+    let path = PathBuf::from(account_path);
+
+    if !path.exists() || !path.is_dir() {
+        return Err("Папка аккаунта не найдена".to_string());
+    }
+
+    let mut files = Vec::new();
+
+    match fs::read_dir(&path) {
+        Ok(entries) => {
+            for entry in entries {
+                if let Ok(entry) = entry {
+                    if let Some(file_name) = entry.file_name().to_str() {
+                        files.push(file_name.to_string());
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            return Err(format!("Ошибка чтения файлов: {}", e));
+        }
+    }
+
+    Ok(files)
+}
+
+pub fn read_account_file(account_path: &str, file_name: &str) -> Result<String, String> {
+    info!("Reading file '{}' from account: {}", file_name, account_path);
+
+    let account_dir = PathBuf::from(account_path);
+    let file_path = account_dir.join(file_name);
+
+    debug!("Full file path: {:?}", file_path);
+
+    if !file_path.exists() {
+        return Err(format!("Файл не найден: {}", file_name));
+    }
+
+    match fs::read_to_string(&file_path) {
+        Ok(content) => {
+            debug!("Successfully read {} bytes from {}", content.len(), file_name);
+            Ok(content)
+        },
+        Err(e) => Err(format!("Ошибка чтения файла: {}", e)),
+    }
+}
+
+/// Первый PNG-скриншот в папке аккаунта.
+pub fn find_screenshot(account_path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(account_path);
+
+    if !path.exists() || !path.is_dir() {
+        return Err("Папка аккаунта не найдена".to_string());
+    }
+
+    let entries = fs::read_dir(&path).map_err(|e| format!("Ошибка чтения папки аккаунта: {}", e))?;
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        if entry_path.is_file() && entry_path.extension().and_then(|e| e.to_str()) == Some("png") {
+            debug!("Found PNG file: {:?}", entry_path);
+            return Ok(entry_path);
+        }
+    }
+    Err("PNG файлы не найдены в папке аккаунта".to_string())
+}
+
+pub fn parse_account_to_csv(text: &str) -> Result<String, String> {
+    let start_marker = "Hi there,";
+    let start_idx = text.find(start_marker)
+        .ok_or("Could not find 'Hi there,' in account file")?;
+
+    let end_markers = [
+        "Thank you for buying from Accounterra,gl&hf!",
+        "Thank you for buying from Accounterra, gl&hf!",
+        "gl&hf!",
+    ];
+
+    let mut end_idx = None;
+    for marker in &end_markers {
+        if let Some(idx) = text[start_idx..].find(marker) {
+            end_idx = Some(start_idx + idx + marker.len());
+            break;
+        }
+    }
+
+    let description_block = if let Some(end) = end_idx {
+        &text[start_idx..end]
+    } else {
+        if let Some(screenshot_idx) = text[start_idx..].find("Screenshot URL") {
+            text[start_idx..start_idx + screenshot_idx].trim()
+        } else {
+            text[start_idx..].trim()
+        }
+    };
+
+    let mut login = String::new();
+    let mut password = String::new();
+    let mut email = String::new();
+    let mut email_access = String::new();
+
+    for line in description_block.lines() {
+        let line = line.trim();
+        if line.starts_with("Login:") {
+            login = line.replace("Login:", "").trim().to_string();
+        } else if line.starts_with("Password:") {
+            password = line.replace("Password:", "").trim().to_string();
+        } else if line.starts_with("Email is") {
+            email = line.replace("Email is", "").trim().to_string();
+        } else if line.contains("[DOT]com/email/") && !line.starts_with("Email is") {
+            email_access = line.split_whitespace().next().unwrap_or("").to_string();
+        }
+    }
+
+    // Учётные данные аккаунта маскируются во всех логах этой сессии
+    for (field, secret) in [("login", &login), ("password", &password), ("email", &email), ("email_access", &email_access)] {
+        crate::redact::register_secret(&format!("account:{}:{}", login, field), secret);
+    }
+
+    if login.is_empty() || password.is_empty() || email.is_empty() {
+        // Значения полей в ошибку не попадают — только какие из них пусты
+        let missing: Vec<&str> = [("Login", &login), ("Password", &password), ("Email", &email)]
+            .iter()
+            .filter(|(_, value)| value.is_empty())
+            .map(|(name, _)| *name)
+            .collect();
+        return Err(format!("Missing fields: {}", missing.join(", ")));
+    }
+
+    let description_csv_safe = description_block
+        .replace("\"", "\"\"")
+        .replace("\r\n", "\n")
+        .replace("\r", "\n");
+
+    let csv_line = format!(
+        "{},{},,,,,,,,{},{},\"{}\"\r\n",
+        login,
+        password,
+        email,
+        email_access,
+        description_csv_safe
+    );
+
+    let comma_count = csv_line.matches(',').count();
+    info!("CSV created with {} commas", comma_count);
+
+    Ok(csv_line)
+}
+
+/// Результат проверки папки аккаунта перед выставлением.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountValidation {
+    pub name: String,
+    pub path: String,
+    pub login: Option<String>,
+    pub is_listed: bool,
+    pub offer_id: Option<String>,
+    pub screenshot_urls: usize,
+    pub local_images: usize,
+    /// Данные аккаунта читаются и конвертируются в CSV для G2G.
+    pub valid: bool,
+    pub error: Option<String>,
+}
+
+/// Проверяет, что папку аккаунта можно выставить: есть `{name}.txt`,
+/// из него собирается CSV для G2G; плюс сколько скриншотов найдено.
+pub fn validate_account(account_path: &str) -> AccountValidation {
+    let path = PathBuf::from(account_path);
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_string();
+    let offer_id = find_offer_id(account_path);
+    let local_images = image_host::find_local_images(&path).len();
+
+    let content = fs::read_to_string(path.join(format!("{}.txt", name)))
+        .map_err(|e| format!("Не удалось прочитать {}.txt: {}", name, e));
+    let (login, screenshot_urls, error) = match content {
+        Ok(text) => (
+            extract_login(&text),
+            extract_screenshot_urls(&text).len(),
+            parse_account_to_csv(&text).err(),
+        ),
+        Err(e) => (None, 0, Some(e)),
+    };
+
+    AccountValidation {
+        name,
+        path: account_path.to_string(),
+        login,
+        is_listed: offer_id.is_some(),
+        offer_id,
+        screenshot_urls,
+        local_images,
+        valid: error.is_none(),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_store_listed_and_validate() {
+        let root = std::env::temp_dir().join(format!("g2g-app-accounts-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for name in ["acc1", "acc2"] {
+            fs::create_dir_all(root.join(name)).unwrap();
+        }
+        fs::write(
+            root.join("acc1").join("acc1.txt"),
+            "Hi there,\nLogin: store_login\nPassword: store_pass\nEmail is store@example.com\ngl&hf!\n",
+        )
+        .unwrap();
+        fs::write(root.join("acc1").join("G1700000001.txt"), "Offer ID: G1700000001\n").unwrap();
+
+        // Вложенная партия: root/RESELL/16.01.26/acc3; папка без файла аккаунта — не аккаунт
+        let nested = root.join("RESELL").join("16.01.26").join("acc3");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("acc3.txt"), "Login: nested_login\n").unwrap();
+        fs::create_dir_all(root.join("acc2").join("screens")).unwrap();
+
+        let data = AccountStore::new(&root).folders().unwrap();
+        let names: Vec<&str> = data.accounts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["acc1", "acc3"]);
+        assert_eq!(data.accounts[1].group, "RESELL/16.01.26");
+        assert_eq!(data.groups.len(), 2);
+        assert_eq!(data.groups[0].listed, 1);
+        assert_eq!(data.accounts[0].summary.login.as_deref(), Some("store_login"));
+        assert_eq!(data.accounts[0].summary.offer_id.as_deref(), Some("G1700000001"));
+        assert_eq!(AccountStore::new(&root).with_max_depth(2).folders().unwrap().accounts.len(), 1);
+
+        // acc2 с логином, но без пароля и почты: аккаунт, но не проходит проверку
+        fs::write(root.join("acc2").join("acc2.txt"), "Login: broken\n").unwrap();
+        let store = AccountStore::new(&root).with_max_depth(1);
+        let listed = store.listed().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].0.name, "acc1");
+        assert_eq!(listed[0].1, "G1700000001");
+
+        let checks = store.validate().unwrap();
+        assert!(checks[0].valid);
+        assert_eq!(checks[0].login.as_deref(), Some("store_login"));
+        assert!(!checks[1].valid);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,259 @@
+//! Консольная утилита для ночных задач и выставления без UI: те же функции
+//! библиотеки, что и у десктопного приложения, результат — JSON в stdout.
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use g2g_app_lib::accounts::{self, AccountStore};
+use g2g_app_lib::g2g_api::G2GApiClient;
+use g2g_app_lib::listing::{CreateOfferRequest, ListingProgressPayload, ListingService};
+use g2g_app_lib::logging;
+use g2g_app_lib::pricing::{PriceProgressPayload, PriceService, SkinPriceRequest};
+use g2g_app_lib::reconcile;
+use g2g_app_lib::sheet_diagnose;
+use g2g_app_lib::sheet_outbox::SheetOutbox;
+use g2g_app_lib::sheet_sync::SheetSync;
+use g2g_app_lib::sheets::SheetUpdate;
+use g2g_app_lib::watcher::{AccountChange, AccountWatcher, WatchMode};
+
+#[derive(Parser)]
+#[command(name = "g2g-cli", version, about = "G2G listing tool without the desktop UI (JSON output)")]
+struct Cli {
+    /// JSON в одну строку вместо форматированного
+    #[arg(long, global = true)]
+    compact: bool,
+
+    /// Глубина поиска аккаунтов во вложенных папках
+    #[arg(long, global = true, default_value_t = accounts::DEFAULT_SCAN_DEPTH)]
+    depth: usize,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Папки аккаунтов в каталоге и их статус (выставлен / нет)
+    ListFolder { folder: String },
+    /// Проверить папки аккаунтов перед выставлением
+    Validate {
+        /// Папки аккаунтов
+        paths: Vec<String>,
+        /// Проверить все аккаунты каталога
+        #[arg(long)]
+        folder: Option<String>,
+    },
+    /// Цены скинов на G2G
+    PriceSkins {
+        #[arg(long)]
+        server: String,
+        /// Id категории G2G (по умолчанию league-of-legends)
+        #[arg(long)]
+        category: Option<String>,
+        /// Файл со списком скинов, по одному в строке
+        #[arg(long)]
+        skins_file: Option<PathBuf>,
+        skins: Vec<String>,
+    },
+    /// Выставить аккаунт (JSON запроса как у create_g2g_offer; "-" — stdin)
+    CreateOffer {
+        #[arg(long)]
+        request: String,
+    },
+    /// Какие аккаунты каталога уже есть в Google-таблице
+    CheckSheet {
+        folder: String,
+        /// Сравнивать логины без пробелов и с заменой похожих символов
+        #[arg(long)]
+        fuzzy: bool,
+    },
+    /// Дописать в таблицу выставленные аккаунты каталога, которых там нет
+    Sync {
+        folder: String,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Обновить строку таблицы по offer_id или логину
+    UpdateRow {
+        #[arg(long)]
+        offer_id: Option<String>,
+        #[arg(long)]
+        username: Option<String>,
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long)]
+        price: Option<String>,
+        #[arg(long)]
+        sold_date: Option<String>,
+        /// Другие колонки: --set "Колонка=значение" (можно несколько раз)
+        #[arg(long = "set", value_name = "COLUMN=VALUE")]
+        columns: Vec<String>,
+    },
+    /// Сверить выставленные аккаунты каталога с таблицей и офферами на G2G
+    Reconcile {
+        folder: String,
+        /// Не проверять офферы на G2G (только каталог и таблица)
+        #[arg(long)]
+        skip_g2g: bool,
+        /// Применить исправления в таблице
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Проверить настройку веб-хука Apps Script (чек-лист проблем)
+    DiagnoseSheet {
+        /// URL веб-хука; по умолчанию — из настроек
+        #[arg(long)]
+        url: Option<String>,
+        /// Секрет подписи для --url
+        #[arg(long)]
+        secret: Option<String>,
+    },
+    /// Строки таблицы, ожидающие повторной записи
+    Outbox {
+        /// Отправить всю очередь сейчас
+        #[arg(long)]
+        flush: bool,
+    },
+    /// Следить за каталогом: каждая пачка изменений — строка JSON, до Ctrl+C
+    Watch {
+        folder: String,
+        /// Опрашивать папку вместо уведомлений ФС (сетевые диски)
+        #[arg(long)]
+        poll: bool,
+    },
+}
+
+fn read_input(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut input = String::new();
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("Failed to read stdin: {}", e))?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<serde_json::Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize output: {}", e))
+}
+
+fn new_client() -> Arc<Mutex<G2GApiClient>> {
+    Arc::new(Mutex::new(G2GApiClient::new()))
+}
+
+async fn run(command: Command, depth: usize) -> Result<serde_json::Value, String> {
+    let store = |folder: String| AccountStore::new(folder).with_max_depth(depth);
+    match command {
+        Command::ListFolder { folder } => to_json(&store(folder).folders()?),
+        Command::Validate { paths, folder } => {
+            let mut results: Vec<_> = paths.iter().map(|p| accounts::validate_account(p)).collect();
+            if let Some(folder) = folder {
+                results.extend(store(folder).validate()?);
+            }
+            if results.is_empty() {
+                return Err("Не указаны папки аккаунтов (пути или --folder)".to_string());
+            }
+            to_json(&results)
+        }
+        Command::PriceSkins { server, category, skins_file, mut skins } => {
+            if let Some(file) = skins_file {
+                let content = fs::read_to_string(&file)
+                    .map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+                skins.extend(content.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from));
+            }
+            let request = SkinPriceRequest { skins, server, category };
+            let on_progress = Arc::new(|p: PriceProgressPayload| {
+                info!("[{}/{}] {} — {}", p.current, p.total, p.skin_name, p.status);
+            });
+            to_json(&PriceService::new(new_client()).fetch(&request, on_progress).await?)
+        }
+        Command::CreateOffer { request } => {
+            let request: CreateOfferRequest = serde_json::from_str(&read_input(&request)?)
+                .map_err(|e| format!("Некорректный JSON запроса: {}", e))?;
+            let on_progress = Arc::new(|p: ListingProgressPayload| {
+                info!("[{}/{}] {}", p.current, p.total, p.message);
+            });
+            let offer_id = ListingService::new(new_client()).create_offer(&request, on_progress).await?;
+            Ok(serde_json::json!({ "offer_id": offer_id }))
+        }
+        Command::CheckSheet { folder, fuzzy } => {
+            let refs = store(folder).refs()?;
+            to_json(&SheetSync::from_settings()?.check(&refs, fuzzy).await?)
+        }
+        Command::Sync { folder, dry_run } => {
+            to_json(&SheetSync::from_settings()?.sync_listed(&store(folder), dry_run).await?)
+        }
+        Command::UpdateRow { offer_id, username, status, price, sold_date, columns } => {
+            let columns = columns
+                .iter()
+                .map(|pair| {
+                    pair.split_once('=')
+                        .map(|(k, v)| (k.trim().to_string(), v.to_string()))
+                        .ok_or_else(|| format!("Ожидается КОЛОНКА=ЗНАЧЕНИЕ: {}", pair))
+                })
+                .collect::<Result<_, _>>()?;
+            let update = SheetUpdate { offer_id, username, status, price, sold_date, columns };
+            to_json(&SheetSync::from_settings()?.update_row(&update).await?)
+        }
+        Command::Reconcile { folder, skip_g2g, apply } => {
+            let listing = (!skip_g2g).then(|| ListingService::new(new_client()));
+            let sheet = SheetSync::from_settings()?;
+            to_json(&reconcile::reconcile(&store(folder), &sheet, listing.as_ref(), apply).await?)
+        }
+        Command::DiagnoseSheet { url, secret } => {
+            to_json(&sheet_diagnose::diagnose_configured(url.as_deref(), secret.as_deref()).await?)
+        }
+        Command::Outbox { flush } => {
+            let outbox = SheetOutbox::open()?;
+            if flush {
+                to_json(&outbox.flush(&SheetSync::from_settings()?, true).await?)
+            } else {
+                to_json(&outbox.pending()?)
+            }
+        }
+        Command::Watch { folder, poll } => {
+            let store = store(folder);
+            let data = store.folders()?;
+            let on_change = Arc::new(|changes: Vec<AccountChange>| {
+                if let Ok(line) = serde_json::to_string(&changes) {
+                    println!("{}", line);
+                }
+            });
+            let mode = if poll { WatchMode::Poll } else { WatchMode::from_settings() };
+            let _watcher = AccountWatcher::start_with_mode(store, &data.accounts, mode, on_change)?;
+            tokio::signal::ctrl_c()
+                .await
+                .map_err(|e| format!("Failed to wait for Ctrl+C: {}", e))?;
+            Ok(serde_json::json!({ "watched": data.accounts.len() }))
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // Лог пишется в свой файл cli-<время>.log рядом с логами приложения;
+    // stdout — только JSON.
+    let _log_guard = logging::init_cli().map_err(|e| eprintln!("Логирование в файл недоступно: {}", e)).ok();
+
+    let (output, code) = match run(cli.command, cli.depth).await {
+        Ok(value) => (value, ExitCode::SUCCESS),
+        Err(e) => (serde_json::json!({ "error": e }), ExitCode::FAILURE),
+    };
+    let text = if cli.compact {
+        serde_json::to_string(&output)
+    } else {
+        serde_json::to_string_pretty(&output)
+    };
+    println!("{}", text.unwrap_or_else(|e| format!("{{\"error\":\"{}\"}}", e)));
+    code
+}
@@ -0,0 +1,718 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::g2g_api::OfferAttribute;
+use tracing::{info, warn};
+
+/// Id категории по умолчанию (аккаунты League of Legends).
+pub const DEFAULT_CATEGORY_ID: &str = "league-of-legends";
+
+/// Версия формата файла категории, которую понимает приложение.
+pub const CATEGORY_FORMAT_VERSION: u32 = 1;
+
+// Встроенное описание категории LoL — используется, если в директории
+// конфигурации нет собственного файла categories/league-of-legends.json.
+const BUILTIN_LOL_CATEGORY: &str = include_str!("../data/categories/league-of-legends.json");
+
+/// Значения полей листинга, по которым выбираются атрибуты оффера
+/// ("server", "rank", "champions_count", "skins_count" и любые другие).
+pub type ListingValues = HashMap<String, String>;
+
+/// Категория G2G (игра + тип товара): бренд, сервис, SEO-термин поиска
+/// и коллекции атрибутов с маппингом значений листинга на dataset id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryDefinition {
+    /// Версия формата файла (см. CATEGORY_FORMAT_VERSION).
+    #[serde(default = "default_format_version")]
+    pub format_version: u32,
+    /// Ревизия маппингов: увеличивается при каждом обновлении из G2G.
+    #[serde(default)]
+    pub revision: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    pub id: String,
+    pub name: String,
+    pub brand_id: String,
+    pub service_id: String,
+    pub seo_term: String,
+    /// Путь API G2G с определениями атрибутов категории ({brand_id} и
+    /// {service_id} подставляются). Можно поменять, если G2G изменит API.
+    #[serde(default = "default_attributes_endpoint")]
+    pub attributes_endpoint: String,
+    pub attributes: Vec<AttributeCollection>,
+}
+
+fn default_format_version() -> u32 {
+    CATEGORY_FORMAT_VERSION
+}
+
+fn default_attributes_endpoint() -> String {
+    "/offer/attributes?brand_id={brand_id}&service_id={service_id}".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeCollection {
+    pub collection_id: String,
+    pub name: String,
+    /// Поле листинга, по которому выбирается dataset. Без поля атрибут
+    /// всегда получает default_dataset (например, тип "Account").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(default)]
+    pub match_by: MatchBy,
+    /// Добавлять ли атрибут в filter_attr при поиске цен.
+    #[serde(default)]
+    pub use_in_search: bool,
+    pub default_dataset: String,
+    pub datasets: Vec<DatasetMapping>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchBy {
+    /// Значение поля сравнивается с aliases без учёта регистра.
+    #[default]
+    Alias,
+    /// Числовое поле: берётся dataset с наибольшим min, не превышающим значение.
+    MinValue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetMapping {
+    pub dataset_id: String,
+    /// Название значения в G2G (заполняется при обновлении из G2G).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+}
+
+/// Краткая информация о категории для выбора в интерфейсе.
+#[derive(Debug, Serialize)]
+pub struct CategorySummary {
+    pub id: String,
+    pub name: String,
+}
+
+impl AttributeCollection {
+    // Выбрать dataset id для значений листинга (или default_dataset)
+    pub fn resolve(&self, values: &ListingValues) -> &str {
+        let value = match self.field.as_ref().and_then(|f| values.get(f)) {
+            Some(v) => v.trim(),
+            None => return &self.default_dataset,
+        };
+
+        let found = match self.match_by {
+            MatchBy::Alias => {
+                let wanted = value.to_uppercase();
+                self.datasets
+                    .iter()
+                    .find(|d| d.aliases.iter().any(|a| a.to_uppercase() == wanted))
+            }
+            MatchBy::MinValue => match value.parse::<i64>() {
+                Ok(number) => self
+                    .datasets
+                    .iter()
+                    .filter(|d| d.min.map(|min| number >= min).unwrap_or(false))
+                    .max_by_key(|d| d.min),
+                Err(_) => None,
+            },
+        };
+
+        found.map(|d| d.dataset_id.as_str()).unwrap_or(&self.default_dataset)
+    }
+}
+
+impl CategoryDefinition {
+    pub fn builtin() -> Self {
+        serde_json::from_str(BUILTIN_LOL_CATEGORY).expect("built-in category JSON is valid")
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.format_version > CATEGORY_FORMAT_VERSION {
+            return Err(format!(
+                "Category '{}' uses format version {}, this app supports up to {}",
+                self.id, self.format_version, CATEGORY_FORMAT_VERSION
+            ));
+        }
+        if self.id.trim().is_empty() || self.brand_id.trim().is_empty() || self.service_id.trim().is_empty() {
+            return Err(format!("Category '{}': id, brand_id and service_id are required", self.id));
+        }
+        check_category_id(&self.id)?;
+        for attr in &self.attributes {
+            if attr.collection_id.trim().is_empty() || attr.default_dataset.trim().is_empty() {
+                return Err(format!(
+                    "Category '{}': attribute '{}' needs collection_id and default_dataset",
+                    self.id, attr.name
+                ));
+            }
+            if attr.match_by == MatchBy::MinValue && attr.datasets.iter().any(|d| d.min.is_none()) {
+                return Err(format!(
+                    "Category '{}': attribute '{}' matches by min_value but has datasets without min",
+                    self.id, attr.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Атрибуты оффера для G2G по значениям листинга.
+    pub fn offer_attributes(&self, values: &ListingValues) -> Vec<OfferAttribute> {
+        self.attributes
+            .iter()
+            .map(|attr| OfferAttribute {
+                collection_id: attr.collection_id.clone(),
+                dataset_id: attr.resolve(values).to_string(),
+            })
+            .collect()
+    }
+
+    /// Значение filter_attr для поиска офферов (уже URL-кодированное, через %7C).
+    pub fn search_filter(&self, values: &ListingValues) -> String {
+        self.attributes
+            .iter()
+            .filter(|attr| attr.use_in_search)
+            .map(|attr| format!("{}:{}", attr.collection_id, attr.resolve(values)))
+            .collect::<Vec<_>>()
+            .join("%7C")
+    }
+}
+
+/// Значения листинга для стандартных полей аккаунта LoL плюс дополнительные
+/// поля (для категорий других игр).
+pub fn listing_values(
+    server: &str,
+    rank: &str,
+    champions_count: i32,
+    skins_count: i32,
+    extra: Option<&HashMap<String, String>>,
+) -> ListingValues {
+    let mut values = ListingValues::new();
+    values.insert("server".to_string(), server.to_string());
+    values.insert("rank".to_string(), rank.to_string());
+    values.insert("champions_count".to_string(), champions_count.to_string());
+    values.insert("skins_count".to_string(), skins_count.to_string());
+    if let Some(extra) = extra {
+        for (key, value) in extra {
+            values.insert(key.clone(), value.clone());
+        }
+    }
+    values
+}
+
+// Директория с пользовательскими описаниями категорий
+fn categories_dir() -> Result<PathBuf, String> {
+    Ok(config::config_dir()?.join("categories"))
+}
+
+// Id категории становится именем файла {id}.json — только [A-Za-z0-9_-],
+// чтобы "../settings" не читал и не перезаписывал файлы вне categories.
+fn check_category_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Недопустимый id категории: {}", id));
+    }
+    Ok(())
+}
+
+/// Загружает категорию по id: сначала из {config}/categories/{id}.json,
+/// для категории по умолчанию — встроенное описание.
+pub fn load_category(id: Option<&str>) -> Result<CategoryDefinition, String> {
+    let id = id.map(str::trim).filter(|s| !s.is_empty()).unwrap_or(DEFAULT_CATEGORY_ID);
+    check_category_id(id)?;
+
+    let file_path = categories_dir()?.join(format!("{}.json", id));
+    if file_path.exists() {
+        let content = fs::read_to_string(&file_path)
+            .map_err(|e| format!("Failed to read category file {:?}: {}", file_path, e))?;
+        let category: CategoryDefinition = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse category file {:?}: {}", file_path, e))?;
+        category.validate()?;
+        info!("Category '{}' loaded from {:?}", id, file_path);
+        return Ok(category);
+    }
+
+    if id == DEFAULT_CATEGORY_ID {
+        return Ok(CategoryDefinition::builtin());
+    }
+
+    Err(format!("Категория '{}' не найдена (ожидался файл {:?})", id, file_path))
+}
+
+/// Все доступные категории: встроенная плюс файлы из {config}/categories.
+pub fn list_categories() -> Result<Vec<CategorySummary>, String> {
+    let builtin = CategoryDefinition::builtin();
+    let mut categories = vec![CategorySummary {
+        id: builtin.id,
+        name: builtin.name,
+    }];
+
+    if let Ok(entries) = fs::read_dir(categories_dir()?) {
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let parsed = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<CategoryDefinition>(&content).ok());
+            match parsed {
+                Some(category) => {
+                    categories.retain(|c| c.id != category.id);
+                    categories.push(CategorySummary {
+                        id: category.id,
+                        name: category.name,
+                    });
+                }
+                None => warn!("Skipping invalid category file {:?}", path),
+            }
+        }
+    }
+
+    Ok(categories)
+}
+
+/// Коллекция атрибутов в том виде, в каком её отдаёт G2G.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteCollection {
+    pub collection_id: String,
+    pub name: Option<String>,
+    pub datasets: Vec<RemoteDataset>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteDataset {
+    pub dataset_id: String,
+    pub label: Option<String>,
+}
+
+/// Одно расхождение между локальным маппингом и определениями G2G.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MappingChange {
+    /// В G2G появилось значение, которого нет в маппинге.
+    DatasetAdded { collection_id: String, dataset_id: String, label: Option<String> },
+    /// Значение из маппинга больше не существует в G2G.
+    DatasetRemoved { collection_id: String, dataset_id: String },
+    /// У того же значения (совпало название, алиас или нижняя граница
+    /// бакета) сменился dataset id.
+    DatasetIdChanged { collection_id: String, label: String, old_id: String, new_id: String },
+    /// default_dataset коллекции больше не существует в G2G — маппинг
+    /// нужно поправить вручную, такое обновление не применяется.
+    DefaultDatasetRemoved { collection_id: String, dataset_id: String },
+    /// Коллекции из маппинга нет в ответе G2G.
+    CollectionMissing { collection_id: String },
+    /// В G2G есть коллекция, которую категория не использует.
+    CollectionUnmapped { collection_id: String, name: Option<String> },
+}
+
+#[derive(Debug, Serialize)]
+pub struct MappingDiffReport {
+    pub category_id: String,
+    pub old_revision: u32,
+    pub new_revision: u32,
+    pub changes: Vec<MappingChange>,
+    pub applied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saved_to: Option<String>,
+}
+
+// Первое строковое поле из списка возможных имён
+fn string_field(obj: &serde_json::Map<String, serde_json::Value>, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|k| obj.get(*k).and_then(|v| v.as_str()))
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Извлекает коллекции атрибутов из ответа G2G. Формат ответа не
+/// документирован, поэтому ищем рекурсивно объекты с collection_id,
+/// у которых есть массив объектов с dataset_id.
+pub fn parse_remote_collections(json: &serde_json::Value) -> Vec<RemoteCollection> {
+    let mut collections = Vec::new();
+    collect_remote(json, &mut collections);
+    collections
+}
+
+fn collect_remote(value: &serde_json::Value, out: &mut Vec<RemoteCollection>) {
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_remote(item, out);
+            }
+        }
+        serde_json::Value::Object(obj) => {
+            if let Some(collection_id) = string_field(obj, &["collection_id"]) {
+                let datasets: Vec<RemoteDataset> = obj
+                    .values()
+                    .filter_map(|v| v.as_array())
+                    .flatten()
+                    .filter_map(|d| d.as_object())
+                    .filter_map(|d| {
+                        Some(RemoteDataset {
+                            dataset_id: string_field(d, &["dataset_id"])?,
+                            label: string_field(d, &["value", "label", "dataset_name", "name"]),
+                        })
+                    })
+                    .collect();
+                if !datasets.is_empty() {
+                    out.push(RemoteCollection {
+                        collection_id,
+                        name: string_field(obj, &["collection_name", "label", "name"]),
+                        datasets,
+                    });
+                    return;
+                }
+            }
+            for v in obj.values() {
+                collect_remote(v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Нижняя граница числового бакета из названия: "100 - 129" → 100, "1000+" → 1000
+fn min_from_label(label: &str) -> Option<i64> {
+    let digits: String = label
+        .trim()
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+// Одно и то же значение атрибута у локального маппинга и у G2G: название
+// из G2G совпадает с сохранённым названием или одним из алиасов, а для
+// числовых бакетов — нижняя граница из названия совпадает с min.
+fn same_dataset(match_by: MatchBy, local: &DatasetMapping, remote: &RemoteDataset) -> bool {
+    let remote_label = match remote.label.as_deref() {
+        Some(label) => label.trim(),
+        None => return false,
+    };
+    if local.label.as_deref().map(str::trim) == Some(remote_label) {
+        return true;
+    }
+    match match_by {
+        MatchBy::Alias => local.aliases.iter().any(|a| a.trim().eq_ignore_ascii_case(remote_label)),
+        MatchBy::MinValue => local.min.is_some() && local.min == min_from_label(remote_label),
+    }
+}
+
+/// Сравнивает маппинг категории с определениями G2G и возвращает обновлённую
+/// категорию (с применёнными изменениями) и список расхождений.
+pub fn diff_with_remote(
+    category: &CategoryDefinition,
+    remote: &[RemoteCollection],
+) -> (CategoryDefinition, Vec<MappingChange>) {
+    let mut updated = category.clone();
+    let mut changes = Vec::new();
+
+    for attr in updated.attributes.iter_mut() {
+        let remote_collection = match remote.iter().find(|c| c.collection_id == attr.collection_id) {
+            Some(c) => c,
+            None => {
+                changes.push(MappingChange::CollectionMissing {
+                    collection_id: attr.collection_id.clone(),
+                });
+                continue;
+            }
+        };
+
+        let mut kept: Vec<DatasetMapping> = Vec::new();
+        for local in &attr.datasets {
+            if let Some(found) = remote_collection.datasets.iter().find(|d| d.dataset_id == local.dataset_id) {
+                let mut local = local.clone();
+                if found.label.is_some() {
+                    local.label = found.label.clone();
+                }
+                kept.push(local);
+                continue;
+            }
+
+            // Тот же бакет под новым id: совпадает название, один из алиасов
+            // или нижняя граница числового бакета. Id, которые уже есть
+            // в маппинге, не считаем переименованием.
+            let renamed = remote_collection.datasets.iter().find(|d| {
+                !attr.datasets.iter().any(|other| other.dataset_id == d.dataset_id)
+                    && same_dataset(attr.match_by, local, d)
+            });
+            match renamed {
+                Some(found) => {
+                    changes.push(MappingChange::DatasetIdChanged {
+                        collection_id: attr.collection_id.clone(),
+                        label: found.label.clone().unwrap_or_default(),
+                        old_id: local.dataset_id.clone(),
+                        new_id: found.dataset_id.clone(),
+                    });
+                    if attr.default_dataset == local.dataset_id {
+                        attr.default_dataset = found.dataset_id.clone();
+                    }
+                    let mut local = local.clone();
+                    local.dataset_id = found.dataset_id.clone();
+                    if found.label.is_some() {
+                        local.label = found.label.clone();
+                    }
+                    kept.push(local);
+                }
+                None => changes.push(MappingChange::DatasetRemoved {
+                    collection_id: attr.collection_id.clone(),
+                    dataset_id: local.dataset_id.clone(),
+                }),
+            }
+        }
+
+        for remote_dataset in &remote_collection.datasets {
+            if kept.iter().any(|d| d.dataset_id == remote_dataset.dataset_id) {
+                continue;
+            }
+            changes.push(MappingChange::DatasetAdded {
+                collection_id: attr.collection_id.clone(),
+                dataset_id: remote_dataset.dataset_id.clone(),
+                label: remote_dataset.label.clone(),
+            });
+            let (aliases, min) = match attr.match_by {
+                MatchBy::Alias => (remote_dataset.label.iter().cloned().collect(), None),
+                MatchBy::MinValue => (vec![], remote_dataset.label.as_deref().and_then(min_from_label)),
+            };
+            // Числовой бакет без распознанной границы не добавляем —
+            // он попадёт только в отчёт, иначе файл не пройдёт validate().
+            if attr.match_by == MatchBy::MinValue && min.is_none() {
+                continue;
+            }
+            kept.push(DatasetMapping {
+                dataset_id: remote_dataset.dataset_id.clone(),
+                label: remote_dataset.label.clone(),
+                aliases,
+                min,
+            });
+        }
+
+        if !kept.iter().any(|d| d.dataset_id == attr.default_dataset) {
+            changes.push(MappingChange::DefaultDatasetRemoved {
+                collection_id: attr.collection_id.clone(),
+                dataset_id: attr.default_dataset.clone(),
+            });
+        }
+
+        attr.datasets = kept;
+    }
+
+    for remote_collection in remote {
+        if !category.attributes.iter().any(|a| a.collection_id == remote_collection.collection_id) {
+            changes.push(MappingChange::CollectionUnmapped {
+                collection_id: remote_collection.collection_id.clone(),
+                name: remote_collection.name.clone(),
+            });
+        }
+    }
+
+    (updated, changes)
+}
+
+// Записать файл категории атомарно: временный файл рядом, затем rename,
+// чтобы оборванная запись не оставила битый {id}.json
+fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    let temp_path = path.with_extension("json.tmp");
+    {
+        let mut file = fs::File::create(&temp_path)
+            .map_err(|e| format!("Failed to create temp category file: {}", e))?;
+        file.write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to write category file: {}", e))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to flush category file: {}", e))?;
+    }
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to replace category file: {}", e))
+}
+
+/// Сверяет маппинг категории с ответом G2G. При apply = true и наличии
+/// изменений сохраняет новую ревизию в {config}/categories/{id}.json
+/// (кроме случая, когда в G2G пропал default_dataset какой-либо коллекции).
+/// Отчёт о расхождениях всегда пишется в {config}/categories/reports/
+/// ({id}.diff-<время>.json) — не рядом с категориями, которые читает
+/// list_categories.
+pub fn refresh_from_remote(
+    category: &CategoryDefinition,
+    remote_json: &serde_json::Value,
+    apply: bool,
+) -> Result<MappingDiffReport, String> {
+    // Путь файла и отчёта строится из id
+    check_category_id(&category.id)?;
+    let remote = parse_remote_collections(remote_json);
+    if remote.is_empty() {
+        return Err("Ответ G2G не содержит определений атрибутов (collection_id/dataset_id)".to_string());
+    }
+
+    let (mut updated, changes) = diff_with_remote(category, &remote);
+    let now = chrono::Local::now();
+
+    let mut report = MappingDiffReport {
+        category_id: category.id.clone(),
+        old_revision: category.revision,
+        new_revision: category.revision,
+        changes,
+        applied: false,
+        saved_to: None,
+    };
+
+    info!(
+        "Category '{}' mapping check: {} change(s)",
+        category.id,
+        report.changes.len()
+    );
+
+    if report.changes.is_empty() {
+        return Ok(report);
+    }
+
+    let dir = categories_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create categories directory: {}", e))?;
+
+    let default_removed = report
+        .changes
+        .iter()
+        .any(|c| matches!(c, MappingChange::DefaultDatasetRemoved { .. }));
+    if apply && default_removed {
+        warn!(
+            "Category '{}': default dataset removed in G2G, mapping update not applied",
+            category.id
+        );
+    } else if apply {
+        updated.revision = category.revision + 1;
+        updated.updated_at = Some(now.format("%Y-%m-%d %H:%M:%S").to_string());
+        updated.validate()?;
+
+        let path = dir.join(format!("{}.json", category.id));
+        let json = serde_json::to_string_pretty(&updated)
+            .map_err(|e| format!("Failed to serialize category: {}", e))?;
+        write_atomic(&path, &json)?;
+
+        report.new_revision = updated.revision;
+        report.applied = true;
+        report.saved_to = Some(path.to_string_lossy().to_string());
+        info!("Category '{}' updated to revision {}", category.id, updated.revision);
+    }
+
+    let reports_dir = dir.join("reports");
+    let report_path = reports_dir.join(format!("{}.diff-{}.json", category.id, now.format("%Y%m%d-%H%M%S")));
+    let report_json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize diff report: {}", e))?;
+    if let Err(e) = fs::create_dir_all(&reports_dir).and_then(|_| fs::write(&report_path, report_json)) {
+        warn!("Failed to save diff report: {}", e);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lol_mappings() {
+        let category = CategoryDefinition::builtin();
+        assert!(category.validate().is_ok());
+        for id in ["../settings", "a/b", "..\\settings", "lol.json"] {
+            let error = load_category(Some(id)).unwrap_err();
+            assert!(error.contains("Недопустимый id"), "{}: {}", id, error);
+        }
+        let mut escaped = category.clone();
+        escaped.id = "../settings".to_string();
+        assert!(escaped.validate().is_err());
+
+        let values = listing_values("euw1", "Gold", 165, 120, None);
+        let attrs: Vec<(String, String)> = category
+            .offer_attributes(&values)
+            .into_iter()
+            .map(|a| (a.collection_id, a.dataset_id))
+            .collect();
+        assert_eq!(
+            attrs,
+            vec![
+                ("e80c30d1".to_string(), "304244a1".to_string()),
+                ("319340f0".to_string(), "65ec9642".to_string()),
+                ("eb7040e2".to_string(), "55077770".to_string()),
+                ("04862150".to_string(), "3ee17abb".to_string()),
+                ("962f619a".to_string(), "70f8019b".to_string()),
+            ]
+        );
+
+        let unknown = listing_values("Mars", "Wood", 5, 0, None);
+        assert_eq!(category.search_filter(&unknown), "e80c30d1:1a87dd85%7C319340f0:65ec9642");
+        assert_eq!(category.offer_attributes(&unknown)[2].dataset_id, "dc514fdf");
+    }
+
+    #[test]
+    fn test_diff_with_remote() {
+        let mut category = CategoryDefinition::builtin();
+        category.attributes.retain(|a| a.collection_id == "962f619a" || a.collection_id == "eb7040e2");
+
+        let remote = serde_json::json!({
+            "code": 2000,
+            "payload": { "collections": [
+                { "collection_id": "962f619a", "collection_name": "Skins", "dataset": [
+                    { "dataset_id": "new00000", "value": "0 - 9" },
+                    { "dataset_id": "4be5718c", "value": "10 - 49" },
+                    { "dataset_id": "c1721794", "value": "50 - 99" },
+                    { "dataset_id": "70f8019b", "value": "100 - 299" },
+                    { "dataset_id": "bbe13228", "value": "300 - 499" },
+                    { "dataset_id": "32895a53", "value": "500 - 999" },
+                    { "dataset_id": "da83ec6e", "value": "1000 - 1999" },
+                    { "dataset_id": "add00000", "value": "2000+" }
+                ]},
+                { "collection_id": "eb7040e2", "collection_name": "Rank", "dataset": [
+                    { "dataset_id": "dc514fdf", "value": "Unranked" },
+                    { "dataset_id": "64174ec3", "value": "Iron" },
+                    { "dataset_id": "bf08fd78", "value": "Bronze" },
+                    { "dataset_id": "405dc68e", "value": "Silver" },
+                    { "dataset_id": "gold0000", "value": "Gold" },
+                    { "dataset_id": "8a193251", "value": "Platinum" },
+                    { "dataset_id": "297e3197", "value": "Emerald" },
+                    { "dataset_id": "3b06cc4c", "value": "Diamond" },
+                    { "dataset_id": "08f99b44", "value": "Master" },
+                    { "dataset_id": "447eb997", "value": "Grandmaster" },
+                    { "dataset_id": "99197149", "value": "Challenger" }
+                ]},
+                { "collection_id": "aa11bb22", "collection_name": "Loot", "dataset": [
+                    { "dataset_id": "x1", "value": "Yes" }
+                ]}
+            ]}
+        });
+
+        let collections = parse_remote_collections(&remote);
+        assert_eq!(collections.len(), 3);
+
+        let (updated, changes) = diff_with_remote(&category, &collections);
+        assert_eq!(changes.len(), 4);
+        assert!(matches!(&changes[0], MappingChange::DatasetIdChanged { new_id, .. } if new_id == "gold0000"));
+        assert!(matches!(&changes[1], MappingChange::DatasetIdChanged { new_id, .. } if new_id == "new00000"));
+        assert!(matches!(&changes[2], MappingChange::DatasetAdded { dataset_id, .. } if dataset_id == "add00000"));
+        assert!(matches!(&changes[3], MappingChange::CollectionUnmapped { collection_id, .. } if collection_id == "aa11bb22"));
+
+        // Алиасы переименованного значения сохраняются
+        let rank = &updated.attributes[0];
+        let mut values = ListingValues::new();
+        values.insert("rank".to_string(), "gold".to_string());
+        assert_eq!(rank.resolve(&values), "gold0000");
+
+        let skins = &updated.attributes[1];
+        assert_eq!(skins.default_dataset, "new00000");
+        assert!(updated.validate().is_ok());
+        values.insert("skins_count".to_string(), "2500".to_string());
+        assert_eq!(skins.resolve(&values), "add00000");
+
+        // Пропавший default_dataset попадает в отчёт отдельным изменением
+        let without_default = serde_json::json!([
+            { "collection_id": "eb7040e2", "dataset": [{ "dataset_id": "64174ec3", "value": "Iron" }] }
+        ]);
+        let (_, changes) = diff_with_remote(&category, &parse_remote_collections(&without_default));
+        assert!(changes.iter().any(
+            |c| matches!(c, MappingChange::DefaultDatasetRemoved { dataset_id, .. } if dataset_id == "dc514fdf")
+        ));
+    }
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::g2g_api::{DeliverySpeed, SalesTerritory};
+use crate::watcher::WatchMode;
+use tracing::{info, warn};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct G2GSettings {
@@ -10,10 +15,237 @@ pub struct G2GSettings {
     pub active_device_token: String,
 }
 
+/// Способы доступа к таблице (SheetsSettings::backend).
+pub const SHEETS_BACKEND_WEBHOOK: &str = "webhook";
+pub const SHEETS_BACKEND_API: &str = "api";
+pub const SHEETS_BACKEND_LOCAL: &str = "local";
+
+/// Минимальная длина секрета веб-хука таблицы.
+pub const MIN_WEBHOOK_SECRET_LEN: usize = 16;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SheetsSettings {
+    /// "webhook" — веб-хук Apps Script (по умолчанию), "api" — Google Sheets API v4
+    /// с ключом сервисного аккаунта, "local" — только файл ledger_path.
+    #[serde(default = "default_sheets_backend")]
+    pub backend: String,
     /// URL веб-хука Google Apps Script, в который отправляется строка после выставления.
+    #[serde(default)]
     pub webhook_url: String,
+    /// Общий секрет для подписи запросов к веб-хуку (HMAC); тот же секрет
+    /// задаётся в свойстве скрипта WEBHOOK_SECRET. Пусто — без подписи.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    /// Id таблицы из её URL (.../spreadsheets/d/<id>/edit) — для "api".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spreadsheet_id: Option<String>,
+    /// Путь к JSON-ключу сервисного аккаунта Google — для "api".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_account_key: Option<String>,
+    /// Локальный файл-журнал (.csv или .xlsx): для "local" — вместо таблицы,
+    /// для остальных — копия строк параллельно с таблицей.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ledger_path: Option<String>,
+    /// Дополнительные колонки, которые заполняются при выставлении.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<SheetColumn>,
+}
+
+/// Дополнительная колонка таблицы: какое поле выставления в неё писать.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SheetColumn {
+    /// Поле из sheet_sync::SHEET_FIELDS (price, server, rank, title, ...).
+    pub field: String,
+    /// Заголовок колонки в таблице (регистр не важен).
+    pub header: String,
+}
+
+fn default_sheets_backend() -> String {
+    SHEETS_BACKEND_WEBHOOK.to_string()
+}
+
+impl SheetsSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_columns()?;
+        let filled = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+        if self.webhook_secret.as_deref().is_some_and(|s| !s.trim().is_empty() && s.trim().len() < MIN_WEBHOOK_SECRET_LEN) {
+            return Err(format!("Секрет веб-хука должен быть не короче {} символов", MIN_WEBHOOK_SECRET_LEN));
+        }
+        if let Some(path) = self.ledger_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            crate::ledger::LedgerFormat::from_path(std::path::Path::new(path))?;
+        }
+        match self.backend.as_str() {
+            SHEETS_BACKEND_WEBHOOK if self.webhook_url.trim().is_empty() => {
+                Err("Google Sheets webhook не настроен".to_string())
+            }
+            SHEETS_BACKEND_WEBHOOK => Ok(()),
+            SHEETS_BACKEND_API if !filled(&self.spreadsheet_id) => {
+                Err("Для Google Sheets API нужен id таблицы (spreadsheet_id)".to_string())
+            }
+            SHEETS_BACKEND_API if !filled(&self.service_account_key) => {
+                Err("Для Google Sheets API нужен ключ сервисного аккаунта (service_account_key)".to_string())
+            }
+            SHEETS_BACKEND_API => Ok(()),
+            SHEETS_BACKEND_LOCAL if !filled(&self.ledger_path) => {
+                Err("Для локального журнала нужен путь к файлу (ledger_path)".to_string())
+            }
+            SHEETS_BACKEND_LOCAL => Ok(()),
+            other => Err(format!("Неизвестный способ доступа к таблице: {}", other)),
+        }
+    }
+
+    /// Регистрирует URL веб-хука (любой, кто его знает, может писать в таблицу
+    /// без секрета) и секрет подписи для маскирования в логах.
+    pub fn register_secrets(&self) {
+        crate::redact::register_secret("sheets.webhook_url", &self.webhook_url);
+        crate::redact::register_secret("sheets.webhook_secret", self.webhook_secret.as_deref().unwrap_or_default());
+    }
+
+    pub fn validate_columns(&self) -> Result<(), String> {
+        let mut headers = std::collections::HashSet::new();
+        for column in &self.columns {
+            if !crate::sheet_sync::SHEET_FIELDS.contains(&column.field.as_str()) {
+                return Err(format!(
+                    "Неизвестное поле колонки таблицы: {} (доступны: {})",
+                    column.field,
+                    crate::sheet_sync::SHEET_FIELDS.join(", ")
+                ));
+            }
+            if column.header.trim().is_empty() {
+                return Err(format!("Не указан заголовок колонки для поля {}", column.field));
+            }
+            // Два поля в одну колонку: одно молча затёрло бы другое
+            if !headers.insert(column.header.trim().to_lowercase()) {
+                return Err(format!("Колонка {} указана в настройках дважды", column.header.trim()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Image-хост для загрузки локальных скриншотов из папки аккаунта.
+/// Любой сервис, принимающий multipart POST и отвечающий JSON с URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageHostSettings {
+    /// URL загрузки, например https://api.imgur.com/3/image.
+    pub upload_url: String,
+    /// Имя multipart-поля с файлом.
+    #[serde(default = "default_image_field")]
+    pub file_field: String,
+    /// Значение заголовка Authorization (например, "Client-ID ...").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_header: Option<String>,
+    /// JSON Pointer к URL картинки в ответе хоста.
+    #[serde(default = "default_image_url_pointer")]
+    pub url_pointer: String,
+}
+
+fn default_image_field() -> String {
+    "image".to_string()
+}
+
+fn default_image_url_pointer() -> String {
+    "/data/link".to_string()
+}
+
+/// Настройки генерации заголовка и описания по шаблонам
+/// ({config}/templates/title.hbs, title-<variant>.hbs, description.hbs).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingTextSettings {
+    /// Вариант шаблона заголовка для A/B (title-<variant>.hbs); пусто — title.hbs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_variant: Option<String>,
+    #[serde(default = "default_title_max_length")]
+    pub title_max_length: usize,
+}
+
+fn default_title_max_length() -> usize {
+    crate::listing_text::DEFAULT_TITLE_MAX_LENGTH
+}
+
+/// Хранилище статистики использования чемпионов/скинов в заголовках.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageSettings {
+    /// Путь к файлу статистики (например, на общем диске команды);
+    /// пусто — title_usage.json в директории конфигурации.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store_path: Option<String>,
+    /// Через сколько дней вес использования уменьшается вдвое (0 — без затухания).
+    #[serde(default = "default_half_life_days")]
+    pub half_life_days: f64,
+}
+
+fn default_half_life_days() -> f64 {
+    crate::usage::DEFAULT_HALF_LIFE_DAYS
+}
+
+/// Наблюдение за папкой аккаунтов.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatcherSettings {
+    /// "auto" — опрос для сетевых путей, иначе уведомления ФС; "native" или
+    /// "poll" — принудительно. На сетевом диске уведомления не приходят
+    /// о файлах, изменённых с других машин.
+    #[serde(default)]
+    pub mode: WatchMode,
+}
+
+/// Шаблон параметров оффера, которые не зависят от конкретного аккаунта:
+/// доставка, количество, территория продаж и оптовые цены.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfferTemplateSettings {
+    /// "instant" или "manual". Для "manual" нужны delivery_speed_details.
+    #[serde(default = "default_delivery_speed")]
+    pub delivery_speed: String,
+    #[serde(default)]
+    pub delivery_speed_details: Vec<DeliverySpeed>,
+    #[serde(default = "default_qty")]
+    pub qty: i32,
+    #[serde(default = "default_qty")]
+    pub min_qty: i32,
+    #[serde(default)]
+    pub low_stock_alert_qty: i32,
+    /// settings_type "global" — без ограничений; любой другой тип
+    /// (например, "restricted") требует непустой список countries.
+    #[serde(default = "default_sales_territory")]
+    pub sales_territory: SalesTerritory,
+    /// Оптовые цены передаются в G2G как есть.
+    #[serde(default)]
+    pub wholesale_details: Vec<serde_json::Value>,
+}
+
+/// Переопределение шаблона для одного выставления: заданные поля
+/// заменяют значения из настроек, остальные берутся из шаблона.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OfferTemplateOverride {
+    #[serde(default)]
+    pub delivery_speed: Option<String>,
+    #[serde(default)]
+    pub delivery_speed_details: Option<Vec<DeliverySpeed>>,
+    #[serde(default)]
+    pub qty: Option<i32>,
+    #[serde(default)]
+    pub min_qty: Option<i32>,
+    #[serde(default)]
+    pub low_stock_alert_qty: Option<i32>,
+    #[serde(default)]
+    pub sales_territory: Option<SalesTerritory>,
+    #[serde(default)]
+    pub wholesale_details: Option<Vec<serde_json::Value>>,
+}
+
+fn default_delivery_speed() -> String {
+    "instant".to_string()
+}
+
+fn default_qty() -> i32 {
+    1
+}
+
+fn default_sales_territory() -> SalesTerritory {
+    SalesTerritory {
+        settings_type: "global".to_string(),
+        countries: vec![],
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +255,16 @@ pub struct AppSettings {
     pub theme: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sheets: Option<SheetsSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer_template: Option<OfferTemplateSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_host: Option<ImageHostSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listing_text: Option<ListingTextSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watcher: Option<WatcherSettings>,
 }
 
 impl G2GSettings {
@@ -41,68 +283,182 @@ impl G2GSettings {
         }
         Ok(())
     }
+
+    /// Регистрирует токены для маскирования в логах (заменяя прежние).
+    pub fn register_secrets(&self) {
+        crate::redact::register_secret("g2g.user_id", &self.user_id);
+        crate::redact::register_secret("g2g.refresh_token", &self.refresh_token);
+        crate::redact::register_secret("g2g.long_lived_token", &self.long_lived_token);
+        crate::redact::register_secret("g2g.active_device_token", &self.active_device_token);
+    }
+}
+
+impl Default for OfferTemplateSettings {
+    fn default() -> Self {
+        Self {
+            delivery_speed: default_delivery_speed(),
+            delivery_speed_details: vec![],
+            qty: default_qty(),
+            min_qty: default_qty(),
+            low_stock_alert_qty: 0,
+            sales_territory: default_sales_territory(),
+            wholesale_details: vec![],
+        }
+    }
+}
+
+impl OfferTemplateSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.delivery_speed.as_str() {
+            "instant" => {}
+            "manual" => {
+                if self.delivery_speed_details.is_empty() {
+                    return Err("Manual delivery requires delivery speed details".to_string());
+                }
+            }
+            other => return Err(format!("Unknown delivery speed: {}", other)),
+        }
+        if self.qty < 1 {
+            return Err("Quantity must be at least 1".to_string());
+        }
+        if self.min_qty < 1 || self.min_qty > self.qty {
+            return Err("Min quantity must be between 1 and quantity".to_string());
+        }
+        if self.low_stock_alert_qty < 0 {
+            return Err("Low stock alert quantity cannot be negative".to_string());
+        }
+        if self.sales_territory.settings_type != "global" && self.sales_territory.countries.is_empty() {
+            return Err(format!(
+                "Sales territory '{}' requires at least one country",
+                self.sales_territory.settings_type
+            ));
+        }
+        Ok(())
+    }
+
+    // Применить переопределение из запроса на выставление
+    pub fn merged(&self, over: &OfferTemplateOverride) -> Self {
+        Self {
+            delivery_speed: over.delivery_speed.clone().unwrap_or_else(|| self.delivery_speed.clone()),
+            delivery_speed_details: over
+                .delivery_speed_details
+                .clone()
+                .unwrap_or_else(|| self.delivery_speed_details.clone()),
+            qty: over.qty.unwrap_or(self.qty),
+            min_qty: over.min_qty.unwrap_or(self.min_qty),
+            low_stock_alert_qty: over.low_stock_alert_qty.unwrap_or(self.low_stock_alert_qty),
+            sales_territory: over.sales_territory.clone().unwrap_or_else(|| self.sales_territory.clone()),
+            wholesale_details: over.wholesale_details.clone().unwrap_or_else(|| self.wholesale_details.clone()),
+        }
+    }
 }
 
 impl AppSettings {
     // Получить путь к файлу настроек
     fn get_settings_path() -> Result<PathBuf, String> {
-        // Для Tauri 2 используем домашнюю директорию + .config
-        let home_dir = std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE"))
-            .map_err(|_| "Failed to get home directory")?;
+        Ok(config_dir()?.join("settings.json"))
+    }
 
-        #[cfg(target_os = "linux")]
-        let config_dir = PathBuf::from(home_dir).join(".config").join("g2g-app");
+    // Путь к резервной копии рядом с основным файлом (settings.json.bak)
+    fn backup_path(settings_path: &Path) -> PathBuf {
+        settings_path.with_extension("json.bak")
+    }
 
-        #[cfg(target_os = "macos")]
-        let config_dir = PathBuf::from(home_dir).join("Library").join("Application Support").join("g2g-app");
+    // Путь к временному файлу, который атомарно переименовывается в settings.json
+    fn temp_path(settings_path: &Path) -> PathBuf {
+        settings_path.with_extension("json.tmp")
+    }
 
-        #[cfg(target_os = "windows")]
-        let config_dir = PathBuf::from(home_dir).join("AppData").join("Roaming").join("g2g-app");
+    // Прочитать и распарсить один файл настроек (без fallback на бэкап)
+    fn read_file(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read settings file: {}", e))?;
 
-        // Создаем директорию если не существует
-        if !config_dir.exists() {
-            fs::create_dir_all(&config_dir)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
-        }
+        let settings: AppSettings = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse settings: {}", e))?;
+
+        // Валидация G2G настроек
+        settings.g2g.validate()?;
 
-        Ok(config_dir.join("settings.json"))
+        Ok(settings)
     }
 
     // Загрузить настройки из файла
     pub fn load() -> Result<Self, String> {
         let settings_path = Self::get_settings_path()?;
+        Self::load_from(&settings_path)
+    }
+
+    // Загрузить настройки по указанному пути. Если основной файл повреждён
+    // или отсутствует, пробуем резервную копию settings.json.bak.
+    fn load_from(settings_path: &Path) -> Result<Self, String> {
+        let backup_path = Self::backup_path(settings_path);
 
-        if !settings_path.exists() {
+        if !settings_path.exists() && !backup_path.exists() {
             return Err("Settings file not found".to_string());
         }
 
-        let content = fs::read_to_string(&settings_path)
-            .map_err(|e| format!("Failed to read settings file: {}", e))?;
-
-        let settings: AppSettings = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse settings: {}", e))?;
-
-        // Валидация G2G настроек
-        settings.g2g.validate()?;
+        let primary_err = match Self::read_file(settings_path) {
+            Ok(settings) => return Ok(settings),
+            Err(e) => e,
+        };
 
-        Ok(settings)
+        match Self::read_file(&backup_path) {
+            Ok(settings) => {
+                warn!(
+                    "Основной файл настроек не читается ({}), настройки восстановлены из резервной копии: {:?}",
+                    primary_err, backup_path
+                );
+                Ok(settings)
+            }
+            Err(_) => Err(primary_err),
+        }
     }
 
     // Сохранить настройки в файл
     pub fn save(&self) -> Result<(), String> {
         // Валидация перед сохранением
         self.g2g.validate()?;
+        if let Some(template) = &self.offer_template {
+            template.validate()?;
+        }
+        if let Some(sheets) = &self.sheets {
+            sheets.validate_columns()?;
+        }
 
         let settings_path = Self::get_settings_path()?;
+        self.save_to(&settings_path)?;
+
+        info!("Settings saved to: {:?}", settings_path);
+        Ok(())
+    }
 
+    // Записать настройки атомарно: сначала во временный файл, затем rename
+    // поверх settings.json. Прежняя рабочая версия уходит в settings.json.bak.
+    fn save_to(&self, settings_path: &Path) -> Result<(), String> {
         let json = serde_json::to_string_pretty(self)
             .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
-        fs::write(&settings_path, json)
-            .map_err(|e| format!("Failed to write settings file: {}", e))?;
+        let temp_path = Self::temp_path(settings_path);
+        {
+            let mut file = fs::File::create(&temp_path)
+                .map_err(|e| format!("Failed to create temp settings file: {}", e))?;
+            file.write_all(json.as_bytes())
+                .map_err(|e| format!("Failed to write settings file: {}", e))?;
+            file.sync_all()
+                .map_err(|e| format!("Failed to flush settings file: {}", e))?;
+        }
+
+        // В бэкап кладём только читаемый файл, иначе повреждённый основной
+        // файл затёр бы последнюю хорошую копию.
+        if Self::read_file(settings_path).is_ok() {
+            fs::copy(settings_path, Self::backup_path(settings_path))
+                .map_err(|e| format!("Failed to back up settings file: {}", e))?;
+        }
+
+        fs::rename(&temp_path, settings_path)
+            .map_err(|e| format!("Failed to replace settings file: {}", e))?;
 
-        println!("✅ Settings saved to: {:?}", settings_path);
         Ok(())
     }
 
@@ -113,7 +469,14 @@ impl AppSettings {
         if settings_path.exists() {
             fs::remove_file(&settings_path)
                 .map_err(|e| format!("Failed to delete settings file: {}", e))?;
-            println!("✅ Settings file deleted");
+            info!("Settings file deleted");
+        }
+
+        // Бэкап тоже удаляем, иначе load() «воскресит» очищенные токены
+        let backup_path = Self::backup_path(&settings_path);
+        if backup_path.exists() {
+            fs::remove_file(&backup_path)
+                .map_err(|e| format!("Failed to delete settings backup: {}", e))?;
         }
 
         Ok(())
@@ -122,13 +485,39 @@ impl AppSettings {
     // Проверить наличие настроек
     pub fn exists() -> bool {
         if let Ok(settings_path) = Self::get_settings_path() {
-            settings_path.exists()
+            settings_path.exists() || Self::backup_path(&settings_path).exists()
         } else {
             false
         }
     }
 }
 
+/// Директория конфигурации приложения (создаётся при первом обращении).
+/// Здесь лежат settings.json и остальные файлы данных приложения.
+pub fn config_dir() -> Result<PathBuf, String> {
+    // Для Tauri 2 используем домашнюю директорию + .config
+    let home_dir = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map_err(|_| "Failed to get home directory")?;
+
+    #[cfg(target_os = "linux")]
+    let config_dir = PathBuf::from(home_dir).join(".config").join("g2g-app");
+
+    #[cfg(target_os = "macos")]
+    let config_dir = PathBuf::from(home_dir).join("Library").join("Application Support").join("g2g-app");
+
+    #[cfg(target_os = "windows")]
+    let config_dir = PathBuf::from(home_dir).join("AppData").join("Roaming").join("g2g-app");
+
+    // Создаем директорию если не существует
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    Ok(config_dir)
+}
+
 // Fallback: попытка загрузить из .env (для разработки)
 pub fn load_from_env() -> Option<G2GSettings> {
     use std::env;
@@ -149,6 +538,39 @@ pub fn load_from_env() -> Option<G2GSettings> {
     })
 }
 
+/// Настройки G2G из файла настроек, с fallback на .env. Токены
+/// регистрируются для маскирования в логах.
+pub fn load_g2g_settings() -> Result<G2GSettings, String> {
+    let settings = match AppSettings::load() {
+        Ok(settings) => {
+            info!("G2G settings loaded from file");
+            settings.g2g
+        }
+        Err(_) => {
+            // Fallback на .env
+            load_from_env()
+                .ok_or_else(|| {
+                    "G2G токены не настроены. Перейдите в настройки приложения.".to_string()
+                })?
+        }
+    };
+    settings.register_secrets();
+    Ok(settings)
+}
+
+/// Настройки таблицы, если запись в неё включена: для веб-хука — непустой URL
+/// (пустое поле в форме настроек означает «не писать в таблицу»). URL и секрет
+/// веб-хука регистрируются для маскирования в логах.
+pub fn load_sheets_settings() -> Option<SheetsSettings> {
+    let sheets = AppSettings::load().ok()?.sheets?;
+    sheets.register_secrets();
+    if sheets.backend == SHEETS_BACKEND_WEBHOOK && sheets.webhook_url.trim().is_empty() {
+        None
+    } else {
+        Some(sheets)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,4 +593,92 @@ mod tests {
         };
         assert!(invalid.validate().is_err());
     }
+
+    #[test]
+    fn test_offer_template_override() {
+        let base = OfferTemplateSettings::default();
+        assert!(base.validate().is_ok());
+
+        let restricted = base.merged(&OfferTemplateOverride {
+            sales_territory: Some(SalesTerritory {
+                settings_type: "restricted".to_string(),
+                countries: vec![],
+            }),
+            ..Default::default()
+        });
+        assert!(restricted.validate().is_err());
+
+        let manual = base.merged(&OfferTemplateOverride {
+            delivery_speed: Some("manual".to_string()),
+            delivery_speed_details: Some(vec![DeliverySpeed { min: 1, max: 1, delivery_time: 60 }]),
+            ..Default::default()
+        });
+        assert!(manual.validate().is_ok());
+        assert_eq!(manual.qty, 1);
+    }
+
+    #[test]
+    fn test_validate_columns() {
+        let sheets = |columns: &[(&str, &str)]| SheetsSettings {
+            backend: SHEETS_BACKEND_WEBHOOK.to_string(),
+            webhook_url: "https://script.google.com/macros/s/test/exec".to_string(),
+            webhook_secret: None,
+            spreadsheet_id: None,
+            service_account_key: None,
+            ledger_path: None,
+            columns: columns
+                .iter()
+                .map(|(field, header)| SheetColumn { field: field.to_string(), header: header.to_string() })
+                .collect(),
+        };
+        assert!(sheets(&[("price", "Price"), ("purchase_cost", "Purchase Cost")]).validate_columns().is_ok());
+
+        let unknown = sheets(&[("price", "Price"), ("buyer", "Buyer")]).validate_columns().unwrap_err();
+        assert!(unknown.contains("buyer"), "{}", unknown);
+        assert!(sheets(&[("price", "  ")]).validate_columns().is_err());
+        // Заголовки сравниваются без учёта регистра и пробелов по краям
+        let duplicate = sheets(&[("price", "Price"), ("title", " price ")]).validate_columns().unwrap_err();
+        assert!(duplicate.contains("дважды"), "{}", duplicate);
+    }
+
+    fn test_settings(user_id: &str) -> AppSettings {
+        AppSettings {
+            g2g: G2GSettings {
+                user_id: user_id.to_string(),
+                refresh_token: "token".to_string(),
+                long_lived_token: "token".to_string(),
+                active_device_token: "token".to_string(),
+            },
+            theme: None,
+            sheets: None,
+            offer_template: None,
+            image_host: None,
+            listing_text: None,
+            usage: None,
+            watcher: None,
+        }
+    }
+
+    #[test]
+    fn test_settings_recovered_from_backup() {
+        let dir = std::env::temp_dir().join(format!("g2g-app-settings-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+
+        test_settings("first").save_to(&path).unwrap();
+        test_settings("second").save_to(&path).unwrap();
+        assert_eq!(AppSettings::load_from(&path).unwrap().g2g.user_id, "second");
+
+        // Обрыв записи посреди файла: основной файл не парсится
+        fs::write(&path, "{\"g2g\": {\"user_id\": \"sec").unwrap();
+        assert_eq!(AppSettings::load_from(&path).unwrap().g2g.user_id, "first");
+
+        // Повреждённый файл не должен попасть в бэкап при следующем сохранении
+        test_settings("third").save_to(&path).unwrap();
+        assert_eq!(AppSettings::load_from(&path).unwrap().g2g.user_id, "third");
+        let backup = AppSettings::read_file(&AppSettings::backup_path(&path)).unwrap();
+        assert_eq!(backup.g2g.user_id, "first");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -5,6 +5,10 @@ use rand::Rng;
 use std::io::Read;
 use flate2::read::GzDecoder;
 
+use crate::category::{CategoryDefinition, ListingValues};
+use crate::config::OfferTemplateSettings;
+use tracing::{debug, error, info, warn};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct G2GAuthTokens {
     pub user_id: String,
@@ -13,6 +17,20 @@ pub struct G2GAuthTokens {
     pub active_device_token: String,
 }
 
+impl G2GAuthTokens {
+    /// Токены из настроек приложения (или из .env, если настроек нет).
+    pub fn from_settings() -> Result<Self, String> {
+        let settings = crate::config::load_g2g_settings()
+            .map_err(|e| format!("Не удалось загрузить настройки G2G: {}", e))?;
+        Ok(Self {
+            user_id: settings.user_id,
+            refresh_token: settings.refresh_token,
+            long_lived_token: settings.long_lived_token,
+            active_device_token: settings.active_device_token,
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct RefreshResponse {
     payload: RefreshPayload,
@@ -78,6 +96,12 @@ pub struct ExternalImage {
     pub image_url: String,
 }
 
+impl Default for G2GApiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl G2GApiClient {
     pub fn new() -> Self {
         // Генерируем случайный session_id
@@ -167,7 +191,7 @@ impl G2GApiClient {
     }
 
     pub async fn refresh_token(&mut self, tokens: &G2GAuthTokens) -> Result<String, String> {
-        println!("🔄 Refreshing G2G token...");
+        info!("Refreshing G2G token...");
 
         // Человекоподобная задержка перед запросом
         let delay_ms = rand::thread_rng().gen_range(1500..2500);
@@ -183,8 +207,7 @@ impl G2GApiClient {
 
         let headers = self.get_browser_headers(false);
 
-        println!("📤 Sending refresh request to G2G...");
-        println!("   User ID: {}", tokens.user_id);
+        debug!("Sending refresh request to G2G...");
 
         let response = self.client
             .post(&url)
@@ -195,37 +218,39 @@ impl G2GApiClient {
             .map_err(|e| format!("Request failed: {}", e))?;
 
         let status = response.status();
-        println!("📥 Response status: {}", status);
+        debug!("Response status: {}", status);
 
         if status.is_success() {
             let json: RefreshResponse = response.json().await
                 .map_err(|e| format!("Failed to parse response: {}", e))?;
 
+            crate::redact::register_secret("g2g.access_token", &json.payload.access_token);
             self.current_token = Some(json.payload.access_token.clone());
-            println!("✅ Token refreshed successfully");
+            info!("Token refreshed successfully");
             Ok(json.payload.access_token)
         } else {
             let error_body = response.text().await.unwrap_or_else(|_| "Unable to read error body".to_string());
-            println!("❌ Failed to refresh token: {} - {}", status, error_body);
+            error!("Failed to refresh token: {} - {}", status, error_body);
             Err(format!("Failed to refresh token: {} - {}", status, error_body))
         }
     }
 
-    pub async fn fetch_skin_price(&mut self, skin_name: &str, server: &str, tokens: &G2GAuthTokens) -> Result<String, String> {
-        self.fetch_skin_price_impl(skin_name, server, tokens, false).await
+    pub async fn fetch_skin_price(&mut self, skin_name: &str, server: &str, category: &CategoryDefinition, tokens: &G2GAuthTokens) -> Result<String, String> {
+        self.fetch_skin_price_impl(skin_name, server, category, tokens, false).await
     }
 
     fn fetch_skin_price_impl<'a>(
         &'a mut self,
         skin_name: &'a str,
         server: &'a str,
+        category: &'a CategoryDefinition,
         tokens: &'a G2GAuthTokens,
         is_retry: bool,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<String, String>> + Send + 'a>> {
         Box::pin(async move {
             // Ensure we have a valid token
             if self.current_token.is_none() {
-                println!("🔑 No token available, refreshing...");
+                info!("No token available, refreshing...");
                 self.refresh_token(tokens).await?;
             }
 
@@ -233,30 +258,15 @@ impl G2GApiClient {
             let delay_ms = rand::thread_rng().gen_range(1000..2000);
             tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
 
-            // Server filter mapping (из Python скрипта)
-            let server_filter = match server {
-                "EUW" | "Europe West" => "e80c30d1:304244a1%7C319340f0:65ec9642",
-                "EUNE" | "Europe Nordic & East" => "e80c30d1:1a87dd85%7C319340f0:65ec9642",
-                "NA" | "North America" | "NA1" => "e80c30d1:e2f2c55b%7C319340f0:65ec9642",
-                "BR" | "BR1" | "Brazil" => "e80c30d1:31e5d298%7C319340f0:65ec9642",
-                "LAN" => "e80c30d1:302ba1e6%7C319340f0:65ec9642",
-                "LAS" => "e80c30d1:f28899f5%7C319340f0:65ec9642",
-                "OCE" | "Oceania" => "e80c30d1:5c030fef%7C319340f0:65ec9642",
-                "TR" | "Turkey" => "e80c30d1:2247e703%7C319340f0:65ec9642",
-                "RU" | "Russia" => "319340f0:65ec9642%7Ce80c30d1:d94d8d49",
-                "JP" | "Japan" => "e80c30d1:e9926686%7C319340f0:65ec9642",
-                "KR" | "Korea" => "319340f0:65ec9642%7Ce80c30d1:a7bb0eb5",
-                "SEA" | "SG2" | "Singapore" | "Southeast Asia" => "e80c30d1:67d20e31%7C319340f0:65ec9642",
-                _ => "e80c30d1:1a87dd85%7C319340f0:65ec9642", // Default to EUNE
-            };
+            let server_filter = category.search_filter(&search_values(server));
 
             let encoded_skin = urlencoding::encode(skin_name);
             let search_url = format!(
-                "{}/offer/search?seo_term=league-of-legends-account&q={}&sort=lowest_price&filter_attr={}&page_size=48&currency=USD&country=RU&include_localization=0",
-                self.base_url, encoded_skin, server_filter
+                "{}/offer/search?seo_term={}&q={}&sort=lowest_price&filter_attr={}&page_size=48&currency=USD&country=RU&include_localization=0",
+                self.base_url, category.seo_term, encoded_skin, server_filter
             );
 
-            println!("🔍 Searching for: {} on server {}", skin_name, server);
+            debug!("Searching for: {} on server {}", skin_name, server);
 
             let headers = self.get_browser_headers(true);
 
@@ -268,13 +278,13 @@ impl G2GApiClient {
                 .map_err(|e| format!("Search request failed: {}", e))?;
 
             let status = response.status();
-            println!("   📥 Response status: {}", status);
+            debug!("Response status: {}", status);
 
             if status == 401 && !is_retry {
-                println!("⚠️  Token expired (401), refreshing and retrying...");
+                warn!("Token expired (401), refreshing and retrying...");
                 self.current_token = None;
                 self.refresh_token(tokens).await?;
-                return self.fetch_skin_price_impl(skin_name, server, tokens, true).await;
+                return self.fetch_skin_price_impl(skin_name, server, category, tokens, true).await;
             }
 
             if !status.is_success() {
@@ -283,26 +293,26 @@ impl G2GApiClient {
 
             // Проверяем Content-Encoding в ответе
             if let Some(encoding) = response.headers().get("content-encoding") {
-                println!("   📦 Content-Encoding: {:?}", encoding);
+                debug!("Content-Encoding: {:?}", encoding);
             }
 
             // Пробуем получить байты и декодировать
             let bytes = response.bytes().await
                 .map_err(|e| format!("Failed to read response bytes: {}", e))?;
 
-            println!("   📊 Response size: {} bytes", bytes.len());
+            debug!("Response size: {} bytes", bytes.len());
 
             // Проверяем gzip сигнатуру (1f 8b)
             let decoded_bytes = if bytes.len() >= 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
-                println!("   🗜️  Decompressing gzip response...");
+                debug!("Decompressing gzip response...");
                 let mut decoder = GzDecoder::new(&bytes[..]);
                 let mut decoded = Vec::new();
                 decoder.read_to_end(&mut decoded)
                     .map_err(|e| format!("Failed to decompress gzip: {}", e))?;
-                println!("   ✅ Decompressed to {} bytes", decoded.len());
+                debug!("Decompressed to {} bytes", decoded.len());
                 decoded
             } else {
-                println!("   📄 Response is not gzipped");
+                debug!("Response is not gzipped");
                 bytes.to_vec()
             };
 
@@ -313,20 +323,20 @@ impl G2GApiClient {
                     format!("Failed to parse JSON: {} | Preview: {}", e, preview)
                 })?;
 
-            println!("   ✅ Successfully parsed response, code: {}", json.code);
+            debug!("Successfully parsed response, code: {}", json.code);
 
-            println!("   📦 Total results returned: {}", json.payload.results.len());
+            debug!("Total results returned: {}", json.payload.results.len());
 
             // Логируем первые несколько результатов для отладки
             if json.payload.results.len() > 0 {
-                println!("   🔍 Sample results (showing first 3):");
+                debug!("Sample results (showing first 3):");
                 for (i, result) in json.payload.results.iter().take(3).enumerate() {
-                    println!("      ━━━ Result #{} ━━━", i+1);
-                    println!("      💵 Price: ${:.2}", result.converted_unit_price);
+                    debug!("Result #{} ━━━", i+1);
+                    debug!("Price: ${:.2}", result.converted_unit_price);
                     if let Some(title) = &result.title {
-                        println!("      📌 Title: {}", title);
+                        debug!("Title: {}", title);
                     } else {
-                        println!("      📌 Title: [none]");
+                        debug!("Title: [none]");
                     }
                     if let Some(desc) = &result.description {
                         let preview = if desc.len() > 150 {
@@ -334,16 +344,16 @@ impl G2GApiClient {
                         } else {
                             desc.clone()
                         };
-                        println!("      📝 Description: {}", preview);
+                        debug!("Description: {}", preview);
                     } else {
-                        println!("      📝 Description: [none]");
+                        debug!("Description: [none]");
                     }
                 }
             }
 
             // Find minimum price from matching results
             let skin_lower = skin_name.to_lowercase();
-            println!("   🎯 Looking for skin: '{}'", skin_lower);
+            debug!("Looking for skin: '{}'", skin_lower);
 
             let matching_results: Vec<_> = json.payload.results
                 .iter()
@@ -358,14 +368,14 @@ impl G2GApiClient {
 
                     let matches = desc_match || title_match;
                     if matches {
-                        println!("      ✓ Match found at index {}", idx);
+                        debug!("Match found at index {}", idx);
                     }
                     matches
                 })
                 .map(|(_, result)| result)
                 .collect();
 
-            println!("   📊 Matching results: {} out of {}", matching_results.len(), json.payload.results.len());
+            debug!("Matching results: {} out of {}", matching_results.len(), json.payload.results.len());
 
             if matching_results.is_empty() {
                 // Если ничего не нашли с фильтром, возвращаем минимальную цену из всех результатов
@@ -374,10 +384,10 @@ impl G2GApiClient {
                         .iter()
                         .map(|r| r.converted_unit_price)
                         .fold(f64::INFINITY, f64::min);
-                    println!("   ⚠️  No exact match for '{}', using minimum from all results: ${:.2}", skin_name, min_price);
+                    warn!("No exact match for '{}', using minimum from all results: ${:.2}", skin_name, min_price);
                     Ok(format!("~${:.2}", min_price))
                 } else {
-                    println!("   ❌ No offers found at all");
+                    error!("No offers found at all");
                     Ok("No offers".to_string())
                 }
             } else {
@@ -385,7 +395,7 @@ impl G2GApiClient {
                     .iter()
                     .map(|r| r.converted_unit_price)
                     .fold(f64::INFINITY, f64::min);
-                println!("   ✅ Found exact match: ${:.2} (from {} matching offers)", min_price, matching_results.len());
+                debug!("Found exact match: ${:.2} (from {} matching offers)", min_price, matching_results.len());
                 Ok(format!("${:.2}", min_price))
             }
         })
@@ -436,23 +446,70 @@ pub struct UpdateOfferRequest {
     pub external_images_mapping: Vec<ExternalImage>,
     pub unit_price: f64,
     pub other_pricing: Vec<String>,
-    pub wholesale_details: Vec<String>,
+    pub wholesale_details: Vec<serde_json::Value>,
     pub other_wholesale_details: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeliverySpeed {
     pub min: i32,
     pub max: i32,
     pub delivery_time: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SalesTerritory {
     pub settings_type: String,
     pub countries: Vec<String>,
 }
 
+/// Состояние оффера на G2G.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OfferStatus {
+    pub offer_id: String,
+    /// false — оффер удалён или не найден.
+    pub found: bool,
+    /// Статус из ответа G2G ("live", "inactive", "deleted", ...).
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+impl OfferStatus {
+    fn gone(offer_id: &str) -> Self {
+        Self { offer_id: offer_id.to_string(), found: false, status: None }
+    }
+
+    // Ответ GET /offer/{id}: code 2000 и payload с полем status. Оффер
+    // считается пропавшим только по явному ответу «не найден»; любой другой
+    // ответ (другой code, payload не объект) — ошибка: оффер не проверен,
+    // а не удалён.
+    fn from_response(offer_id: &str, json: &serde_json::Value) -> Result<Self, String> {
+        let payload = &json["payload"];
+        if json["code"].as_i64() == Some(2000) && payload.is_object() {
+            let status = payload["status"]
+                .as_str()
+                .or_else(|| payload["offer_status"].as_str())
+                .map(str::to_string);
+            return Ok(Self { offer_id: offer_id.to_string(), found: true, status });
+        }
+        let message = json["message"].as_str().unwrap_or_default();
+        if message.to_lowercase().contains("not found") {
+            return Ok(Self::gone(offer_id));
+        }
+        let body = json.to_string();
+        Err(format!(
+            "Unexpected offer response (code {}): {}",
+            json["code"],
+            body.chars().take(200).collect::<String>()
+        ))
+    }
+
+    /// Оффера больше нет на G2G (удалён или не найден).
+    pub fn is_gone(&self) -> bool {
+        !self.found || self.status.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("deleted"))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct UpdateOfferResponse {
     code: i32,
@@ -470,18 +527,18 @@ struct UpdateOfferPayload {
 
 impl G2GApiClient {
     // Создать пустой оффер и получить ID
-    pub async fn create_offer_id(&mut self, tokens: &G2GAuthTokens) -> Result<String, String> {
-        println!("📝 Creating empty offer to get ID...");
+    pub async fn create_offer_id(&mut self, category: &CategoryDefinition, tokens: &G2GAuthTokens) -> Result<String, String> {
+        info!("Creating empty offer to get ID...");
 
         // ВАЖНО: Принудительно обновляем токен перед созданием оффера
-        println!("🔄 Refreshing token before creating offer...");
+        info!("Refreshing token before creating offer...");
         self.refresh_token(tokens).await?;
 
         let url = format!("{}/offer", self.base_url);
 
         let request = CreateOfferRequest {
-            service_id: "f6a1aba5-473a-4044-836a-8968bbab16d7".to_string(),
-            brand_id: "lgc_game_22666".to_string(),
+            service_id: category.service_id.clone(),
+            brand_id: category.brand_id.clone(),
             offer_type: "public".to_string(),
             seller_id: tokens.user_id.clone(),
         };
@@ -497,11 +554,11 @@ impl G2GApiClient {
             .map_err(|e| format!("Failed to create offer: {}", e))?;
 
         let status = response.status();
-        println!("📥 Create offer response status: {}", status);
+        debug!("Create offer response status: {}", status);
 
         if status == 401 {
             // Если все еще 401, пробуем еще раз с новым токеном
-            println!("⚠️  Got 401, refreshing token and retrying...");
+            warn!("Got 401, refreshing token and retrying...");
             self.current_token = None;
             self.refresh_token(tokens).await?;
 
@@ -515,7 +572,7 @@ impl G2GApiClient {
                 .map_err(|e| format!("Failed to create offer (retry): {}", e))?;
 
             let status = response.status();
-            println!("📥 Create offer response status (retry): {}", status);
+            debug!("Create offer response status (retry): {}", status);
 
             if !status.is_success() {
                 let error_body = response.text().await.unwrap_or_else(|_| "Unable to read error".to_string());
@@ -525,7 +582,7 @@ impl G2GApiClient {
             let json: CreateOfferResponse = response.json().await
                 .map_err(|e| format!("Failed to parse create offer response: {}", e))?;
 
-            println!("✅ Offer ID created: {}", json.payload.offer_id);
+            info!("Offer ID created: {}", json.payload.offer_id);
             return Ok(json.payload.offer_id);
         }
 
@@ -537,7 +594,7 @@ impl G2GApiClient {
         let json: CreateOfferResponse = response.json().await
             .map_err(|e| format!("Failed to parse create offer response: {}", e))?;
 
-        println!("✅ Offer ID created: {}", json.payload.offer_id);
+        info!("Offer ID created: {}", json.payload.offer_id);
         Ok(json.payload.offer_id)
     }
 
@@ -548,50 +605,49 @@ impl G2GApiClient {
         title: &str,
         description: &str,
         price: f64,
-        server: &str,
-        rank: &str,
-        champions_count: i32,
-        skins_count: i32,
-        screenshot_url: Option<&str>,
+        category: &CategoryDefinition,
+        values: &ListingValues,
+        screenshot_urls: &[String],
+        template: &OfferTemplateSettings,
         tokens: &G2GAuthTokens,
     ) -> Result<String, String> {
-        println!("📄 Updating offer {} with data...", offer_id);
+        debug!("Updating offer {} with data...", offer_id);
+
+        template.validate()?;
 
         let url = format!("{}/offer/{}", self.base_url, offer_id);
 
-        let offer_attributes = self.create_offer_attributes(server, rank, champions_count, skins_count);
+        let offer_attributes = self.create_offer_attributes(category, values);
 
-        // Формируем массив изображений
-        let external_images = if let Some(url) = screenshot_url {
-            vec![ExternalImage {
-                image_name: "1".to_string(),
-                image_url: url.to_string(),
-            }]
-        } else {
-            vec![]
-        };
+        // Формируем массив изображений (имена — порядковые номера с 1)
+        let external_images: Vec<ExternalImage> = screenshot_urls
+            .iter()
+            .enumerate()
+            .map(|(i, url)| ExternalImage {
+                image_name: (i + 1).to_string(),
+                image_url: url.clone(),
+            })
+            .collect();
+        info!("Attaching {} image(s)", external_images.len());
 
         let request = UpdateOfferRequest {
             seller_id: tokens.user_id.clone(),
             offer_id: offer_id.to_string(),
             delivery_method_ids: vec![],
-            delivery_speed: "instant".to_string(),
-            delivery_speed_details: vec![],
-            qty: 1,
+            delivery_speed: template.delivery_speed.clone(),
+            delivery_speed_details: template.delivery_speed_details.clone(),
+            qty: template.qty,
             currency: "USD".to_string(),
-            min_qty: 1,
-            low_stock_alert_qty: 0,
-            sales_territory_settings: SalesTerritory {
-                settings_type: "global".to_string(),
-                countries: vec![],
-            },
+            min_qty: template.min_qty,
+            low_stock_alert_qty: template.low_stock_alert_qty,
+            sales_territory_settings: template.sales_territory.clone(),
             title: title.to_string(),
             description: description.to_string(),
             offer_attributes,
-            external_images_mapping: external_images,  // ← Теперь эта переменная существует
+            external_images_mapping: external_images,
             unit_price: price,
             other_pricing: vec![],
-            wholesale_details: vec![],
+            wholesale_details: template.wholesale_details.clone(),
             other_wholesale_details: vec![],
         };
 
@@ -606,7 +662,7 @@ impl G2GApiClient {
             .map_err(|e| format!("Failed to update offer: {}", e))?;
 
         let status = response.status();
-        println!("📥 Update offer response status: {}", status);
+        debug!("Update offer response status: {}", status);
 
         if !status.is_success() {
             let error_body = response.text().await.unwrap_or_else(|_| "Unable to read error".to_string());
@@ -617,7 +673,7 @@ impl G2GApiClient {
             .map_err(|e| format!("Failed to read response bytes: {}", e))?;
 
         let decoded_bytes = if bytes.len() >= 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
-            println!("🗜️ Decompressing gzip response...");
+            debug!("Decompressing gzip response...");
             let mut decoder = GzDecoder::new(&bytes[..]);
             let mut decoded = Vec::new();
             decoder.read_to_end(&mut decoded)
@@ -628,7 +684,7 @@ impl G2GApiClient {
         };
 
         let response_text = String::from_utf8_lossy(&decoded_bytes);
-        println!("📄 Update offer response body: {}", response_text);
+        debug!("Update offer response body: {}", response_text);
 
         let json: UpdateOfferResponse = serde_json::from_slice(&decoded_bytes)
             .map_err(|e| format!("Failed to parse update response: {}", e))?;
@@ -641,7 +697,7 @@ impl G2GApiClient {
             .ok_or("No payload in update response")?
             .relation_id;
 
-        println!("✅ Offer updated successfully! Relation ID: {}", relation_id);
+        info!("Offer updated successfully! Relation ID: {}", relation_id);
         Ok(relation_id)
     }
 
@@ -653,9 +709,9 @@ impl G2GApiClient {
         softpin_content: &str,
         tokens: &G2GAuthTokens,
     ) -> Result<(), String> {
-        println!("📤 Uploading softpin...");
-        println!("🔍 Softpin content length: {}", softpin_content.len());
-        println!("🔍 First 300 chars: {}", &softpin_content[..softpin_content.len().min(300)]);
+        debug!("Uploading softpin...");
+        crate::redact::register_secret("g2g.softpin", softpin_content);
+        debug!("Softpin content length: {}", softpin_content.len());
 
         let url = format!("{}/inventory/softpin", self.base_url);
 
@@ -670,9 +726,7 @@ impl G2GApiClient {
         let json_body = serde_json::to_string(&request_body)
             .map_err(|e| format!("Failed to serialize request: {}", e))?;
 
-        println!("📤 Final JSON length: {}", json_body.len());
-        println!("📤 JSON preview (first 500 chars):");
-        println!("{}", &json_body[..json_body.len().min(500)]);
+        debug!("Final JSON length: {}", json_body.len());
 
         let headers = self.get_browser_headers(true);
 
@@ -686,7 +740,7 @@ impl G2GApiClient {
             .map_err(|e| format!("Failed to upload softpin: {}", e))?;
 
         let status = response.status();
-        println!("📥 Response status: {}", status);
+        debug!("Response status: {}", status);
 
         if !status.is_success() {
             let error_body = response.text().await.unwrap_or_else(|_| "Unable to read".to_string());
@@ -704,7 +758,7 @@ impl G2GApiClient {
         };
 
         let response_text = String::from_utf8_lossy(&decoded_bytes);
-        println!("📄 Response: {}", response_text);
+        debug!("Response: {}", response_text);
 
         let json: serde_json::Value = serde_json::from_slice(&decoded_bytes)
             .map_err(|e| format!("Parse error: {}", e))?;
@@ -713,7 +767,7 @@ impl G2GApiClient {
             return Err(format!("Failed with code: {}", json["code"]));
         }
 
-        println!("✅ Softpin uploaded!");
+        info!("Softpin uploaded!");
         Ok(())
     }
 
@@ -723,9 +777,10 @@ impl G2GApiClient {
         offer_id: &str,
         relation_id: &str,
         softpin_content: &str,
+        category: &CategoryDefinition,
         tokens: &G2GAuthTokens,
     ) -> Result<(), String> {
-        println!("📦 Processing account data for offer {}...", offer_id);
+        debug!("Processing account data for offer {}...", offer_id);
 
         // Шаг 1: Загрузить softpin данные
         self.upload_softpin_data(offer_id, relation_id, softpin_content, tokens).await?;
@@ -735,7 +790,7 @@ impl G2GApiClient {
         tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
 
         // Шаг 2: Создать job для обработки
-        println!("📋 Creating processing job...");
+        info!("Creating processing job...");
 
         let job_url = format!("{}/inventory/job", self.base_url);
 
@@ -748,11 +803,11 @@ impl G2GApiClient {
             "seller_id": tokens.user_id,
             "file_type": "csv",
             "files": [csv_filename],
-            "brand_id": "lgc_game_22666",
-            "service_id": "f6a1aba5-473a-4044-836a-8968bbab16d7",
+            "brand_id": category.brand_id,
+            "service_id": category.service_id,
         });
 
-        println!("📤 Job request body: {}", serde_json::to_string_pretty(&job_request).unwrap());
+        debug!("Job request body: {}", serde_json::to_string_pretty(&job_request).unwrap());
 
         let headers = self.get_browser_headers(true);
 
@@ -765,7 +820,7 @@ impl G2GApiClient {
             .map_err(|e| format!("Failed to create job: {}", e))?;
 
         let status = response.status();
-        println!("📥 Create job response status: {}", status);
+        debug!("Create job response status: {}", status);
 
         let bytes = response.bytes().await
             .map_err(|e| format!("Failed to read job response bytes: {}", e))?;
@@ -781,7 +836,7 @@ impl G2GApiClient {
         };
 
         let response_text = String::from_utf8_lossy(&decoded_bytes);
-        println!("📄 Job response body: {}", response_text);
+        debug!("Job response body: {}", response_text);
 
         if !status.is_success() {
             return Err(format!("Job creation failed with status {}: {}", status, response_text));
@@ -791,7 +846,7 @@ impl G2GApiClient {
             .map_err(|e| format!("Failed to parse job response: {}", e))?;
 
         if job_json["code"].as_i64() == Some(2000) {
-            println!("✅ Account data processing job created successfully!");
+            info!("Account data processing job created successfully!");
             Ok(())
         } else {
             Err(format!("Create job returned code: {}", job_json["code"]))
@@ -805,18 +860,17 @@ impl G2GApiClient {
         title: &str,
         description: &str,
         price: f64,
-        server: &str,
-        rank: &str,
-        champions_count: i32,
-        skins_count: i32,
+        category: &CategoryDefinition,
+        values: &ListingValues,
         softpin_content: &str,
-        screenshot_url: Option<&str>,
+        screenshot_urls: &[String],
+        template: &OfferTemplateSettings,
         tokens: &G2GAuthTokens,
     ) -> Result<String, String> {
-        println!("🎯 Starting full offer creation with data upload...");
+        info!("Starting full offer creation with data upload...");
 
         // Шаг 1: Создать пустой оффер
-        let offer_id = self.create_offer_id(tokens).await?;
+        let offer_id = self.create_offer_id(category, tokens).await?;
 
         let delay_ms = rand::thread_rng().gen_range(1500..2500);
         tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
@@ -827,11 +881,10 @@ impl G2GApiClient {
             title,
             description,
             price,
-            server,
-            rank,
-            champions_count,
-            skins_count,
-            screenshot_url,
+            category,
+            values,
+            screenshot_urls,
+            template,
             tokens,
         ).await?;
 
@@ -839,121 +892,146 @@ impl G2GApiClient {
         tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
 
         // Шаг 3: Загрузить данные аккаунта используя relation_id
-        self.upload_account_data(&offer_id, &relation_id, softpin_content, tokens).await?;
+        self.upload_account_data(&offer_id, &relation_id, softpin_content, category, tokens).await?;
 
-        println!("🎉 Full offer with data created successfully! ID: {}", offer_id);
+        info!("Full offer with data created successfully! ID: {}", offer_id);
         Ok(offer_id)
     }
 
-    // Вспомогательные функции маппинга
-    fn get_server_id(&self, server: &str) -> &str {
-        // Вход приводится к верхнему регистру, поэтому все варианты — заглавными.
-        // Принимаем короткие коды, платформенные коды Riot и полные названия,
-        // чтобы быть не менее терпимыми, чем ценовой путь (map_server_filter).
-        match server.to_uppercase().as_str() {
-            "EUNE" | "EUNE1" | "EUROPE NORDIC & EAST" | "EUROPE NORDIC EAST" => "1a87dd85",
-            "EUW" | "EUW1" | "EUROPE WEST" => "304244a1",
-            "NA" | "NA1" | "NORTH AMERICA" => "e2f2c55b",
-            "BR" | "BR1" | "BRAZIL" => "31e5d298",
-            "LAN" | "LAN1" | "LA1" | "LATIN AMERICA NORTH" => "302ba1e6",
-            "LAS" | "LAS1" | "LA2" | "LATIN AMERICA SOUTH" => "f28899f5",
-            "OCE" | "OCE1" | "OC1" | "OCEANIA" => "5c030fef",
-            "TR" | "TR1" | "TURKEY" => "2247e703",
-            "RU" | "RU1" | "RUSSIA" => "d94d8d49",
-            "JP" | "JP1" | "JAPAN" => "e9926686",
-            "KR" | "KR1" | "KOREA" => "a7bb0eb5",
-            "SEA" | "SG2" | "SINGAPORE" | "SOUTHEAST ASIA" => "67d20e31",
-            _ => "1a87dd85", // Default EUNE
+    // Получить актуальные определения атрибутов категории из G2G
+    // (используется для обновления маппингов dataset id).
+    pub async fn fetch_attribute_definitions(
+        &mut self,
+        category: &CategoryDefinition,
+        tokens: &G2GAuthTokens,
+    ) -> Result<serde_json::Value, String> {
+        info!("Fetching attribute definitions for category '{}'...", category.id);
+
+        if self.current_token.is_none() {
+            self.refresh_token(tokens).await?;
         }
-    }
 
-    fn get_rank_id(&self, rank: &str) -> &str {
-        match rank {
-            "Unranked" | "Has games" => "dc514fdf",
-            "Iron" => "64174ec3",
-            "Bronze" => "bf08fd78",
-            "Silver" => "405dc68e",
-            "Gold" => "55077770",
-            "Platinum" => "8a193251",
-            "Emerald" => "297e3197",
-            "Diamond" => "3b06cc4c",
-            "Master" => "08f99b44",
-            "Grandmaster" => "447eb997",
-            "Challenger" => "99197149",
-            _ => "dc514fdf", // Default Unranked
+        let path = category
+            .attributes_endpoint
+            .replace("{brand_id}", &category.brand_id)
+            .replace("{service_id}", &category.service_id);
+        let url = format!("{}{}", self.base_url, path);
+
+        let mut response = self.client
+            .get(&url)
+            .headers(self.get_browser_headers(true))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch attribute definitions: {}", e))?;
+
+        if response.status() == 401 {
+            warn!("Got 401, refreshing token and retrying...");
+            self.refresh_token(tokens).await?;
+            response = self.client
+                .get(&url)
+                .headers(self.get_browser_headers(true))
+                .send()
+                .await
+                .map_err(|e| format!("Failed to fetch attribute definitions (retry): {}", e))?;
         }
-    }
 
-    fn get_champions_id(&self, count: i32) -> &str {
-        if count > 159 {
-            "3ee17abb"
-        } else if count > 129 {
-            "dc9b65bb"
-        } else if count > 99 {
-            "2ea03f75"
-        } else if count > 49 {
-            "7bbf537c"
-        } else if count > 29 {
-            "191cd6d7"
-        } else if count > 9 {
-            "b03ce3d1"
+        let status = response.status();
+        debug!("Attribute definitions response status: {}", status);
+
+        let bytes = response.bytes().await
+            .map_err(|e| format!("Failed to read response bytes: {}", e))?;
+
+        let decoded_bytes = if bytes.len() >= 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
+            let mut decoder = GzDecoder::new(&bytes[..]);
+            let mut decoded = Vec::new();
+            decoder.read_to_end(&mut decoded)
+                .map_err(|e| format!("Failed to decompress gzip: {}", e))?;
+            decoded
         } else {
-            "b5d60c4b"
+            bytes.to_vec()
+        };
+
+        if !status.is_success() {
+            let preview = String::from_utf8_lossy(&decoded_bytes[..decoded_bytes.len().min(200)]);
+            return Err(format!("Attribute definitions request failed: {} - {}", status, preview));
         }
+
+        serde_json::from_slice(&decoded_bytes)
+            .map_err(|e| format!("Failed to parse attribute definitions: {}", e))
     }
 
-    fn get_skins_id(&self, count: i32) -> &str {
-        if count > 999 {
-            "da83ec6e"
-        } else if count > 499 {
-            "32895a53"
-        } else if count > 299 {
-            "bbe13228"
-        } else if count > 99 {
-            "70f8019b"
-        } else if count > 49 {
-            "c1721794"
-        } else if count > 9 {
-            "4be5718c"
+    /// Состояние существующего оффера (для сверки с таблицей). Оффер,
+    /// которого нет (404 или ответ «not found»), возвращается с found = false;
+    /// неожиданный ответ — ошибка.
+    pub async fn fetch_offer_status(
+        &mut self,
+        offer_id: &str,
+        tokens: &G2GAuthTokens,
+    ) -> Result<OfferStatus, String> {
+        debug!("Fetching offer {}...", offer_id);
+
+        if self.current_token.is_none() {
+            self.refresh_token(tokens).await?;
+        }
+
+        let url = format!("{}/offer/{}", self.base_url, offer_id);
+
+        let mut response = self.client
+            .get(&url)
+            .headers(self.get_browser_headers(true))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch offer: {}", e))?;
+
+        if response.status() == 401 {
+            warn!("Got 401, refreshing token and retrying...");
+            self.refresh_token(tokens).await?;
+            response = self.client
+                .get(&url)
+                .headers(self.get_browser_headers(true))
+                .send()
+                .await
+                .map_err(|e| format!("Failed to fetch offer (retry): {}", e))?;
+        }
+
+        let status = response.status();
+        debug!("Offer response status: {}", status);
+
+        if status == 404 {
+            return Ok(OfferStatus::gone(offer_id));
+        }
+
+        let bytes = response.bytes().await
+            .map_err(|e| format!("Failed to read response bytes: {}", e))?;
+
+        let decoded_bytes = if bytes.len() >= 2 && bytes[0] == 0x1f && bytes[1] == 0x8b {
+            let mut decoder = GzDecoder::new(&bytes[..]);
+            let mut decoded = Vec::new();
+            decoder.read_to_end(&mut decoded)
+                .map_err(|e| format!("Failed to decompress gzip: {}", e))?;
+            decoded
         } else {
-            "ce97df6f"
+            bytes.to_vec()
+        };
+
+        if !status.is_success() {
+            let preview = String::from_utf8_lossy(&decoded_bytes[..decoded_bytes.len().min(200)]);
+            return Err(format!("Offer request failed: {} - {}", status, preview));
         }
+
+        let json: serde_json::Value = serde_json::from_slice(&decoded_bytes)
+            .map_err(|e| format!("Failed to parse offer response: {}", e))?;
+        OfferStatus::from_response(offer_id, &json)
     }
 
+    // Атрибуты оффера (сервер, тип, ранг, количество чемпионов/скинов)
+    // берутся из описания категории.
     fn create_offer_attributes(
         &self,
-        server: &str,
-        rank: &str,
-        champions_count: i32,
-        skins_count: i32,
+        category: &CategoryDefinition,
+        values: &ListingValues,
     ) -> Vec<OfferAttribute> {
-        vec![
-            // Server
-            OfferAttribute {
-                collection_id: "e80c30d1".to_string(),
-                dataset_id: self.get_server_id(server).to_string(),
-            },
-            // Type (Account for League of Legends)
-            OfferAttribute {
-                collection_id: "319340f0".to_string(),
-                dataset_id: "65ec9642".to_string(),
-            },
-            // Rank
-            OfferAttribute {
-                collection_id: "eb7040e2".to_string(),
-                dataset_id: self.get_rank_id(rank).to_string(),
-            },
-            // Champions count
-            OfferAttribute {
-                collection_id: "04862150".to_string(),
-                dataset_id: self.get_champions_id(champions_count).to_string(),
-            },
-            // Skins count
-            OfferAttribute {
-                collection_id: "962f619a".to_string(),
-                dataset_id: self.get_skins_id(skins_count).to_string(),
-            },
-        ]
+        category.offer_attributes(values)
     }
 }
 
@@ -1003,22 +1081,11 @@ fn build_browser_headers_shared(
     headers
 }
 
-fn map_server_filter(server: &str) -> &'static str {
-    match server {
-        "EUW" | "Europe West" => "e80c30d1:304244a1%7C319340f0:65ec9642",
-        "EUNE" | "Europe Nordic & East" => "e80c30d1:1a87dd85%7C319340f0:65ec9642",
-        "NA" | "North America" | "NA1" => "e80c30d1:e2f2c55b%7C319340f0:65ec9642",
-        "BR" | "BR1" | "Brazil" => "e80c30d1:31e5d298%7C319340f0:65ec9642",
-        "LAN" => "e80c30d1:302ba1e6%7C319340f0:65ec9642",
-        "LAS" => "e80c30d1:f28899f5%7C319340f0:65ec9642",
-        "OCE" | "Oceania" => "e80c30d1:5c030fef%7C319340f0:65ec9642",
-        "TR" | "Turkey" => "e80c30d1:2247e703%7C319340f0:65ec9642",
-        "RU" | "Russia" => "319340f0:65ec9642%7Ce80c30d1:d94d8d49",
-        "JP" | "Japan" => "e80c30d1:e9926686%7C319340f0:65ec9642",
-        "KR" | "Korea" => "319340f0:65ec9642%7Ce80c30d1:a7bb0eb5",
-        "SEA" | "SG2" | "Singapore" | "Southeast Asia" => "e80c30d1:67d20e31%7C319340f0:65ec9642",
-        _ => "e80c30d1:1a87dd85%7C319340f0:65ec9642",
-    }
+// Значения листинга для поиска цен: известен только сервер.
+fn search_values(server: &str) -> ListingValues {
+    let mut values = ListingValues::new();
+    values.insert("server".to_string(), server.to_string());
+    values
 }
 
 // Single-skin search that takes its state by value — safe to run concurrently.
@@ -1029,12 +1096,13 @@ pub async fn search_skin_price_shared(
     access_token: &str,
     skin_name: &str,
     server: &str,
+    category: &CategoryDefinition,
 ) -> Result<String, SkinSearchError> {
-    let server_filter = map_server_filter(server);
+    let server_filter = category.search_filter(&search_values(server));
     let encoded_skin = urlencoding::encode(skin_name);
     let search_url = format!(
-        "{}/offer/search?seo_term=league-of-legends-account&q={}&sort=lowest_price&filter_attr={}&page_size=48&currency=USD&country=RU&include_localization=0",
-        base_url, encoded_skin, server_filter
+        "{}/offer/search?seo_term={}&q={}&sort=lowest_price&filter_attr={}&page_size=48&currency=USD&country=RU&include_localization=0",
+        base_url, category.seo_term, encoded_skin, server_filter
     );
 
     let headers = build_browser_headers_shared(session_id, Some(access_token));
@@ -1166,3 +1234,26 @@ pub async fn refresh_access_token_shared(
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_offer_status_from_response() {
+        let live = OfferStatus::from_response("G1", &json!({ "code": 2000, "payload": { "status": "live" } })).unwrap();
+        assert!(live.found && !live.is_gone());
+        let deleted =
+            OfferStatus::from_response("G1", &json!({ "code": 2000, "payload": { "offer_status": "deleted" } })).unwrap();
+        assert!(deleted.is_gone());
+        let not_found =
+            OfferStatus::from_response("G1", &json!({ "code": 4004, "message": "Offer not found", "payload": null }))
+                .unwrap();
+        assert!(not_found.is_gone());
+
+        // Ошибка сервиса или неожиданный формат — не «удалён», а не проверен
+        assert!(OfferStatus::from_response("G1", &json!({ "code": 5000, "message": "Internal error" })).is_err());
+        assert!(OfferStatus::from_response("G1", &json!({ "code": 2000, "payload": [] })).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config;
+use tracing::{info, warn};
+
+const HISTORY_FILE_NAME: &str = "listing_history.jsonl";
+
+// Сериализует дописывание записей внутри процесса.
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Запись журнала выставлений: одна попытка create_g2g_offer/create_listing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingRecord {
+    pub id: String,
+    pub timestamp: DateTime<Local>,
+    /// Команда, через которую выставлялся аккаунт.
+    pub source: String,
+    pub account_name: String,
+    pub account_path: String,
+    #[serde(default)]
+    pub login: Option<String>,
+    pub server: String,
+    pub rank: String,
+    pub price: f64,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub offer_id: Option<String>,
+    #[serde(default)]
+    pub relation_id: Option<String>,
+    /// Последний этап, до которого дошло выставление
+    /// (reading, creating, updating, uploading, finishing, done).
+    pub stage: String,
+    /// "success" или "failed".
+    pub status: String,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub duration_ms: u64,
+}
+
+/// Фильтр журнала. Даты — "YYYY-MM-DD" (включительно) или RFC 3339.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Подстрока имени аккаунта, логина или offer_id.
+    #[serde(default)]
+    pub search: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl ListingRecord {
+    pub fn new(source: &str, account_path: &str, server: &str, rank: &str, price: f64) -> Self {
+        let timestamp = Local::now();
+        let account_name = PathBuf::from(account_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string();
+        Self {
+            id: format!("{}-{:04x}", timestamp.format("%Y%m%d%H%M%S%3f"), rand::random::<u16>()),
+            timestamp,
+            source: source.to_string(),
+            account_name,
+            account_path: account_path.to_string(),
+            login: None,
+            server: server.to_string(),
+            rank: rank.to_string(),
+            price,
+            title: String::new(),
+            category: None,
+            offer_id: None,
+            relation_id: None,
+            stage: "reading".to_string(),
+            status: "failed".to_string(),
+            error: None,
+            duration_ms: 0,
+        }
+    }
+
+    /// Фиксирует итог попытки (успех или ошибку на текущем этапе).
+    pub fn finish(&mut self, result: &Result<String, String>) {
+        self.duration_ms = (Local::now() - self.timestamp).num_milliseconds().max(0) as u64;
+        match result {
+            Ok(offer_id) => {
+                self.offer_id = Some(offer_id.clone());
+                self.stage = "done".to_string();
+                self.status = "success".to_string();
+                self.error = None;
+            }
+            Err(e) => {
+                self.status = "failed".to_string();
+                // Текст ошибки может содержать ответ G2G с данными аккаунта
+                self.error = Some(crate::redact::redact(e));
+            }
+        }
+    }
+}
+
+// Начало/конец интервала: дата без времени означает весь день.
+fn parse_bound(value: &str, end_of_day: bool) -> Result<DateTime<Local>, String> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt.with_timezone(&Local));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Некорректная дата: {}", value))?;
+    let time = if end_of_day {
+        date.and_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    time.and_then(|t| t.and_local_timezone(Local).earliest())
+        .ok_or_else(|| format!("Некорректная дата: {}", value))
+}
+
+impl HistoryQuery {
+    /// Отбирает записи по фильтру; новые — первыми.
+    pub fn apply(&self, records: Vec<ListingRecord>) -> Result<Vec<ListingRecord>, String> {
+        let from = self.from.as_deref().filter(|s| !s.trim().is_empty()).map(|s| parse_bound(s, false)).transpose()?;
+        let to = self.to.as_deref().filter(|s| !s.trim().is_empty()).map(|s| parse_bound(s, true)).transpose()?;
+        let server = self.server.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let status = self.status.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let search = self.search.as_deref().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+
+        let mut matched: Vec<ListingRecord> = records
+            .into_iter()
+            .filter(|r| from.map(|f| r.timestamp >= f).unwrap_or(true))
+            .filter(|r| to.map(|t| r.timestamp <= t).unwrap_or(true))
+            .filter(|r| server.map(|s| r.server.eq_ignore_ascii_case(s)).unwrap_or(true))
+            .filter(|r| status.map(|s| r.status.eq_ignore_ascii_case(s)).unwrap_or(true))
+            .filter(|r| match &search {
+                Some(needle) => [Some(&r.account_name), r.login.as_ref(), r.offer_id.as_ref()]
+                    .iter()
+                    .flatten()
+                    .any(|v| v.to_lowercase().contains(needle)),
+                None => true,
+            })
+            .collect();
+
+        matched.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+        if let Some(limit) = self.limit {
+            matched.truncate(limit);
+        }
+        Ok(matched)
+    }
+}
+
+pub fn history_path() -> Result<PathBuf, String> {
+    Ok(config::config_dir()?.join(HISTORY_FILE_NAME))
+}
+
+// Журнал — JSON Lines: запись дописывается одной строкой, поэтому сбой во
+// время записи портит максимум последнюю строку, а не весь файл.
+pub fn append_to(path: &Path, record: &ListingRecord) -> Result<(), String> {
+    let _guard = HISTORY_LOCK.lock().map_err(|_| "History lock poisoned".to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create history directory: {}", e))?;
+    }
+    let line = serde_json::to_string(record)
+        .map_err(|e| format!("Failed to serialize history record: {}", e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open listing history: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write listing history: {}", e))
+}
+
+pub fn read_from(path: &Path) -> Result<Vec<ListingRecord>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read listing history: {}", e))?;
+    let mut records = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<ListingRecord>(line) {
+            Ok(record) => records.push(record),
+            Err(e) => warn!("Пропущена повреждённая строка журнала {}: {}", index + 1, e),
+        }
+    }
+    Ok(records)
+}
+
+/// Записывает попытку выставления в журнал. Best-effort: ошибка записи
+/// журнала только логируется и не влияет на результат выставления.
+pub fn record(record: &ListingRecord) {
+    let result = history_path().and_then(|path| append_to(&path, record));
+    match result {
+        Ok(()) => info!("Listing history: {} ({}, stage {})", record.account_name, record.status, record.stage),
+        Err(e) => warn!("Не удалось записать журнал выставлений: {}", e),
+    }
+}
+
+pub fn query(query: &HistoryQuery) -> Result<Vec<ListingRecord>, String> {
+    query.apply(read_from(&history_path()?)?)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn to_csv(records: &[ListingRecord]) -> String {
+    let mut out = String::from(
+        "timestamp,source,account_name,login,server,rank,price,title,category,offer_id,relation_id,stage,status,error,duration_ms,account_path\n",
+    );
+    for r in records {
+        let fields = [
+            r.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            r.source.clone(),
+            r.account_name.clone(),
+            r.login.clone().unwrap_or_default(),
+            r.server.clone(),
+            r.rank.clone(),
+            format!("{:.2}", r.price),
+            r.title.clone(),
+            r.category.clone().unwrap_or_default(),
+            r.offer_id.clone().unwrap_or_default(),
+            r.relation_id.clone().unwrap_or_default(),
+            r.stage.clone(),
+            r.status.clone(),
+            r.error.clone().unwrap_or_default(),
+            r.duration_ms.to_string(),
+            r.account_path.clone(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Экспорт отфильтрованного журнала в файл ("csv" или "json").
+/// Возвращает число выгруженных записей.
+pub fn export(query: &HistoryQuery, format: &str, output_path: &Path) -> Result<usize, String> {
+    let records = self::query(query)?;
+    let content = match format.trim().to_lowercase().as_str() {
+        "csv" => to_csv(&records),
+        "json" => serde_json::to_string_pretty(&records)
+            .map_err(|e| format!("Failed to serialize listing history: {}", e))?,
+        other => return Err(format!("Неизвестный формат экспорта: {}", other)),
+    };
+    fs::write(output_path, content)
+        .map_err(|e| format!("Failed to write export {:?}: {}", output_path, e))?;
+    info!("Exported {} history record(s) to {:?}", records.len(), output_path);
+    Ok(records.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_roundtrip_query_and_csv() {
+        let path = std::env::temp_dir().join(format!("g2g-app-history-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        // Дата фиксируется до записи, иначе около полуночи фильтр "сегодня"
+        // разошёлся бы с временем записей
+        let now = Local::now();
+        let today = now.format("%Y-%m-%d").to_string();
+
+        let mut ok = ListingRecord::new("create_listing", "/accounts/EUW/acc1", "EUW", "Gold", 25.0);
+        ok.timestamp = now;
+        ok.login = Some("smurf1".to_string());
+        ok.title = "Title, with \"quotes\"".to_string();
+        ok.finish(&Ok("G1700000001".to_string()));
+        append_to(&path, &ok).unwrap();
+
+        let mut failed = ListingRecord::new("create_g2g_offer", "/accounts/NA/acc2", "NA", "Unranked", 10.0);
+        failed.timestamp = now;
+        failed.stage = "uploading".to_string();
+        failed.finish(&Err("Upload failed".to_string()));
+        append_to(&path, &failed).unwrap();
+
+        let records = read_from(&path).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let failed_only = HistoryQuery { status: Some("failed".to_string()), ..Default::default() }
+            .apply(records.clone())
+            .unwrap();
+        assert_eq!(failed_only.len(), 1);
+        assert_eq!(failed_only[0].stage, "uploading");
+        assert_eq!(failed_only[0].error.as_deref(), Some("Upload failed"));
+
+        let euw_today = HistoryQuery {
+            from: Some(today.clone()),
+            to: Some(today),
+            server: Some("euw".to_string()),
+            search: Some("SMURF".to_string()),
+            ..Default::default()
+        }
+        .apply(records.clone())
+        .unwrap();
+        assert_eq!(euw_today.len(), 1);
+        assert_eq!(euw_today[0].offer_id.as_deref(), Some("G1700000001"));
+
+        let csv = to_csv(&euw_today);
+        assert!(csv.contains("\"Title, with \"\"quotes\"\"\""));
+        assert_eq!(csv.lines().count(), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ImageHostSettings;
+use tracing::{info, warn};
+
+/// Расширения локальных скриншотов, которые загружаются на image-хост.
+const LOCAL_IMAGE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Находит локальные скриншоты (PNG/JPG) в папке аккаунта, отсортированные по имени.
+pub fn find_local_images(account_path: &Path) -> Vec<PathBuf> {
+    let mut images: Vec<PathBuf> = match fs::read_dir(account_path) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .map(|ext| LOCAL_IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                        .unwrap_or(false)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    images.sort();
+    images
+}
+
+/// Загружает один файл на image-хост (multipart POST) и возвращает публичный URL,
+/// взятый из JSON-ответа по `url_pointer`.
+pub async fn upload_image(settings: &ImageHostSettings, file_path: &Path) -> Result<String, String> {
+    let bytes = fs::read(file_path)
+        .map_err(|e| format!("Failed to read image {:?}: {}", file_path, e))?;
+
+    let file_name = file_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("screenshot.png")
+        .to_string();
+
+    let mime = match file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .as_deref()
+    {
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        _ => "image/png",
+    };
+
+    let part = reqwest::multipart::Part::bytes(bytes)
+        .file_name(file_name.clone())
+        .mime_str(mime)
+        .map_err(|e| format!("Failed to build upload part: {}", e))?;
+    let form = reqwest::multipart::Form::new().part(settings.file_field.clone(), part);
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let mut request = client.post(&settings.upload_url).multipart(form);
+    if let Some(auth) = settings.auth_header.as_deref().filter(|a| !a.trim().is_empty()) {
+        request = request.header("Authorization", auth.trim());
+    }
+
+    info!("Uploading {} to image host...", file_name);
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to upload image: {}", e))?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read image host response: {}", e))?;
+
+    if !status.is_success() {
+        return Err(format!("Image host returned {}: {}", status, body.chars().take(200).collect::<String>()));
+    }
+
+    let json: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| format!("Failed to parse image host response: {} | Body: {}", e, body.chars().take(200).collect::<String>()))?;
+
+    let url = json
+        .pointer(&settings.url_pointer)
+        .and_then(|v| v.as_str())
+        .filter(|u| u.starts_with("http"))
+        .ok_or_else(|| format!("No image URL at '{}' in image host response", settings.url_pointer))?;
+
+    info!("Uploaded {} → {}", file_name, url);
+    Ok(url.to_string())
+}
+
+/// Загружает все локальные скриншоты из папки аккаунта. Best-effort: файлы,
+/// которые не удалось загрузить, пропускаются с предупреждением.
+pub async fn upload_account_images(settings: &ImageHostSettings, account_path: &Path) -> Vec<String> {
+    let images = find_local_images(account_path);
+    if images.is_empty() {
+        info!("No local screenshots in account folder");
+        return Vec::new();
+    }
+
+    let mut urls = Vec::with_capacity(images.len());
+    for image in &images {
+        match upload_image(settings, image).await {
+            Ok(url) => urls.push(url),
+            Err(e) => warn!("Не удалось загрузить скриншот {:?}: {}", image, e),
+        }
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Локальная замена image-хоста: принимает multipart POST и отвечает
+    // JSON в формате imgur ({"data":{"link":...}}).
+    async fn spawn_stand_in_host() -> String {
+        let mut counter = 0;
+        let base = crate::test_http::spawn(move |request| {
+            counter += 1;
+            let body = if request.body.contains("name=\"image\"") {
+                format!("{{\"data\":{{\"link\":\"https://img.example/{}.png\"}}}}", counter)
+            } else {
+                "{\"error\":\"no image field\"}".to_string()
+            };
+            ("200 OK", body)
+        })
+        .await;
+        format!("{}/upload", base)
+    }
+
+    #[tokio::test]
+    async fn test_upload_account_images() {
+        let dir = std::env::temp_dir().join(format!("g2g-app-images-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("b.jpg"), b"jpg").unwrap();
+        fs::write(dir.join("a.png"), b"png").unwrap();
+        fs::write(dir.join("account.txt"), b"Login: test").unwrap();
+
+        let settings = ImageHostSettings {
+            upload_url: spawn_stand_in_host().await,
+            file_field: "image".to_string(),
+            auth_header: None,
+            url_pointer: "/data/link".to_string(),
+        };
+
+        let found = find_local_images(&dir);
+        assert_eq!(found, vec![dir.join("a.png"), dir.join("b.jpg")]);
+
+        let urls = upload_account_images(&settings, &dir).await;
+        assert_eq!(urls, vec!["https://img.example/1.png", "https://img.example/2.png"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
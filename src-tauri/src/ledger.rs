@@ -0,0 +1,439 @@
+use calamine::Reader;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::info;
+
+use crate::sheet_backend::{BackendFuture, SheetBackend};
+use crate::sheets::{SheetAppendResult, SheetEntry, SheetRow, SheetUpdate, SheetUpdateResult};
+
+// Колонки нового файла; Price, Sold Date и дополнительные добавляются по мере записи.
+const BASE_HEADERS: &[&str] = &["Username", "Offer ID", "Listed Date", "Status", "Folder"];
+const XLSX_SHEET_NAME: &str = "Listings";
+// Excel без BOM читает UTF-8 CSV как ANSI и ломает кириллицу в путях.
+const UTF8_BOM: &str = "\u{feff}";
+
+// Чтение-изменение-запись файла внутри процесса.
+static LEDGER_LOCK: Mutex<()> = Mutex::new(());
+
+/// Формат файла-журнала по расширению.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerFormat {
+    Csv,
+    Xlsx,
+}
+
+impl LedgerFormat {
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("csv") => Ok(Self::Csv),
+            Some("xlsx") => Ok(Self::Xlsx),
+            _ => Err(format!("Файл-журнал должен быть .csv или .xlsx: {}", path.display())),
+        }
+    }
+}
+
+// Содержимое файла: первая строка — заголовки.
+#[derive(Debug, Default)]
+struct Ledger {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Ledger {
+    fn from_grid(mut grid: Vec<Vec<String>>) -> Self {
+        if grid.is_empty() {
+            return Self { headers: BASE_HEADERS.iter().map(|h| h.to_string()).collect(), rows: Vec::new() };
+        }
+        let headers = grid.remove(0);
+        Self { headers, rows: grid }
+    }
+
+    fn col(&self, name: &str) -> Option<usize> {
+        let name = name.trim().to_lowercase();
+        self.headers.iter().position(|h| h.trim().to_lowercase() == name)
+    }
+
+    // Колонка по заголовку; если её нет — добавляется в конец.
+    fn col_or_add(&mut self, name: &str) -> usize {
+        self.col(name).unwrap_or_else(|| {
+            self.headers.push(name.trim().to_string());
+            self.headers.len() - 1
+        })
+    }
+
+    fn get(&self, row: usize, col: usize) -> &str {
+        self.rows[row].get(col).map(|v| v.trim()).unwrap_or_default()
+    }
+
+    fn set(&mut self, row: usize, col: usize, value: &str) {
+        let cells = &mut self.rows[row];
+        if cells.len() <= col {
+            cells.resize(col + 1, String::new());
+        }
+        cells[col] = value.to_string();
+    }
+
+    fn last_row_where(&self, col: Option<usize>, pred: impl Fn(&str) -> bool) -> Option<usize> {
+        let col = col?;
+        (0..self.rows.len()).rev().find(|&row| pred(self.get(row, col)))
+    }
+
+    fn find(&self, offer_id: Option<&str>, username: Option<&str>) -> Option<usize> {
+        let offer_id = offer_id.map(str::trim).filter(|s| !s.is_empty());
+        let username = username.map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+        offer_id
+            .and_then(|offer| self.last_row_where(self.col("Offer ID"), |v| v == offer))
+            .or_else(|| username.and_then(|user| self.last_row_where(self.col("Username"), |v| v.to_lowercase() == user)))
+    }
+
+    fn append(&mut self, row: &SheetRow) -> usize {
+        // Та же логика, что у скрипта: строка с тем же логином и пустым Offer ID
+        // заполняется. Строка с тем же (непустым) Offer ID перезаписывается —
+        // повтор из очереди не создаёт дубликат.
+        let offer_col = self.col("Offer ID");
+        let user = row.username.trim().to_lowercase();
+        let target = Some(row.offer_id.trim())
+            .filter(|offer| !offer.is_empty())
+            .and_then(|offer| self.last_row_where(offer_col, |v| v == offer))
+            .or_else(|| {
+                (0..self.rows.len()).rev().find(|&r| {
+                    self.col("Username").is_some_and(|c| self.get(r, c).to_lowercase() == user)
+                        && offer_col.is_none_or(|c| self.get(r, c).is_empty())
+                })
+            });
+        let index = target.unwrap_or_else(|| {
+            self.rows.push(Vec::new());
+            self.rows.len() - 1
+        });
+
+        let mut values = vec![
+            ("Username", row.username.as_str()),
+            ("Offer ID", row.offer_id.as_str()),
+            ("Listed Date", row.listed_date.as_str()),
+            ("Status", row.status.as_str()),
+            ("Folder", row.folder.as_str()),
+        ];
+        values.extend(row.columns.iter().map(|(header, value)| (header.as_str(), value.as_str())));
+        for (header, value) in values {
+            let col = self.col_or_add(header);
+            self.set(index, col, value);
+        }
+        index
+    }
+
+    fn update(&mut self, update: &SheetUpdate) -> Option<usize> {
+        let index = self.find(update.offer_id.as_deref(), update.username.as_deref())?;
+        let mut values: Vec<(&str, &String)> = Vec::new();
+        if let Some(status) = &update.status {
+            values.push(("Status", status));
+        }
+        if let Some(price) = &update.price {
+            values.push(("Price", price));
+        }
+        if let Some(sold_date) = &update.sold_date {
+            values.push(("Sold Date", sold_date));
+        }
+        values.extend(update.columns.iter().map(|(header, value)| (header.as_str(), value)));
+        for (header, value) in values {
+            let col = self.col_or_add(header);
+            self.set(index, col, value);
+        }
+        Some(index)
+    }
+
+    fn entries(&self) -> Vec<SheetEntry> {
+        let (user, status, offer) = (self.col("Username"), self.col("Status"), self.col("Offer ID"));
+        let cell = |row: usize, col: Option<usize>| {
+            col.map(|c| self.get(row, c).to_string()).filter(|v| !v.is_empty())
+        };
+        (0..self.rows.len())
+            .filter_map(|row| {
+                Some(SheetEntry { username: cell(row, user)?, status: cell(row, status), offer_id: cell(row, offer) })
+            })
+            .collect()
+    }
+}
+
+/// Локальный файл вместо Google-таблицы (или параллельно с ней): те же строки
+/// SheetRow в CSV или XLSX. Колонки ищутся по заголовкам, как в скрипте;
+/// файл переписывается целиком, поэтому оформление XLSX не сохраняется.
+#[derive(Debug, Clone)]
+pub struct LocalLedgerBackend {
+    path: PathBuf,
+    format: LedgerFormat,
+}
+
+impl LocalLedgerBackend {
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let format = LedgerFormat::from_path(&path)?;
+        Ok(Self { path, format })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read(&self) -> Result<Ledger, String> {
+        if !self.path.exists() {
+            return Ok(Ledger::from_grid(Vec::new()));
+        }
+        let grid = match self.format {
+            LedgerFormat::Csv => self.read_csv()?,
+            LedgerFormat::Xlsx => self.read_xlsx()?,
+        };
+        Ok(Ledger::from_grid(grid))
+    }
+
+    fn read_csv(&self) -> Result<Vec<Vec<String>>, String> {
+        let content = fs::read_to_string(&self.path).map_err(|e| format!("Failed to read ledger {:?}: {}", self.path, e))?;
+        let content = content.strip_prefix(UTF8_BOM).unwrap_or(&content);
+        csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(content.as_bytes())
+            .records()
+            .map(|record| {
+                record
+                    .map(|r| r.iter().map(str::to_string).collect())
+                    .map_err(|e| format!("Failed to parse ledger {:?}: {}", self.path, e))
+            })
+            .collect()
+    }
+
+    fn read_xlsx(&self) -> Result<Vec<Vec<String>>, String> {
+        let mut workbook: calamine::Xlsx<_> =
+            calamine::open_workbook(&self.path).map_err(|e| format!("Failed to open ledger {:?}: {}", self.path, e))?;
+        // Файл переписывается целиком с одним листом — чужие листы пропали бы
+        let sheets = workbook.sheet_names();
+        if sheets.len() > 1 {
+            return Err(format!(
+                "В файле-журнале {:?} несколько листов ({}): журнал перезаписывает файл целиком, укажи отдельный файл",
+                self.path,
+                sheets.join(", ")
+            ));
+        }
+        let Some(range) = workbook.worksheet_range_at(0) else {
+            return Ok(Vec::new());
+        };
+        let range = range.map_err(|e| format!("Failed to read ledger {:?}: {}", self.path, e))?;
+        // Диапазон начинается с первой непустой ячейки — возвращаем её на место
+        let (start_row, start_col) = range.start().unwrap_or((0, 0));
+        let mut grid = vec![Vec::new(); start_row as usize];
+        grid.extend(range.rows().map(|cells| {
+            let mut row = vec![String::new(); start_col as usize];
+            row.extend(cells.iter().map(|c| c.to_string()));
+            row
+        }));
+        Ok(grid)
+    }
+
+    // Через временный файл и rename: сбой посреди записи не портит журнал.
+    fn write(&self, ledger: &Ledger) -> Result<(), String> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create ledger directory: {}", e))?;
+        }
+        let tmp = self.path.with_extension(match self.format {
+            LedgerFormat::Csv => "tmp.csv",
+            LedgerFormat::Xlsx => "tmp.xlsx",
+        });
+        match self.format {
+            LedgerFormat::Csv => self.write_csv(ledger, &tmp)?,
+            LedgerFormat::Xlsx => self.write_xlsx(ledger, &tmp)?,
+        }
+        fs::rename(&tmp, &self.path)
+            .map_err(|e| format!("Failed to replace ledger {:?} (файл открыт в другой программе?): {}", self.path, e))
+    }
+
+    fn write_csv(&self, ledger: &Ledger, path: &Path) -> Result<(), String> {
+        let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(UTF8_BOM.as_bytes().to_vec());
+        for record in std::iter::once(&ledger.headers).chain(&ledger.rows) {
+            writer.write_record(record).map_err(|e| format!("Failed to write ledger: {}", e))?;
+        }
+        let bytes = writer.into_inner().map_err(|e| format!("Failed to write ledger: {}", e))?;
+        fs::write(path, bytes).map_err(|e| format!("Failed to write ledger {:?}: {}", path, e))
+    }
+
+    fn write_xlsx(&self, ledger: &Ledger, path: &Path) -> Result<(), String> {
+        let map_err = |e: rust_xlsxwriter::XlsxError| format!("Failed to write ledger: {}", e);
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let bold = rust_xlsxwriter::Format::new().set_bold();
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(XLSX_SHEET_NAME).map_err(map_err)?;
+        for (col, header) in ledger.headers.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, header, &bold).map_err(map_err)?;
+        }
+        for (row, cells) in ledger.rows.iter().enumerate() {
+            for (col, value) in cells.iter().enumerate().filter(|(_, v)| !v.is_empty()) {
+                sheet.write_string(row as u32 + 1, col as u16, value).map_err(map_err)?;
+            }
+        }
+        workbook.save(path).map_err(map_err)
+    }
+
+    // Файл читается и переписывается целиком: в async-коде — не в потоке рантайма.
+    async fn blocking<T: Send + 'static>(
+        &self,
+        task: impl FnOnce(&Self) -> Result<T, String> + Send + 'static,
+    ) -> Result<T, String> {
+        let ledger = self.clone();
+        tokio::task::spawn_blocking(move || task(&ledger))
+            .await
+            .map_err(|e| format!("Ledger task failed: {}", e))?
+    }
+
+    fn modify<T>(&self, change: impl FnOnce(&mut Ledger) -> T) -> Result<T, String> {
+        let _guard = LEDGER_LOCK.lock().map_err(|_| "Ledger lock poisoned".to_string())?;
+        let mut ledger = self.read()?;
+        let result = change(&mut ledger);
+        self.write(&ledger)?;
+        Ok(result)
+    }
+
+    pub fn append(&self, row: &SheetRow) -> Result<(), String> {
+        let index = self.modify(|ledger| ledger.append(row))?;
+        info!("Ledger {:?}: row {} written for offer {}", self.path, index + 2, row.offer_id);
+        Ok(())
+    }
+
+    /// Несколько строк за одну перезапись файла.
+    pub fn append_all(&self, rows: &[SheetRow]) -> Result<Vec<usize>, String> {
+        let indexes = self.modify(|ledger| rows.iter().map(|row| ledger.append(row)).collect::<Vec<_>>())?;
+        info!("Ledger {:?}: {} row(s) written", self.path, rows.len());
+        Ok(indexes)
+    }
+
+    pub fn rows(&self) -> Result<Vec<SheetEntry>, String> {
+        let _guard = LEDGER_LOCK.lock().map_err(|_| "Ledger lock poisoned".to_string())?;
+        Ok(self.read()?.entries())
+    }
+
+    pub fn update(&self, update: &SheetUpdate) -> Result<SheetUpdateResult, String> {
+        update.validate()?;
+        let _guard = LEDGER_LOCK.lock().map_err(|_| "Ledger lock poisoned".to_string())?;
+        let mut ledger = self.read()?;
+        let Some(index) = ledger.update(update) else {
+            return Ok(SheetUpdateResult::default());
+        };
+        self.write(&ledger)?;
+        Ok(SheetUpdateResult {
+            found: true,
+            sheet: self.path.file_name().map(|n| n.to_string_lossy().to_string()),
+            row: Some(index as u32 + 2),
+            missing_columns: Vec::new(),
+        })
+    }
+}
+
+impl SheetBackend for LocalLedgerBackend {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn append_row<'a>(&'a self, row: &'a SheetRow) -> BackendFuture<'a, ()> {
+        let row = row.clone();
+        Box::pin(async move { self.blocking(move |ledger| ledger.append(&row)).await })
+    }
+
+    fn append_rows<'a>(&'a self, rows: &'a [SheetRow]) -> BackendFuture<'a, Vec<SheetAppendResult>> {
+        Box::pin(async move {
+            let owned = rows.to_vec();
+            let indexes = self.blocking(move |ledger| ledger.append_all(&owned)).await?;
+            Ok(rows
+                .iter()
+                .zip(indexes)
+                .map(|(row, index)| SheetAppendResult {
+                    offer_id: row.offer_id.clone(),
+                    ok: true,
+                    error: None,
+                    row: Some(index as u32 + 2),
+                })
+                .collect())
+        })
+    }
+
+    fn fetch_rows(&self) -> BackendFuture<'_, Vec<SheetEntry>> {
+        Box::pin(async move { self.blocking(|ledger| ledger.rows()).await })
+    }
+
+    fn update_row<'a>(&'a self, update: &'a SheetUpdate) -> BackendFuture<'a, SheetUpdateResult> {
+        let update = update.clone();
+        Box::pin(async move { self.blocking(move |ledger| ledger.update(&update)).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(username: &str, offer_id: &str) -> SheetRow {
+        SheetRow {
+            username: username.to_string(),
+            offer_id: offer_id.to_string(),
+            listed_date: "2026-01-16 12:00:00".to_string(),
+            folder: "/аккаунты/EUW".to_string(),
+            status: "Active".to_string(),
+            columns: [("Price".to_string(), "25.00".to_string())].into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn test_local_ledger_csv_and_xlsx() {
+        assert!(LocalLedgerBackend::new("ledger.txt").is_err());
+
+        for ext in ["csv", "xlsx"] {
+            let path = std::env::temp_dir().join(format!("g2g-app-ledger-{}.{}", std::process::id(), ext));
+            let _ = fs::remove_file(&path);
+            let ledger = LocalLedgerBackend::new(&path).unwrap();
+
+            ledger.append(&row("smurf1", "G1700000001")).unwrap();
+            ledger.append(&row("smurf2", "")).unwrap();
+            // Пустой Offer ID не совпадает с пустой ячейкой чужой строки
+            ledger.append(&row("smurf3", "")).unwrap();
+            // Строка smurf2 с пустым Offer ID заполняется, повтор G1700000001 не дублируется
+            ledger.append(&row("Smurf2", "G1700000002")).unwrap();
+            ledger.append(&row("smurf1", "G1700000001")).unwrap();
+
+            let update = SheetUpdate {
+                offer_id: Some("G1700000001".to_string()),
+                status: Some("Sold".to_string()),
+                sold_date: Some("2026-01-20".to_string()),
+                ..Default::default()
+            };
+            let result = ledger.update(&update).unwrap();
+            assert!(result.found);
+            assert_eq!(result.row, Some(2));
+            let missing = SheetUpdate { username: Some("nobody".to_string()), ..Default::default() };
+            assert!(!ledger.update(&missing).unwrap().found);
+
+            let rows = ledger.rows().unwrap();
+            assert_eq!(rows.len(), 3, "{}", ext);
+            assert_eq!(rows[0].status.as_deref(), Some("Sold"));
+            assert_eq!(rows[1].username, "Smurf2");
+            assert_eq!(rows[1].offer_id.as_deref(), Some("G1700000002"));
+            assert_eq!((rows[2].username.as_str(), rows[2].offer_id.as_deref()), ("smurf3", None));
+
+            let _guard = LEDGER_LOCK.lock().unwrap();
+            let content = ledger.read().unwrap();
+            assert_eq!(content.headers, ["Username", "Offer ID", "Listed Date", "Status", "Folder", "Price", "Sold Date"]);
+            assert_eq!(content.get(0, 4), "/аккаунты/EUW");
+            assert_eq!(content.get(0, 6), "2026-01-20");
+            drop(_guard);
+
+            fs::remove_file(&path).unwrap();
+        }
+
+        // Рабочая книга с чужими листами не перезаписывается
+        let path = std::env::temp_dir().join(format!("g2g-app-ledger-sheets-{}.xlsx", std::process::id()));
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        workbook.add_worksheet().set_name("Listings").unwrap();
+        workbook.add_worksheet().set_name("Expenses").unwrap().write_string(0, 0, "keep me").unwrap();
+        workbook.save(&path).unwrap();
+        let error = LocalLedgerBackend::new(&path).unwrap().append(&row("smurf1", "G1700000001")).unwrap_err();
+        assert!(error.contains("Expenses"), "{}", error);
+        let kept: calamine::Xlsx<_> = calamine::open_workbook(&path).unwrap();
+        assert_eq!(kept.sheet_names(), ["Listings", "Expenses"]);
+        fs::remove_file(&path).unwrap();
+    }
+}
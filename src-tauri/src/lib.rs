@@ -1,14 +1,50 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
-}
-
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
+//! Логика приложения g2g-app: папки аккаунтов, выставление офферов на G2G,
+//! цены скинов, синхронизация с Google-таблицей. Используется десктопным
+//! приложением (Tauri) и консольной утилитой `g2g-cli`.
+//!
+//! Основные точки входа:
+//! - [`AccountStore`] — папка с аккаунтами: список, проверка, выставленные;
+//! - [`ListingService`] — выставление офферов на G2G;
+//! - [`PriceService`] — цены скинов с отменой;
+//! - [`SheetSync`] — проверка и дозапись аккаунтов в Google-таблицу
+//!   (веб-хук Apps Script или Sheets API, см. [`sheet_backend`]);
+//! - [`SheetOutbox`] — очередь строк таблицы, которые не удалось записать сразу.
+//!
+//! Настройки (токены G2G, веб-хук таблицы, шаблоны) читаются из директории
+//! конфигурации приложения, как и в десктопной версии — см. [`config`].
+
+pub mod account_info;
+pub mod accounts;
+pub mod category;
+pub mod config;
+pub mod g2g_api;
+pub mod history;
+pub mod image_host;
+pub mod ledger;
+pub mod listing;
+pub mod listing_text;
+pub mod logging;
+pub mod pricing;
+pub mod reconcile;
+pub mod redact;
+pub mod sheet_backend;
+pub mod sheet_diagnose;
+pub mod sheet_outbox;
+pub mod sheet_sync;
+pub mod sheets;
+pub mod sheets_api;
+pub mod usage;
+pub mod watcher;
+
+#[cfg(test)]
+mod test_http;
+
+pub use accounts::AccountStore;
+pub use listing::ListingService;
+pub use pricing::PriceService;
+pub use sheet_outbox::SheetOutbox;
+pub use sheet_sync::SheetSync;
+
+/// Колбэк прогресса долгих операций (выставление, расчёт цен): в приложении
+/// отправляет событие во фронтенд, в CLI — пишет в лог.
+pub type ProgressCallback<T> = std::sync::Arc<dyn Fn(T) + Send + Sync>;
@@ -0,0 +1,457 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::accounts;
+use crate::category::{self, MappingDiffReport};
+use crate::config::{AppSettings, OfferTemplateOverride, OfferTemplateSettings};
+use crate::g2g_api::{G2GApiClient, G2GAuthTokens, OfferStatus};
+use crate::history::{self, ListingRecord};
+use crate::sheet_sync::{self, OfferFields};
+use crate::ProgressCallback;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateOfferRequest {
+    pub title: String,
+    pub description: String,
+    pub price: f64,
+    pub server: String,
+    pub rank: String,
+    pub champions_count: i32,
+    pub skins_count: i32,
+    pub account_path: String,
+    pub account_name: String,
+    /// Переопределение шаблона оффера для этого выставления (необязательно).
+    #[serde(default)]
+    pub offer_template: Option<OfferTemplateOverride>,
+    /// Id категории G2G; по умолчанию — аккаунты League of Legends.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Значения дополнительных полей для атрибутов категорий других игр.
+    #[serde(default)]
+    pub extra_attributes: Option<std::collections::HashMap<String, String>>,
+}
+
+/// Выставление с уже прочитанным текстом файла аккаунта (команда
+/// `create_listing`): данные не перечитываются из папки, прогресса нет.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListingRequest {
+    pub title: String,
+    pub description: String,
+    pub price: f64,
+    pub server: String,
+    pub rank: String,
+    pub champions_count: i32,
+    pub skins_count: i32,
+    /// Полный текст файла аккаунта ({name}.txt).
+    pub personal_info: String,
+    pub account_path: String,
+    #[serde(default)]
+    pub offer_template: Option<OfferTemplateOverride>,
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// Результат проверки офферов на G2G (ListingService::offer_statuses).
+#[derive(Debug, Clone, Default)]
+pub struct OfferCheck {
+    pub statuses: HashMap<String, OfferStatus>,
+    /// Офферы, которые не удалось проверить (ошибка запроса или неожиданный
+    /// ответ G2G); они не считаются пропавшими.
+    pub unchecked: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListingProgressPayload {
+    pub stage: String,
+    pub current: usize,
+    pub total: usize,
+    pub message: String,
+}
+
+// Шаблон оффера из настроек (или значения по умолчанию) с учётом
+// переопределения из запроса на выставление.
+pub fn load_offer_template(
+    over: Option<&OfferTemplateOverride>,
+) -> Result<OfferTemplateSettings, String> {
+    let base = AppSettings::load()
+        .ok()
+        .and_then(|s| s.offer_template)
+        .unwrap_or_default();
+    let template = match over {
+        Some(o) => base.merged(o),
+        None => base,
+    };
+    template.validate()?;
+    Ok(template)
+}
+
+/// Выставление аккаунтов на G2G. Клиент API общий с [`crate::pricing::PriceService`],
+/// поэтому токен доступа обновляется один раз на оба сервиса.
+#[derive(Clone)]
+pub struct ListingService {
+    client: Arc<Mutex<G2GApiClient>>,
+}
+
+impl ListingService {
+    pub fn new(client: Arc<Mutex<G2GApiClient>>) -> Self {
+        Self { client }
+    }
+
+    /// См. [`create_g2g_offer`].
+    pub async fn create_offer(
+        &self,
+        request: &CreateOfferRequest,
+        on_progress: ProgressCallback<ListingProgressPayload>,
+    ) -> Result<String, String> {
+        create_g2g_offer(&self.client, request, on_progress).await
+    }
+
+    /// Создаёт оффер (create_offer_id, update_offer, upload_account_data) по тексту
+    /// аккаунта из запроса; дальше — как create_offer: файл `{offer_id}.txt`,
+    /// строка в таблице и запись в журнале.
+    pub async fn create_listing(&self, request: &ListingRequest) -> Result<String, String> {
+        let mut record = ListingRecord::new(
+            "create_listing",
+            &request.account_path,
+            &request.server,
+            &request.rank,
+            request.price,
+        );
+        record.login = accounts::extract_login(&request.personal_info);
+        record.title = request.title.clone();
+        record.category = request.category.clone();
+
+        let result = self.create_listing_steps(request, &mut record).await;
+        record.finish(&result);
+        history::record(&record);
+        result
+    }
+
+    async fn create_listing_steps(
+        &self,
+        request: &ListingRequest,
+        record: &mut ListingRecord,
+    ) -> Result<String, String> {
+        info!("Creating listing - received personal_info:");
+        debug!("Length: {} chars", request.personal_info.len());
+
+        // Загружаем настройки динамически
+        let tokens = G2GAuthTokens::from_settings()?;
+
+        let csv_data = accounts::parse_account_to_csv(&request.personal_info)
+            .map_err(|e| format!("Failed to parse account data: {}", e))?;
+
+        info!("Converted to CSV format");
+        debug!("CSV length: {} bytes", csv_data.len());
+
+        let template = load_offer_template(request.offer_template.as_ref())
+            .map_err(|e| format!("Некорректный шаблон оффера: {}", e))?;
+
+        let category = category::load_category(request.category.as_deref())?;
+        let values = category::listing_values(
+            &request.server,
+            &request.rank,
+            request.champions_count,
+            request.skins_count,
+            None,
+        );
+
+        let screenshot_urls =
+            accounts::collect_offer_images(&request.personal_info, &request.account_path).await;
+
+        let mut client = self.client.lock().await;
+
+        record.stage = "creating".to_string();
+        let offer_id = client.create_offer_id(&category, &tokens).await?;
+
+        // Оффер уже существует на G2G — id попадает в журнал, даже если
+        // следующие шаги упадут
+        info!(offer_id = %offer_id, "Offer created");
+        record.offer_id = Some(offer_id.clone());
+        record.stage = "updating".to_string();
+
+        let delay_ms = rand::thread_rng().gen_range(1500..2500);
+        tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+
+        let relation_id = client
+            .update_offer(
+                &offer_id,
+                &request.title,
+                &request.description,
+                request.price,
+                &category,
+                &values,
+                &screenshot_urls,
+                &template,
+                &tokens,
+            )
+            .await?;
+        info!(offer_id = %offer_id, relation_id = %relation_id, "Offer updated");
+        record.relation_id = Some(relation_id.clone());
+
+        record.stage = "uploading".to_string();
+
+        let delay_ms = rand::thread_rng().gen_range(1500..2500);
+        tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+
+        client
+            .upload_account_data(&offer_id, &relation_id, &csv_data, &category, &tokens)
+            .await?;
+        info!("Account data uploaded");
+
+        record.stage = "finishing".to_string();
+
+        accounts::save_offer_id_to_file(&request.account_path, &offer_id)?;
+
+        // Имя аккаунта — это имя папки в account_path (для fallback Username).
+        let fields = OfferFields::from_listing(
+            &record.account_name,
+            &request.title,
+            request.price,
+            &request.server,
+            &request.rank,
+            request.category.as_deref(),
+            request.champions_count,
+            request.skins_count,
+            &request.personal_info,
+        );
+        sheet_sync::sync_offer_to_sheet(&fields, &request.account_path, &offer_id).await;
+
+        Ok(offer_id)
+    }
+
+    /// Сверяет маппинги атрибутов категории с текущими определениями G2G.
+    /// apply = false — только отчёт о расхождениях; apply = true — сохранить
+    /// новую ревизию файла категории в директорию конфигурации.
+    pub async fn refresh_category_mappings(
+        &self,
+        category_id: Option<&str>,
+        apply: bool,
+    ) -> Result<MappingDiffReport, String> {
+        let tokens = G2GAuthTokens::from_settings()?;
+        let category = category::load_category(category_id)?;
+        let remote = {
+            let mut client = self.client.lock().await;
+            client
+                .fetch_attribute_definitions(&category, &tokens)
+                .await?
+        };
+        category::refresh_from_remote(&category, &remote, apply)
+    }
+
+    /// Состояние офферов на G2G (для сверки). Запросы идут по одному с
+    /// паузой; оффер, который не удалось проверить, попадает в unchecked.
+    pub async fn offer_statuses(&self, offer_ids: &[String]) -> Result<OfferCheck, String> {
+        let tokens = G2GAuthTokens::from_settings()?;
+        let mut client = self.client.lock().await;
+        let mut check = OfferCheck::default();
+        for (i, offer_id) in offer_ids.iter().enumerate() {
+            if i > 0 {
+                let delay_ms = rand::thread_rng().gen_range(300..700);
+                tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+            }
+            match client.fetch_offer_status(offer_id, &tokens).await {
+                Ok(status) => {
+                    check.statuses.insert(offer_id.clone(), status);
+                }
+                Err(e) => {
+                    warn!("Не удалось проверить оффер {} на G2G: {}", offer_id, e);
+                    check.unchecked.push(offer_id.clone());
+                }
+            }
+        }
+        info!(
+            "Checked {} of {} offer(s) on G2G",
+            check.statuses.len(),
+            offer_ids.len()
+        );
+        Ok(check)
+    }
+}
+
+/// Создаёт оффер на G2G по данным из папки аккаунта: пустой оффер, атрибуты
+/// и скриншоты, загрузка данных аккаунта (softpin), файл `{offer_id}.txt` и
+/// строка в Google-таблице. Каждая попытка попадает в журнал выставлений.
+pub async fn create_g2g_offer(
+    client: &Mutex<G2GApiClient>,
+    request: &CreateOfferRequest,
+    on_progress: ProgressCallback<ListingProgressPayload>,
+) -> Result<String, String> {
+    let mut record = ListingRecord::new(
+        "create_g2g_offer",
+        &request.account_path,
+        &request.server,
+        &request.rank,
+        request.price,
+    );
+    record.account_name = request.account_name.clone();
+    record.title = request.title.clone();
+    record.category = request.category.clone();
+
+    let result = create_g2g_offer_steps(client, request, &on_progress, &mut record).await;
+    record.finish(&result);
+    history::record(&record);
+    result
+}
+
+// Этапы выставления; record обновляется по ходу, чтобы в журнал попал
+// этап, на котором выставление остановилось, и уже полученные id.
+async fn create_g2g_offer_steps(
+    client: &Mutex<G2GApiClient>,
+    request: &CreateOfferRequest,
+    on_progress: &ProgressCallback<ListingProgressPayload>,
+    record: &mut ListingRecord,
+) -> Result<String, String> {
+    let total_stages = 5;
+
+    // Этап 1: Чтение данных
+    on_progress(ListingProgressPayload {
+        stage: "reading".to_string(),
+        current: 1,
+        total: total_stages,
+        message: "Чтение данных аккаунта...".to_string(),
+    });
+
+    info!("Creating G2G offer...");
+    debug!("Title: {}", request.title);
+    debug!("Server: {}", request.server);
+    debug!("Account path: {}", request.account_path);
+
+    // Загружаем настройки динамически
+
+    let tokens = G2GAuthTokens::from_settings()?;
+
+    let account_file = format!("{}.txt", request.account_name);
+    let account_path = PathBuf::from(&request.account_path);
+    let file_path = account_path.join(&account_file);
+
+    debug!("Reading account data from: {:?}", file_path);
+
+    let raw_content = match fs::read_to_string(&file_path) {
+        Ok(content) => content,
+        Err(e) => {
+            return Err(format!("Failed to read account data file: {}", e));
+        }
+    };
+
+    info!("Account data loaded, {} bytes", raw_content.len());
+    record.login = accounts::extract_login(&raw_content);
+
+    let csv_data = accounts::parse_account_to_csv(&raw_content)
+        .map_err(|e| format!("Failed to parse account to CSV: {}", e))?;
+
+    info!("Converted to CSV, {} bytes", csv_data.len());
+
+    let template = load_offer_template(request.offer_template.as_ref())
+        .map_err(|e| format!("Некорректный шаблон оффера: {}", e))?;
+
+    let category = category::load_category(request.category.as_deref())?;
+    let values = category::listing_values(
+        &request.server,
+        &request.rank,
+        request.champions_count,
+        request.skins_count,
+        request.extra_attributes.as_ref(),
+    );
+
+    let screenshot_urls = accounts::collect_offer_images(&raw_content, &request.account_path).await;
+
+    // Этап 2: Создание оффера
+    record.stage = "creating".to_string();
+    on_progress(ListingProgressPayload {
+        stage: "creating".to_string(),
+        current: 2,
+        total: total_stages,
+        message: "Создание объявления...".to_string(),
+    });
+
+    let mut client = client.lock().await;
+
+    // Создаем пустой оффер
+    let offer_id = client.create_offer_id(&category, &tokens).await?;
+    info!(offer_id = %offer_id, "Offer ID created");
+    record.offer_id = Some(offer_id.clone());
+
+    // Этап 3: Заполнение информации
+    record.stage = "updating".to_string();
+    on_progress(ListingProgressPayload {
+        stage: "updating".to_string(),
+        current: 3,
+        total: total_stages,
+        message: "Заполнение информации...".to_string(),
+    });
+
+    let delay_ms = rand::thread_rng().gen_range(1500..2500);
+    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+
+    // Обновляем оффер и получаем relation_id
+    let relation_id = client
+        .update_offer(
+            &offer_id,
+            &request.title,
+            &request.description,
+            request.price,
+            &category,
+            &values,
+            &screenshot_urls,
+            &template,
+            &tokens,
+        )
+        .await?;
+    info!(offer_id = %offer_id, relation_id = %relation_id, "Offer updated");
+    record.relation_id = Some(relation_id.clone());
+
+    // Этап 4: Загрузка данных
+    record.stage = "uploading".to_string();
+    on_progress(ListingProgressPayload {
+        stage: "uploading".to_string(),
+        current: 4,
+        total: total_stages,
+        message: "Загрузка данных аккаунта...".to_string(),
+    });
+
+    let delay_ms = rand::thread_rng().gen_range(1500..2500);
+    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+
+    // Загружаем данные аккаунта
+    client
+        .upload_account_data(&offer_id, &relation_id, &csv_data, &category, &tokens)
+        .await?;
+    info!("Account data uploaded");
+
+    // Этап 5: Завершение
+    record.stage = "finishing".to_string();
+    on_progress(ListingProgressPayload {
+        stage: "finishing".to_string(),
+        current: 5,
+        total: total_stages,
+        message: "Сохранение...".to_string(),
+    });
+
+    info!(offer_id = %offer_id, "Offer created with data");
+
+    accounts::save_offer_id_to_file(&request.account_path, &offer_id)?;
+
+    // Записываем строку в Google-таблицу (если веб-хук настроен). Best-effort:
+    // ошибка записи в таблицу не должна отменять уже созданный оффер.
+    let fields = OfferFields::from_listing(
+        &request.account_name,
+        &request.title,
+        request.price,
+        &request.server,
+        &request.rank,
+        request.category.as_deref(),
+        request.champions_count,
+        request.skins_count,
+        &raw_content,
+    );
+    sheet_sync::sync_offer_to_sheet(&fields, &request.account_path, &offer_id).await;
+
+    Ok(offer_id)
+}
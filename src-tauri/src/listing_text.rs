@@ -0,0 +1,300 @@
+use handlebars::{handlebars_helper, Handlebars};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config;
+use tracing::{info, warn};
+
+// Встроенные шаблоны: копируются в {config}/templates при первом обращении,
+// дальше пользователь правит файлы там.
+const DEFAULT_TITLE_TEMPLATE: &str = include_str!("../data/templates/title.hbs");
+const DEFAULT_DESCRIPTION_TEMPLATE: &str = include_str!("../data/templates/description.hbs");
+
+/// Лимит длины заголовка оффера на G2G.
+pub const DEFAULT_TITLE_MAX_LENGTH: usize = 128;
+/// Максимум элементов (скинов + чемпионов), после которого чемпионы не добавляются.
+pub const DEFAULT_TITLE_MAX_ITEMS: usize = 10;
+
+/// Распарсенные данные аккаунта (как AccountData в parser.ts).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AccountData {
+    pub server: String,
+    pub level: i64,
+    pub honor_level: i64,
+    pub champions_count: i64,
+    pub champions_list: Vec<String>,
+    pub skins_count: i64,
+    pub skins_list: Vec<String>,
+    pub riot_points: i64,
+    pub blue_essence: i64,
+    pub mythic_essence: i64,
+    pub orange_essence: i64,
+    pub last_play_date: String,
+    pub last_rank: String,
+    pub icons_list: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opgg_link: Option<String>,
+}
+
+/// Результат генерации текста оффера.
+#[derive(Debug, Serialize)]
+pub struct RenderedListing {
+    pub title: String,
+    pub description: String,
+    /// Чемпионы, попавшие в заголовок (для статистики использования).
+    pub used_champions: Vec<String>,
+    /// Скины, попавшие в заголовок.
+    pub used_skins: Vec<String>,
+    pub title_length: usize,
+    pub max_length: usize,
+}
+
+/// Шаблоны заголовка и описания. Пустое поле = шаблон из директории конфигурации.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListingTemplates {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+handlebars_helper!(join: |list: Vec<String>, separator: str| list.join(separator));
+
+fn templates_dir() -> Result<PathBuf, String> {
+    let dir = config::config_dir()?.join("templates");
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create templates directory: {}", e))?;
+    }
+    Ok(dir)
+}
+
+// Имя варианта заголовка — часть имени файла: только [A-Za-z0-9_-].
+fn is_valid_variant(variant: &str) -> bool {
+    !variant.is_empty() && variant.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// Имя файла шаблона заголовка: title.hbs или title-<variant>.hbs (для A/B).
+fn title_file_name(variant: Option<&str>) -> Result<String, String> {
+    match variant.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(v) if is_valid_variant(v) => Ok(format!("title-{}.hbs", v)),
+        Some(v) => Err(format!("Недопустимое имя варианта заголовка: {}", v)),
+        None => Ok("title.hbs".to_string()),
+    }
+}
+
+// Прочитать шаблон из директории конфигурации; встроенный шаблон
+// записывается туда, если файла ещё нет.
+fn read_template(file_name: &str, default: &str) -> Result<String, String> {
+    let path = templates_dir()?.join(file_name);
+    if !path.exists() {
+        if default.is_empty() {
+            return Err(format!("Шаблон не найден: {:?}", path));
+        }
+        fs::write(&path, default)
+            .map_err(|e| format!("Failed to write default template {:?}: {}", path, e))?;
+    }
+    fs::read_to_string(&path).map_err(|e| format!("Failed to read template {:?}: {}", path, e))
+}
+
+/// Шаблоны из директории конфигурации (вариант заголовка — необязателен).
+pub fn load_templates(title_variant: Option<&str>) -> Result<ListingTemplates, String> {
+    let title_file = title_file_name(title_variant)?;
+    // Для вариантов A/B встроенного шаблона нет — файл должен существовать.
+    let title_default = if title_file == "title.hbs" { DEFAULT_TITLE_TEMPLATE } else { "" };
+    Ok(ListingTemplates {
+        title: Some(read_template(&title_file, title_default)?),
+        description: Some(read_template("description.hbs", DEFAULT_DESCRIPTION_TEMPLATE)?),
+    })
+}
+
+/// Сохранить шаблон ("title", "title-<variant>" или "description") после
+/// проверки, что он компилируется.
+pub fn save_template(name: &str, content: &str) -> Result<(), String> {
+    let valid_name = name == "description"
+        || name == "title"
+        || name.strip_prefix("title-").is_some_and(is_valid_variant);
+    if !valid_name {
+        return Err(format!("Недопустимое имя шаблона: {}", name));
+    }
+
+    let mut registry = new_registry();
+    registry
+        .register_template_string(name, content)
+        .map_err(|e| format!("Ошибка в шаблоне: {}", e))?;
+
+    let path = templates_dir()?.join(format!("{}.hbs", name));
+    fs::write(&path, content).map_err(|e| format!("Failed to save template {:?}: {}", path, e))?;
+    info!("Template saved: {:?}", path);
+    Ok(())
+}
+
+fn new_registry() -> Handlebars<'static> {
+    let mut registry = Handlebars::new();
+    // Текст уходит на G2G как есть — HTML-экранирование не нужно.
+    registry.register_escape_fn(handlebars::no_escape);
+    registry.set_strict_mode(false);
+    registry.register_helper("join", Box::new(join));
+    registry
+}
+
+// Контекст шаблона: поля AccountData плюс вычисляемые значения.
+fn template_context(data: &AccountData, items: &[String]) -> Result<serde_json::Value, String> {
+    let mut context = serde_json::to_value(data)
+        .map_err(|e| format!("Failed to build template context: {}", e))?;
+    if let Some(obj) = context.as_object_mut() {
+        let last_rank = data.last_rank.trim();
+        obj.insert(
+            "lastRankKnown".to_string(),
+            serde_json::Value::Bool(!last_rank.is_empty() && !last_rank.eq_ignore_ascii_case("unknown")),
+        );
+        obj.insert("items".to_string(), serde_json::json!(items));
+    }
+    Ok(context)
+}
+
+fn render(registry: &Handlebars, name: &str, data: &AccountData, items: &[String]) -> Result<String, String> {
+    let context = template_context(data, items)?;
+    registry
+        .render(name, &context)
+        .map_err(|e| format!("Ошибка рендеринга шаблона '{}': {}", name, e))
+}
+
+/// Подбирает элементы заголовка под лимит длины: сначала скины по порядку,
+/// затем чемпионы (в переданном порядке — ротация по частоте использования
+/// делается вызывающей стороной). Останавливается на первом не влезшем элементе.
+fn fit_title(
+    registry: &Handlebars,
+    data: &AccountData,
+    champions: &[String],
+    max_length: usize,
+    max_items: usize,
+) -> Result<(String, Vec<String>, Vec<String>), String> {
+    let mut items: Vec<String> = Vec::new();
+    let mut used_champions: Vec<String> = Vec::new();
+    let mut title = render(registry, "title", data, &items)?.trim().to_string();
+
+    if title.chars().count() > max_length {
+        warn!("Базовый заголовок длиннее {} символов", max_length);
+        return Ok((title, used_champions, Vec::new()));
+    }
+
+    for skin in &data.skins_list {
+        items.push(skin.clone());
+        let candidate = render(registry, "title", data, &items)?.trim().to_string();
+        if candidate.chars().count() <= max_length {
+            title = candidate;
+        } else {
+            items.pop();
+            break;
+        }
+    }
+    let used_skins = items.clone();
+
+    for champion in champions {
+        if items.len() >= max_items {
+            break;
+        }
+        items.push(champion.clone());
+        let candidate = render(registry, "title", data, &items)?.trim().to_string();
+        if candidate.chars().count() <= max_length {
+            title = candidate;
+            used_champions.push(champion.clone());
+        } else {
+            items.pop();
+            break;
+        }
+    }
+
+    Ok((title, used_champions, used_skins))
+}
+
+/// Генерирует заголовок и описание оффера по шаблонам.
+/// `champions` — порядок чемпионов для заголовка (по умолчанию championsList).
+pub fn render_listing(
+    data: &AccountData,
+    templates: &ListingTemplates,
+    champions: Option<&[String]>,
+    max_length: usize,
+) -> Result<RenderedListing, String> {
+    let title_template = templates.title.as_deref().unwrap_or(DEFAULT_TITLE_TEMPLATE);
+    let description_template = templates.description.as_deref().unwrap_or(DEFAULT_DESCRIPTION_TEMPLATE);
+
+    let mut registry = new_registry();
+    registry
+        .register_template_string("title", title_template)
+        .map_err(|e| format!("Ошибка в шаблоне заголовка: {}", e))?;
+    registry
+        .register_template_string("description", description_template)
+        .map_err(|e| format!("Ошибка в шаблоне описания: {}", e))?;
+
+    let champions = champions.unwrap_or(&data.champions_list);
+    let (title, used_champions, used_skins) = fit_title(&registry, data, champions, max_length, DEFAULT_TITLE_MAX_ITEMS)?;
+    let description = render(&registry, "description", data, &[])?.trim_end().to_string();
+
+    info!(
+        "Title generated: {} chars, {} champion(s) used",
+        title.chars().count(),
+        used_champions.len()
+    );
+
+    Ok(RenderedListing {
+        title_length: title.chars().count(),
+        title,
+        description,
+        used_champions,
+        used_skins,
+        max_length,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> AccountData {
+        AccountData {
+            server: "EUW".to_string(),
+            level: 35,
+            honor_level: 2,
+            champions_count: 40,
+            champions_list: vec!["Ahri".to_string(), "Annie".to_string(), "Zed".to_string()],
+            skins_count: 2,
+            skins_list: vec!["Star Guardian Ahri".to_string(), "PROJECT: Zed".to_string()],
+            last_play_date: "2025".to_string(),
+            last_rank: "Unknown".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_title_fitting_and_description() {
+        let data = sample();
+        let templates = ListingTemplates::default();
+
+        let rendered = render_listing(&data, &templates, None, DEFAULT_TITLE_MAX_LENGTH).unwrap();
+        assert_eq!(
+            rendered.title,
+            "[EUW ⍜] - [35 LVL | 40 Champions | Star Guardian Ahri | PROJECT: Zed | Ahri | Annie | Zed | Handleveled | Full Access ⍜]"
+        );
+        assert_eq!(rendered.used_champions, vec!["Ahri", "Annie", "Zed"]);
+        assert_eq!(rendered.used_skins, vec!["Star Guardian Ahri", "PROJECT: Zed"]);
+        assert!(rendered.description.contains("⤱ Last Rank: The Account has never been ranked"));
+        assert!(rendered.description.contains("◉ List of Skins:\n\nStar Guardian Ahri, PROJECT: Zed."));
+
+        assert_eq!(title_file_name(None).unwrap(), "title.hbs");
+        assert_eq!(title_file_name(Some(" short_b ")).unwrap(), "title-short_b.hbs");
+        assert!(title_file_name(Some("../settings")).is_err());
+        assert!(load_templates(Some("../../settings")).is_err());
+        assert!(save_template("title-../x", "{{server}}").is_err());
+        assert!(!rendered.description.contains("List of Icons"));
+
+        // Короткий лимит: скины влезают первыми, чемпионы — нет
+        let short = render_listing(&data, &templates, None, 100).unwrap();
+        assert!(short.title_length <= 100);
+        assert!(short.title.contains("Star Guardian Ahri"));
+        assert!(short.used_champions.is_empty());
+    }
+}
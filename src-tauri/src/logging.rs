@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tracing::{error, info};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+use crate::config;
+use crate::redact::RedactingMakeWriter;
+
+/// Сколько файлов логов (по одному на запуск) хранится в {config}/logs.
+/// Логи приложения и g2g-cli считаются и удаляются раздельно.
+pub const MAX_SESSION_LOGS: usize = 10;
+
+const LOG_DIR_NAME: &str = "logs";
+
+/// Префикс файлов логов приложения (session-<время>.log).
+pub const APP_LOG_PREFIX: &str = "session-";
+/// Префикс файлов логов g2g-cli: частые запуски из скриптов не должны
+/// вытеснять логи приложения.
+pub const CLI_LOG_PREFIX: &str = "cli-";
+
+// Фильтр файла по умолчанию: debug для кода приложения, info для зависимостей.
+// Переопределяется переменной окружения G2G_LOG (синтаксис RUST_LOG).
+const DEFAULT_FILE_FILTER: &str = "info,g2g_app=debug,g2g_app_lib=debug,g2g_cli=debug";
+
+static CURRENT_LOG: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn log_dir() -> Result<PathBuf, String> {
+    Ok(config::config_dir()?.join(LOG_DIR_NAME))
+}
+
+/// Файлы логов с префиксом prefix, от старых к новым (имя содержит время запуска).
+pub fn session_logs(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let mut logs: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.starts_with(prefix) && n.ends_with(".log"))
+                    .unwrap_or(false)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    logs.sort();
+    logs
+}
+
+// Удаляет старые логи с префиксом prefix, оставляя `keep` последних.
+fn prune_logs(dir: &Path, prefix: &str, keep: usize) {
+    let logs = session_logs(dir, prefix);
+    if logs.len() > keep {
+        for old in &logs[..logs.len() - keep] {
+            let _ = fs::remove_file(old);
+        }
+    }
+}
+
+/// Включает логирование в файл новой сессии ({config}/logs/session-<время>.log).
+/// В debug-сборке логи дублируются в консоль. Возвращённый guard нужно держать
+/// до выхода из приложения — он дописывает буфер в файл.
+pub fn init() -> Result<WorkerGuard, String> {
+    init_with_prefix(APP_LOG_PREFIX)
+}
+
+/// То же для g2g-cli: файлы {config}/logs/cli-<время>.log со своей ротацией.
+pub fn init_cli() -> Result<WorkerGuard, String> {
+    init_with_prefix(CLI_LOG_PREFIX)
+}
+
+fn init_with_prefix(prefix: &str) -> Result<WorkerGuard, String> {
+    let dir = log_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+    prune_logs(&dir, prefix, MAX_SESSION_LOGS - 1);
+
+    let file_name = format!("{}{}.log", prefix, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let path = dir.join(file_name);
+    let file = fs::File::create(&path).map_err(|e| format!("Failed to create log file {:?}: {}", path, e))?;
+    let (writer, guard) = tracing_appender::non_blocking(file);
+
+    let file_filter = EnvFilter::try_from_env("G2G_LOG").unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILE_FILTER));
+    let file_layer = tracing_subscriber::fmt::layer()
+        .with_writer(RedactingMakeWriter::new(writer))
+        .with_ansi(false)
+        .with_target(true)
+        .with_filter(file_filter);
+
+    // В release (windows_subsystem = "windows") консоли нет — только файл.
+    // Консольный вывод идёт в stderr: stdout у g2g-cli занят JSON-результатом.
+    let console_layer = cfg!(debug_assertions).then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(RedactingMakeWriter::new(std::io::stderr))
+            .with_target(false)
+            .with_filter(LevelFilter::INFO)
+    });
+
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(console_layer)
+        .try_init()
+        .map_err(|e| format!("Failed to initialize logging: {}", e))?;
+
+    // Паника тоже попадает в лог — иначе в release её не видно вовсе.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        error!("panic: {}", panic_info);
+        default_hook(panic_info);
+    }));
+
+    let _ = CURRENT_LOG.set(path.clone());
+    info!(version = env!("CARGO_PKG_VERSION"), log = ?path, "Session started");
+    Ok(guard)
+}
+
+/// Копирует лог сессии в output_path для отчёта об ошибке.
+/// previous = false — текущий запуск, true — предыдущий (например, после падения).
+pub fn export_session_log(output_path: &Path, previous: bool) -> Result<PathBuf, String> {
+    let logs = session_logs(&log_dir()?, APP_LOG_PREFIX);
+    let current = CURRENT_LOG.get();
+
+    // Логи других запусков (без текущего), от старых к новым.
+    let others: Vec<&PathBuf> = logs.iter().filter(|p| Some(*p) != current).collect();
+    let source = if previous {
+        others.last().copied()
+    } else {
+        current.or(others.last().copied())
+    }
+    .ok_or_else(|| "Файл лога не найден".to_string())?;
+
+    fs::copy(source, output_path)
+        .map_err(|e| format!("Failed to export log {:?}: {}", source, e))?;
+    info!("Session log exported to {:?}", output_path);
+    Ok(source.clone())
+}
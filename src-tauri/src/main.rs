@@ -2,545 +2,152 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::fs;
 use std::path::PathBuf;
-use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
-use rand::Rng;
 use tauri::Emitter;
-use chrono;
-
-mod g2g_api;
-mod config;
-mod sheets;
-
-use g2g_api::{G2GApiClient, G2GAuthTokens, SkinPrice};
-use std::sync::atomic::AtomicUsize;
-use config::{AppSettings, G2GSettings};
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AccountFolder {
-    pub name: String,
-    pub path: String,
-    pub is_listed: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AccountsData {
-    pub accounts: Vec<AccountFolder>,
-    pub base_path: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SkinPriceRequest {
-    pub skins: Vec<String>,
-    pub server: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SkinPriceResponse {
-    pub prices: Vec<SkinPrice>,
-    pub total_value: String,
-    pub most_expensive: Option<SkinPrice>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateOfferRequest {
-    pub title: String,
-    pub description: String,
-    pub price: f64,
-    pub server: String,
-    pub rank: String,
-    pub champions_count: i32,
-    pub skins_count: i32,
-    pub account_path: String,
-    pub account_name: String,
-}
-
-#[derive(Clone, serde::Serialize)]
-struct PriceProgressPayload {
-    current: usize,
-    total: usize,
-    skin_name: String,
-    status: String,
-}
-
-#[derive(Clone, serde::Serialize)]
-struct ListingProgressPayload {
-    stage: String,
-    current: usize,
-    total: usize,
-    message: String,
-}
-
-// Global API client (без g2g_config)
+use tracing::{debug, info, warn};
+
+use g2g_app_lib::{accounts, category, config, history, listing_text, logging, usage};
+use g2g_app_lib::accounts::{AccountRef, AccountStore, AccountsData};
+use g2g_app_lib::g2g_api::G2GApiClient;
+use g2g_app_lib::category::{CategorySummary, MappingDiffReport};
+use g2g_app_lib::listing::{CreateOfferRequest, ListingProgressPayload, ListingRequest, ListingService};
+use g2g_app_lib::listing_text::{AccountData, ListingTemplates, RenderedListing};
+use g2g_app_lib::history::{HistoryQuery, ListingRecord};
+use g2g_app_lib::pricing::{PriceProgressPayload, PriceService, SkinPriceRequest, SkinPriceResponse};
+use g2g_app_lib::reconcile::{self, ReconcileReport};
+use g2g_app_lib::sheet_diagnose::{self, WebhookDiagnosis};
+use g2g_app_lib::sheet_outbox::{self, OutboxEntry, OutboxFlushResult, SheetOutbox};
+use g2g_app_lib::sheet_sync::{SheetCheckResult, SheetSync};
+use g2g_app_lib::sheets::{SheetUpdate, SheetUpdateResult};
+use g2g_app_lib::config::{AppSettings, OfferTemplateOverride};
+use g2g_app_lib::watcher::{AccountChange, AccountWatcher};
+
+// Сервисы библиотеки с общим клиентом G2G (без g2g_config)
 struct AppState {
-    g2g_client: Mutex<G2GApiClient>,
-    cancel_price_calc: Arc<AtomicBool>,
-}
-
-// Функция для загрузки настроек G2G
-fn load_g2g_settings() -> Result<G2GSettings, String> {
-    match AppSettings::load() {
-        Ok(settings) => {
-            println!("✅ G2G settings loaded from file");
-            Ok(settings.g2g)
-        }
-        Err(_) => {
-            // Fallback на .env
-            config::load_from_env()
-                .ok_or_else(|| {
-                    "G2G токены не настроены. Перейдите в настройки приложения.".to_string()
-                })
-        }
-    }
-}
-
-// Загружает URL веб-хука Google Sheets из настроек (если задан и не пустой).
-fn load_sheets_webhook() -> Option<String> {
-    let settings = AppSettings::load().ok()?;
-    let url = settings.sheets?.webhook_url.trim().to_string();
-    if url.is_empty() {
-        None
-    } else {
-        Some(url)
-    }
+    listing: ListingService,
+    prices: PriceService,
+    // Наблюдение за последней загруженной папкой аккаунтов
+    account_watcher: std::sync::Mutex<Option<AccountWatcher>>,
 }
 
-// Извлекает логин аккаунта (строка "Login:") из текста файла аккаунта.
-fn extract_login(text: &str) -> Option<String> {
-    for line in text.lines() {
-        let line = line.trim();
-        if line.starts_with("Login:") {
-            let login = line.replace("Login:", "").trim().to_string();
-            if !login.is_empty() {
-                return Some(login);
-            }
-        }
-    }
-    None
-}
-
-// Отправляет строку о выставленном оффере в Google-таблицу (best-effort).
-// Любая ошибка логируется, но не прерывает процесс выставления.
-async fn sync_offer_to_sheet(
-    raw_content: &str,
-    account_name: &str,
-    account_path: &str,
-    offer_id: &str,
-) {
-    let webhook_url = match load_sheets_webhook() {
-        Some(url) => url,
-        None => {
-            println!("ℹ️  Google Sheets webhook не настроен — пропускаем запись в таблицу");
-            return;
-        }
-    };
-
-    // Username = Login из файла аккаунта; если не найден, используем имя папки.
-    let username = extract_login(raw_content).unwrap_or_else(|| account_name.to_string());
-
-    // Folder = полный путь родительской папки (где лежит папка аккаунта).
-    let folder = PathBuf::from(account_path)
-        .parent()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_default();
-
-    let row = sheets::SheetRow {
-        username,
-        offer_id: offer_id.to_string(),
-        listed_date: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-        folder,
-        status: "Active".to_string(),
-    };
+// Ищет папки аккаунтов в выбранной папке и вложенных (до max_depth уровней)
+// и начинает следить за ней: изменения приходят событием "accounts-changed".
+#[tauri::command]
+async fn load_account_folders(
+    folder_path: String,
+    max_depth: Option<usize>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<AccountsData, String> {
+    let store = AccountStore::new(&folder_path).with_max_depth(max_depth.unwrap_or(accounts::DEFAULT_SCAN_DEPTH));
+    let data = store.folders()?;
 
-    match sheets::append_row(&webhook_url, &row).await {
-        Ok(()) => println!("✅ Listing written to Google Sheet"),
-        Err(e) => println!("⚠️  Не удалось записать в Google-таблицу: {}", e),
+    let on_change = Arc::new(move |changes: Vec<AccountChange>| {
+        let _ = app.emit("accounts-changed", changes);
+    });
+    // Без наблюдения список просто не обновляется сам — загрузку это не ломает
+    let watcher = AccountWatcher::start(store, &data.accounts, on_change)
+        .map_err(|e| warn!("Наблюдение за папкой аккаунтов недоступно: {}", e))
+        .ok();
+    if let Ok(mut current) = state.account_watcher.lock() {
+        *current = watcher;
     }
+    Ok(data)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AccountRef {
-    pub name: String,
-    pub path: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SheetCheckResult {
-    pub path: String,
-    pub username: String,
-    pub in_sheet: bool,
-    pub sheet_status: Option<String>,
-    pub offer_id: Option<String>,
-}
-
-// Читает логин аккаунта из файла {name}.txt в папке аккаунта.
-// Если файла или строки "Login:" нет — fallback на имя папки (как при выставлении).
-fn read_account_login(account: &AccountRef) -> String {
-    let file_path = PathBuf::from(&account.path).join(format!("{}.txt", account.name));
-    if let Ok(content) = fs::read_to_string(&file_path) {
-        if let Some(login) = extract_login(&content) {
-            return login;
-        }
+#[tauri::command]
+fn stop_account_watcher(state: tauri::State<'_, AppState>) {
+    if let Ok(mut current) = state.account_watcher.lock() {
+        *current = None;
     }
-    account.name.clone()
 }
 
-// Проверяет по логину, какие из загруженных аккаунтов уже есть в Google-таблице.
 #[tauri::command]
-async fn check_accounts_in_sheet(accounts: Vec<AccountRef>) -> Result<Vec<SheetCheckResult>, String> {
-    let webhook_url = load_sheets_webhook()
-        .ok_or_else(|| "Google Sheets webhook не настроен".to_string())?;
-
-    let rows = sheets::fetch_rows(&webhook_url).await?;
-
-    // Карта "username (lowercase) -> (status, offer_id)" из таблицы.
-    let mut by_username: std::collections::HashMap<String, (Option<String>, Option<String>)> =
-        std::collections::HashMap::new();
-    for row in rows {
-        let key = row.username.trim().to_lowercase();
-        if !key.is_empty() {
-            by_username.insert(key, (row.status, row.offer_id));
-        }
-    }
-
-    let results = accounts
-        .iter()
-        .map(|account| {
-            let username = read_account_login(account);
-            let found = by_username.get(&username.trim().to_lowercase());
-            SheetCheckResult {
-                path: account.path.clone(),
-                username,
-                in_sheet: found.is_some(),
-                sheet_status: found.and_then(|(s, _)| s.clone()),
-                offer_id: found.and_then(|(_, o)| o.clone()),
-            }
-        })
-        .collect::<Vec<_>>();
-
-    let found_count = results.iter().filter(|r| r.in_sheet).count();
-    println!("📊 Sheet check: {} of {} accounts found in sheet", found_count, results.len());
-
-    Ok(results)
+async fn get_account_files(account_path: String) -> Result<Vec<String>, String> {
+    accounts::list_account_files(&account_path)
 }
 
-fn save_offer_id_to_file(account_path: &str, offer_id: &str) -> Result<(), String> {
-    println!("💾 Saving offer_id to file...");
-
-    let path = PathBuf::from(account_path);
-    let file_path = path.join(format!("{}.txt", offer_id));
-
-    let content = format!(
-        "Offer ID: {}\nCreated: {}\nStatus: Live\n",
-        offer_id,
-        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
-    );
-
-    fs::write(&file_path, content)
-        .map_err(|e| format!("Failed to save offer_id file: {}", e))?;
-
-    println!("✅ Offer ID saved to: {:?}", file_path);
-    Ok(())
+#[tauri::command]
+async fn read_account_file(account_path: String, file_name: String) -> Result<String, String> {
+    accounts::read_account_file(&account_path, &file_name)
 }
 
-fn check_if_listed(account_path: &str) -> bool {
-    if let Ok(entries) = fs::read_dir(account_path) {
-        for entry in entries.flatten() {
-            if let Some(filename) = entry.file_name().to_str() {
-                if filename.starts_with("G17") && filename.ends_with(".txt") {
-                    println!("   ✓ Found offer file: {}", filename);
-                    return true;
-                }
-            }
-        }
-    }
-    false
+#[tauri::command]
+async fn fetch_skin_prices(
+    request: SkinPriceRequest,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<SkinPriceResponse, String> {
+    let on_progress = Arc::new(move |payload: PriceProgressPayload| {
+        let _ = app.emit("price-progress", payload);
+    });
+    state.prices.fetch(&request, on_progress).await
 }
 
 #[tauri::command]
 async fn create_g2g_offer(
     request: CreateOfferRequest,
-    app: tauri::AppHandle,  // ← Добавили app handle
+    app: tauri::AppHandle,
     state: tauri::State<'_, AppState>
 ) -> Result<String, String> {
-    let total_stages = 5;
-
-    // Этап 1: Чтение данных
-    let _ = app.emit("listing-progress", ListingProgressPayload {
-        stage: "reading".to_string(),
-        current: 1,
-        total: total_stages,
-        message: "Чтение данных аккаунта...".to_string(),
-    });
-
-    println!("🎯 Creating G2G offer...");
-    println!("   Title: {}", request.title);
-    println!("   Server: {}", request.server);
-    println!("   Account path: {}", request.account_path);
-
-    // Загружаем настройки динамически
-    let g2g_settings = load_g2g_settings()
-        .map_err(|e| format!("Не удалось загрузить настройки G2G: {}", e))?;
-
-    let tokens = G2GAuthTokens {
-        user_id: g2g_settings.user_id.clone(),
-        refresh_token: g2g_settings.refresh_token.clone(),
-        long_lived_token: g2g_settings.long_lived_token.clone(),
-        active_device_token: g2g_settings.active_device_token.clone(),
-    };
-
-    let account_file = format!("{}.txt", request.account_name);
-    let account_path = PathBuf::from(&request.account_path);
-    let file_path = account_path.join(&account_file);
-
-    println!("📄 Reading account data from: {:?}", file_path);
-
-    let raw_content = match fs::read_to_string(&file_path) {
-        Ok(content) => content,
-        Err(e) => {
-            return Err(format!("Failed to read account data file: {}", e));
-        }
-    };
-
-    println!("✅ Account data loaded, {} bytes", raw_content.len());
-
-    let screenshot_url = extract_screenshot_url(&raw_content);
-    if let Some(ref url) = screenshot_url {
-        println!("🖼️  Found screenshot URL: {}", url);
-    } else {
-        println!("⚠️  No screenshot URL found in account file");
-    }
-
-    let csv_data = parse_account_to_csv(&raw_content)
-        .map_err(|e| format!("Failed to parse account to CSV: {}", e))?;
-
-    println!("✅ Converted to CSV, {} bytes", csv_data.len());
-
-    // Этап 2: Создание оффера
-    let _ = app.emit("listing-progress", ListingProgressPayload {
-        stage: "creating".to_string(),
-        current: 2,
-        total: total_stages,
-        message: "Создание объявления...".to_string(),
-    });
-
-    let mut client = state.g2g_client.lock().await;
-
-    // Создаем пустой оффер
-    let offer_id = client.create_offer_id(&tokens).await?;
-    println!("✅ Offer ID created: {}", offer_id);
-
-    // Этап 3: Заполнение информации
-    let _ = app.emit("listing-progress", ListingProgressPayload {
-        stage: "updating".to_string(),
-        current: 3,
-        total: total_stages,
-        message: "Заполнение информации...".to_string(),
-    });
-
-    let delay_ms = rand::thread_rng().gen_range(1500..2500);
-    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
-
-    // Обновляем оффер и получаем relation_id
-    let relation_id = client.update_offer(
-        &offer_id,
-        &request.title,
-        &request.description,
-        request.price,
-        &request.server,
-        &request.rank,
-        request.champions_count,
-        request.skins_count,
-        screenshot_url.as_deref(),
-        &tokens,
-    ).await?;
-    println!("✅ Offer updated, relation_id: {}", relation_id);
-
-    // Этап 4: Загрузка данных
-    let _ = app.emit("listing-progress", ListingProgressPayload {
-        stage: "uploading".to_string(),
-        current: 4,
-        total: total_stages,
-        message: "Загрузка данных аккаунта...".to_string(),
-    });
-
-    let delay_ms = rand::thread_rng().gen_range(1500..2500);
-    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
-
-    // Загружаем данные аккаунта
-    client.upload_account_data(&offer_id, &relation_id, &csv_data, &tokens).await?;
-    println!("✅ Account data uploaded");
-
-    // Этап 5: Завершение
-    let _ = app.emit("listing-progress", ListingProgressPayload {
-        stage: "finishing".to_string(),
-        current: 5,
-        total: total_stages,
-        message: "Сохранение...".to_string(),
+    let on_progress = Arc::new(move |payload: ListingProgressPayload| {
+        let _ = app.emit("listing-progress", payload);
     });
-
-    println!("✅ Offer created with data! ID: {}", offer_id);
-
-    save_offer_id_to_file(&request.account_path, &offer_id)?;
-
-    // Записываем строку в Google-таблицу (если веб-хук настроен). Best-effort:
-    // ошибка записи в таблицу не должна отменять уже созданный оффер.
-    sync_offer_to_sheet(&raw_content, &request.account_name, &request.account_path, &offer_id).await;
-
-    Ok(offer_id)
+    state.listing.create_offer(&request, on_progress).await
 }
 
-fn extract_screenshot_url(text: &str) -> Option<String> {
-    println!("🔍 Searching for screenshot URL in text...");
-
-    for line in text.lines() {
-        let line = line.trim();
-
-        if line.to_lowercase().contains("screenshot url") {
-            println!("   Found line with 'screenshot url': {}", line);
-
-            if let Some(http_pos) = line.find("http") {
-                let url_part = &line[http_pos..];
-                let url = url_part.split_whitespace().next().unwrap_or(url_part);
-
-                println!("✅ Extracted screenshot URL: {}", url);
-                return Some(url.to_string());
-            }
-        }
-
-        if line.starts_with("http") &&
-           (line.contains("imgur.com") ||
-            line.contains("gyazo.com") ||
-            line.contains("prnt.sc") ||
-            line.contains("i.postimg.cc")) {
-
-            let url = line.split_whitespace().next().unwrap_or(line);
-            println!("✅ Found direct image URL: {}", url);
-            return Some(url.to_string());
-        }
-    }
-
-    println!("⚠️  No screenshot URL found in text");
-    None
+// Проверяет по offer_id и логину, какие из загруженных аккаунтов уже есть
+// в Google-таблице; с fuzzy логины сравниваются нестрого.
+#[tauri::command]
+async fn check_accounts_in_sheet(accounts: Vec<AccountRef>, fuzzy: Option<bool>) -> Result<Vec<SheetCheckResult>, String> {
+    SheetSync::from_settings()?.check(&accounts, fuzzy.unwrap_or(false)).await
 }
 
+// Обновляет строку таблицы по offer_id или username (статус, цена, дата продажи).
 #[tauri::command]
-async fn load_account_folders(folder_path: String) -> Result<AccountsData, String> {
-    println!("Loading account folders from: {}", folder_path);
-
-    let path = PathBuf::from(&folder_path);
-
-    if !path.exists() {
-        return Err("Указанная папка не существует".to_string());
-    }
-
-    if !path.is_dir() {
-        return Err("Указанный путь не является папкой".to_string());
-    }
-
-    let mut accounts = Vec::new();
-
-    match fs::read_dir(&path) {
-        Ok(entries) => {
-            for entry in entries {
-                match entry {
-                    Ok(entry) => {
-                        let entry_path = entry.path();
-                        if entry_path.is_dir() {
-                            if let Some(folder_name) = entry_path.file_name() {
-                                if let Some(name_str) = folder_name.to_str() {
-                                    let path_str = entry_path.to_string_lossy().to_string();
-                                    let is_listed = check_if_listed(&path_str);
-
-                                    accounts.push(AccountFolder {
-                                        name: name_str.to_string(),
-                                        path: path_str,
-                                        is_listed,
-                                    });
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Ошибка чтения записи: {}", e);
-                    }
-                }
-            }
-        }
-        Err(e) => {
-            return Err(format!("Не удалось прочитать содержимое папки: {}", e));
-        }
-    }
-
-    accounts.sort_by(|a, b| a.name.cmp(&b.name));
-
-    let listed_count = accounts.iter().filter(|a| a.is_listed).count();
-    println!("Найдено {} аккаунтов ({} в продаже)", accounts.len(), listed_count);
-
-    Ok(AccountsData {
-        accounts,
-        base_path: folder_path,
-    })
+async fn update_sheet_row(update: SheetUpdate) -> Result<SheetUpdateResult, String> {
+    SheetSync::from_settings()?.update_row(&update).await
 }
 
+// Сверяет выставленные аккаунты папки с таблицей и (с check_g2g) с офферами
+// на G2G; с apply применяет исправления в таблице.
 #[tauri::command]
-async fn get_account_files(account_path: String) -> Result<Vec<String>, String> {
-    println!("Getting files from account: {}", account_path);
-
-    let path = PathBuf::from(&account_path);
-
-    if !path.exists() || !path.is_dir() {
-        return Err("Папка аккаунта не найдена".to_string());
-    }
-
-    let mut files = Vec::new();
-
-    match fs::read_dir(&path) {
-        Ok(entries) => {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    if let Some(file_name) = entry.file_name().to_str() {
-                        files.push(file_name.to_string());
-                    }
-                }
-            }
-        }
-        Err(e) => {
-            return Err(format!("Ошибка чтения файлов: {}", e));
-        }
-    }
-
-    Ok(files)
+async fn reconcile_accounts(
+    folder_path: String,
+    max_depth: Option<usize>,
+    check_g2g: bool,
+    apply: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<ReconcileReport, String> {
+    let store = AccountStore::new(&folder_path).with_max_depth(max_depth.unwrap_or(accounts::DEFAULT_SCAN_DEPTH));
+    let listing = check_g2g.then_some(&state.listing);
+    reconcile::reconcile(&store, &SheetSync::from_settings()?, listing, apply).await
 }
 
+// Проверяет настройку веб-хука Apps Script (доступ, формат ответа, версию
+// скрипта, подпись, заголовки, право записи) и возвращает чек-лист.
 #[tauri::command]
-async fn read_account_file(account_path: String, file_name: String) -> Result<String, String> {
-    println!("Reading file '{}' from account: {}", file_name, account_path);
-
-    let account_dir = PathBuf::from(&account_path);
-    let file_path = account_dir.join(&file_name);
-
-    println!("Full file path: {:?}", file_path);
+async fn diagnose_sheet_webhook(webhook_url: Option<String>, webhook_secret: Option<String>) -> Result<WebhookDiagnosis, String> {
+    sheet_diagnose::diagnose_configured(webhook_url.as_deref(), webhook_secret.as_deref()).await
+}
 
-    if !file_path.exists() {
-        return Err(format!("Файл не найден: {}", file_name));
-    }
+// Строки таблицы, ожидающие повторной записи.
+#[tauri::command]
+fn get_sheet_outbox() -> Result<Vec<OutboxEntry>, String> {
+    SheetOutbox::open()?.pending()
+}
 
-    match fs::read_to_string(&file_path) {
-        Ok(content) => {
-            println!("Successfully read {} bytes from {}", content.len(), file_name);
-            Ok(content)
-        },
-        Err(e) => Err(format!("Ошибка чтения файла: {}", e)),
-    }
+// Отправляет всю очередь таблицы сейчас, не дожидаясь времени повтора.
+#[tauri::command]
+async fn flush_sheet_outbox() -> Result<OutboxFlushResult, String> {
+    SheetOutbox::open()?.flush(&SheetSync::from_settings()?, true).await
 }
 
 #[tauri::command]
 async fn read_text_file(path: String) -> Result<String, String> {
-    println!("Reading text file: {}", path);
+    info!("Reading text file: {}", path);
 
     let file_path = PathBuf::from(&path);
 
@@ -556,398 +163,167 @@ async fn read_text_file(path: String) -> Result<String, String> {
 
 #[tauri::command]
 async fn open_account_screenshot(account_path: String) -> Result<(), String> {
-    println!("Opening screenshot from: {}", account_path);
+    info!("Opening screenshot from: {}", account_path);
 
-    let path = PathBuf::from(&account_path);
+    let path_str = accounts::find_screenshot(&account_path)?.to_string_lossy().to_string();
 
-    if !path.exists() || !path.is_dir() {
-        return Err("Папка аккаунта не найдена".to_string());
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("cmd")
+            .args(&["/C", "start", "", &path_str])
+            .spawn()
+            .map_err(|e| format!("Failed to open image: {}", e))?;
     }
 
-    match fs::read_dir(&path) {
-        Ok(entries) => {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    let entry_path = entry.path();
-                    if entry_path.is_file() {
-                        if let Some(ext) = entry_path.extension() {
-                            if ext.to_str() == Some("png") {
-                                let path_str = entry_path.to_string_lossy().to_string();
-
-                                println!("Found PNG file: {}", path_str);
-
-                                #[cfg(target_os = "windows")]
-                                {
-                                    std::process::Command::new("cmd")
-                                        .args(&["/C", "start", "", &path_str])
-                                        .spawn()
-                                        .map_err(|e| format!("Failed to open image: {}", e))?;
-                                }
-
-                                #[cfg(target_os = "macos")]
-                                {
-                                    std::process::Command::new("open")
-                                        .arg(&path_str)
-                                        .spawn()
-                                        .map_err(|e| format!("Failed to open image: {}", e))?;
-                                }
-
-                                #[cfg(target_os = "linux")]
-                                {
-                                    std::process::Command::new("xdg-open")
-                                        .arg(&path_str)
-                                        .spawn()
-                                        .map_err(|e| format!("Failed to open image: {}", e))?;
-                                }
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(&path_str)
+            .spawn()
+            .map_err(|e| format!("Failed to open image: {}", e))?;
+    }
 
-                                println!("Screenshot opened successfully");
-                                return Ok(());
-                            }
-                        }
-                    }
-                }
-            }
-            Err("PNG файлы не найдены в папке аккаунта".to_string())
-        }
-        Err(e) => Err(format!("Ошибка чтения папки аккаунта: {}", e))
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(&path_str)
+            .spawn()
+            .map_err(|e| format!("Failed to open image: {}", e))?;
     }
-}
 
-// Tunables for the parallel price fetch pipeline.
-// Concurrency is intentionally conservative to avoid triggering G2G rate limits.
-const PRICE_FETCH_CONCURRENCY: usize = 3;
-// Cap total time per single skin including any 401 retry + refresh.
-const PRICE_FETCH_PER_SKIN_TIMEOUT_SECS: u64 = 45;
-// Minimum delay between individual requests (jitter applied on top).
-const PRICE_FETCH_MIN_DELAY_MS: u64 = 400;
-const PRICE_FETCH_MAX_DELAY_MS: u64 = 1200;
+    info!("Screenshot opened successfully");
+    Ok(())
+}
 
-// Cancellation-aware sleep so a cancel click aborts a pending delay immediately.
-async fn cancellable_sleep(duration_ms: u64, cancel_flag: &Arc<AtomicBool>) -> bool {
-    let start = std::time::Instant::now();
-    let total = std::time::Duration::from_millis(duration_ms);
-    loop {
-        if cancel_flag.load(Ordering::Relaxed) {
-            return true;
-        }
-        let elapsed = start.elapsed();
-        if elapsed >= total {
-            return false;
-        }
-        let remaining = total - elapsed;
-        let tick = remaining.min(std::time::Duration::from_millis(100));
-        tokio::time::sleep(tick).await;
-    }
+// Доступные категории G2G (встроенная LoL + файлы из директории конфигурации).
+#[tauri::command]
+fn list_categories() -> Result<Vec<CategorySummary>, String> {
+    category::list_categories()
 }
 
+// Сверяет маппинги атрибутов категории с текущими определениями G2G.
+// apply = false — только отчёт о расхождениях; apply = true — сохранить
+// новую ревизию файла категории в директорию конфигурации.
 #[tauri::command]
-async fn fetch_skin_prices(
-    request: SkinPriceRequest,
-    app: tauri::AppHandle,
+async fn refresh_category_mappings(
+    category_id: Option<String>,
+    apply: bool,
     state: tauri::State<'_, AppState>,
-) -> Result<SkinPriceResponse, String> {
-    let total_skins = request.skins.len();
-    println!(
-        "Fetching prices for {} skins on server {} (parallel, concurrency={})",
-        total_skins, request.server, PRICE_FETCH_CONCURRENCY
-    );
-
-    // Reset cancellation flag before starting.
-    state.cancel_price_calc.store(false, Ordering::Relaxed);
+) -> Result<MappingDiffReport, String> {
+    state.listing.refresh_category_mappings(category_id.as_deref(), apply).await
+}
 
-    if total_skins == 0 {
-        return Ok(SkinPriceResponse {
-            prices: Vec::new(),
-            total_value: "$0.00".to_string(),
-            most_expensive: None,
-        });
+// Вариант шаблона заголовка и лимит длины из настроек.
+fn load_listing_text_settings() -> (Option<String>, usize) {
+    match AppSettings::load().ok().and_then(|s| s.listing_text) {
+        Some(settings) => (settings.title_variant, settings.title_max_length),
+        None => (None, listing_text::DEFAULT_TITLE_MAX_LENGTH),
     }
+}
 
-    let g2g_settings = load_g2g_settings()
-        .map_err(|e| format!("Не удалось загрузить настройки G2G: {}", e))?;
-
-    let tokens = Arc::new(G2GAuthTokens {
-        user_id: g2g_settings.user_id.clone(),
-        refresh_token: g2g_settings.refresh_token.clone(),
-        long_lived_token: g2g_settings.long_lived_token.clone(),
-        active_device_token: g2g_settings.active_device_token.clone(),
-    });
-
-    // Snapshot client state + refresh token once upfront so concurrent tasks
-    // don't need to hold the top-level Mutex<G2GApiClient>.
-    let (http_client, base_url, session_id, initial_token) = {
-        let mut client = state.g2g_client.lock().await;
-        if client.current_token().is_none() {
-            client
-                .refresh_token(&tokens)
-                .await
-                .map_err(|e| format!("Не удалось получить токен G2G: {}", e))?;
-        }
-        let token = client
-            .current_token()
-            .ok_or_else(|| "Не удалось получить токен G2G".to_string())?;
-        (
-            client.http_client(),
-            client.base_url_cloned(),
-            client.session_id_cloned(),
-            token,
-        )
+// Генерирует заголовок и описание по шаблонам из директории конфигурации.
+// champions — порядок чемпионов для заголовка; по умолчанию ротация по
+// статистике использования (реже использованные — первыми).
+#[tauri::command]
+fn generate_listing_text(data: AccountData, champions: Option<Vec<String>>) -> Result<RenderedListing, String> {
+    let (variant, max_length) = load_listing_text_settings();
+    let templates = listing_text::load_templates(variant.as_deref())?;
+    let champions = match champions {
+        Some(list) => list,
+        None => usage::rotate_champions(&data.champions_list).unwrap_or_else(|e| {
+            warn!("Статистика чемпионов недоступна: {}", e);
+            data.champions_list.clone()
+        }),
     };
+    listing_text::render_listing(&data, &templates, Some(&champions), max_length)
+}
 
-    // Shared token updated by whichever task first encounters a 401.
-    let shared_token = Arc::new(tokio::sync::RwLock::new(initial_token));
-    // Serializes refresh calls so concurrent 401s don't all hit /refresh_access.
-    let refresh_mutex = Arc::new(tokio::sync::Mutex::new(()));
-
-    let semaphore = Arc::new(tokio::sync::Semaphore::new(PRICE_FETCH_CONCURRENCY));
-    let cancel_flag = state.cancel_price_calc.clone();
-    let completed = Arc::new(AtomicUsize::new(0));
-
-    let mut join_set: tokio::task::JoinSet<(usize, String, Result<String, String>)> =
-        tokio::task::JoinSet::new();
-
-    for (index, skin) in request.skins.iter().enumerate() {
-        let skin = skin.clone();
-        let server = request.server.clone();
-        let http_client = http_client.clone();
-        let base_url = base_url.clone();
-        let session_id = session_id.clone();
-        let shared_token = shared_token.clone();
-        let refresh_mutex = refresh_mutex.clone();
-        let tokens = tokens.clone();
-        let semaphore = semaphore.clone();
-        let cancel_flag = cancel_flag.clone();
-        let app_handle = app.clone();
-        let completed = completed.clone();
-
-        join_set.spawn(async move {
-            // Wait our turn in the concurrency queue.
-            let _permit = match semaphore.acquire_owned().await {
-                Ok(p) => p,
-                Err(e) => {
-                    return (index, skin, Err(format!("Semaphore closed: {}", e)));
-                }
-            };
-
-            if cancel_flag.load(Ordering::Relaxed) {
-                return (index, skin, Err("cancelled".to_string()));
-            }
-
-            // Small jitter so requests don't fire in perfectly synchronized bursts.
-            let delay_ms = rand::thread_rng()
-                .gen_range(PRICE_FETCH_MIN_DELAY_MS..PRICE_FETCH_MAX_DELAY_MS);
-            if cancellable_sleep(delay_ms, &cancel_flag).await {
-                return (index, skin, Err("cancelled".to_string()));
-            }
-
-            let done_so_far = completed.load(Ordering::Relaxed);
-            let _ = app_handle.emit(
-                "price-progress",
-                PriceProgressPayload {
-                    current: done_so_far + 1,
-                    total: total_skins,
-                    skin_name: skin.clone(),
-                    status: "processing".to_string(),
-                },
-            );
-
-            // Wrap the whole per-skin flow (including one 401 refresh+retry) in a
-            // single timeout so nothing can hang indefinitely.
-            let per_skin_result = tokio::time::timeout(
-                std::time::Duration::from_secs(PRICE_FETCH_PER_SKIN_TIMEOUT_SECS),
-                async {
-                    for attempt in 0..2u8 {
-                        let token_now = shared_token.read().await.clone();
-
-                        let search_res = g2g_api::search_skin_price_shared(
-                            &http_client,
-                            &base_url,
-                            &session_id,
-                            &token_now,
-                            &skin,
-                            &server,
-                        )
-                        .await;
-
-                        match search_res {
-                            Ok(price) => return Ok::<String, String>(price),
-                            Err(g2g_api::SkinSearchError::Unauthorized) if attempt == 0 => {
-                                // Only one task at a time should refresh.
-                                let _guard = refresh_mutex.lock().await;
-                                let current = shared_token.read().await.clone();
-                                if current == token_now {
-                                    println!(
-                                        "⚠️  401 on '{}', refreshing shared token…",
-                                        skin
-                                    );
-                                    let new_token = g2g_api::refresh_access_token_shared(
-                                        &http_client,
-                                        &base_url,
-                                        &session_id,
-                                        &tokens,
-                                    )
-                                    .await
-                                    .map_err(|e| format!("Token refresh failed: {}", e))?;
-                                    *shared_token.write().await = new_token;
-                                }
-                                // Loop and retry with the (now refreshed) token.
-                                continue;
-                            }
-                            Err(g2g_api::SkinSearchError::Unauthorized) => {
-                                return Err(
-                                    "Unauthorized after refresh attempt".to_string()
-                                );
-                            }
-                            Err(g2g_api::SkinSearchError::Other(e)) => {
-                                return Err(e);
-                            }
-                        }
-                    }
-                    Err("Exhausted retry attempts".to_string())
-                },
-            )
-            .await;
-
-            let price_result: Result<String, String> = match per_skin_result {
-                Ok(inner) => inner,
-                Err(_) => Err(format!(
-                    "Timed out after {}s",
-                    PRICE_FETCH_PER_SKIN_TIMEOUT_SECS
-                )),
-            };
-
-            let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
-            let status = if price_result.is_ok() { "completed" } else { "error" };
-            let _ = app_handle.emit(
-                "price-progress",
-                PriceProgressPayload {
-                    current: done,
-                    total: total_skins,
-                    skin_name: skin.clone(),
-                    status: status.to_string(),
-                },
-            );
-
-            (index, skin, price_result)
-        });
-    }
-
-    // Collect results as they complete; abort in-flight tasks as soon as the
-    // user clicks cancel, rather than waiting for the next natural completion.
-    let mut raw_results: Vec<(usize, String, Result<String, String>)> =
-        Vec::with_capacity(total_skins);
-    let mut was_cancelled = false;
-
-    loop {
-        if !was_cancelled && cancel_flag.load(Ordering::Relaxed) {
-            was_cancelled = true;
-            println!("🛑 Cancellation requested — aborting remaining tasks");
-            join_set.abort_all();
-        }
+// Учесть чемпионов и скины, попавшие в заголовок.
+#[tauri::command]
+fn track_title_usage(champions: Vec<String>, skins: Option<Vec<String>>) -> Result<(), String> {
+    usage::track_usage(&champions, &skins.unwrap_or_default())
+}
 
-        // Race between "a task finished" and "cancel flag flipped".
-        let next = tokio::select! {
-            joined = join_set.join_next() => joined,
-            _ = async {
-                // Poll the cancel flag a few times per second. Cheap and avoids
-                // needing an extra Notify channel wired through every task.
-                while !cancel_flag.load(Ordering::Relaxed) {
-                    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
-                }
-            }, if !was_cancelled => {
-                // Loop around so the check at the top runs abort_all().
-                continue;
-            }
-        };
+// Порядок чемпионов для заголовка: реже использованные — первыми.
+#[tauri::command]
+fn rotate_title_champions(champions: Vec<String>) -> Result<Vec<String>, String> {
+    usage::rotate_champions(&champions)
+}
 
-        match next {
-            Some(Ok(tuple)) => raw_results.push(tuple),
-            Some(Err(e)) => {
-                // JoinError — either panic or aborted task. Safe to skip.
-                if !e.is_cancelled() {
-                    println!("⚠️  Task join error: {}", e);
-                }
-            }
-            None => break,
-        }
-    }
+// Импорт статистики из localStorage (JSON { "Ahri": 3, ... }).
+#[tauri::command]
+fn import_champion_usage(json: String) -> Result<usize, String> {
+    usage::import_local_storage(&json)
+}
 
-    // Propagate the cancelled state to the shared token on the client so the
-    // next command re-refreshes if needed (token may still be valid, but keeping
-    // it is fine).
-    {
-        let mut client = state.g2g_client.lock().await;
-        client.set_current_token(Some(shared_token.read().await.clone()));
-    }
+#[tauri::command]
+fn export_title_usage() -> Result<String, String> {
+    usage::export_json()
+}
 
-    if was_cancelled {
-        let _ = app.emit(
-            "price-progress",
-            PriceProgressPayload {
-                current: completed.load(Ordering::Relaxed),
-                total: total_skins,
-                skin_name: "Cancelled".to_string(),
-                status: "cancelled".to_string(),
-            },
-        );
-        return Err("Price calculation cancelled by user".to_string());
-    }
+#[tauri::command]
+fn get_title_usage_stats() -> Result<Vec<usage::UsageStat>, String> {
+    usage::stats()
+}
 
-    // Restore original input order so the output matches what the user sent.
-    raw_results.sort_by_key(|(idx, _, _)| *idx);
+#[tauri::command]
+fn clear_title_usage() -> Result<(), String> {
+    usage::clear()
+}
 
-    let mut prices = Vec::with_capacity(raw_results.len());
-    let mut total_value = 0.0;
-    let mut most_expensive: Option<SkinPrice> = None;
-    let mut max_price = 0.0;
+// Предпросмотр с несохранёнными шаблонами: незаданный шаблон берётся из
+// директории конфигурации (variant — вариант заголовка для сравнения A/B).
+#[tauri::command]
+fn preview_listing_text(
+    data: AccountData,
+    templates: ListingTemplates,
+    variant: Option<String>,
+) -> Result<RenderedListing, String> {
+    let (default_variant, max_length) = load_listing_text_settings();
+    let saved = listing_text::load_templates(variant.as_deref().or(default_variant.as_deref()))?;
+    let templates = ListingTemplates {
+        title: templates.title.or(saved.title),
+        description: templates.description.or(saved.description),
+    };
+    listing_text::render_listing(&data, &templates, None, max_length)
+}
 
-    for (_, skin, result) in raw_results {
-        let price_str = match result {
-            Ok(p) => p,
-            Err(e) => {
-                println!("Error fetching price for {}: {}", skin, e);
-                "Error".to_string()
-            }
-        };
+#[tauri::command]
+fn get_listing_templates(variant: Option<String>) -> Result<ListingTemplates, String> {
+    listing_text::load_templates(variant.as_deref())
+}
 
-        let numeric_price = if price_str.starts_with('$') || price_str.starts_with("~$") {
-            price_str
-                .trim_start_matches('~')
-                .trim_start_matches('$')
-                .parse::<f64>()
-                .ok()
-                .unwrap_or(0.0)
-        } else {
-            0.0
-        };
+#[tauri::command]
+fn save_listing_template(name: String, content: String) -> Result<(), String> {
+    listing_text::save_template(&name, &content)
+}
 
-        if numeric_price > 0.0 {
-            total_value += numeric_price;
-            if numeric_price > max_price {
-                max_price = numeric_price;
-                most_expensive = Some(SkinPrice {
-                    skin_name: skin.clone(),
-                    price: price_str.clone(),
-                });
-            }
-        }
+// Журнал выставлений с фильтром по дате/серверу/статусу (новые — первыми).
+#[tauri::command]
+fn get_listing_history(query: Option<HistoryQuery>) -> Result<Vec<ListingRecord>, String> {
+    history::query(&query.unwrap_or_default())
+}
 
-        prices.push(SkinPrice {
-            skin_name: skin,
-            price: price_str,
-        });
-    }
+// Экспорт журнала в CSV или JSON; возвращает число выгруженных записей.
+#[tauri::command]
+fn export_listing_history(
+    query: Option<HistoryQuery>,
+    format: String,
+    output_path: String,
+) -> Result<usize, String> {
+    history::export(&query.unwrap_or_default(), &format, &PathBuf::from(output_path))
+}
 
-    Ok(SkinPriceResponse {
-        prices,
-        total_value: format!("${:.2}", total_value),
-        most_expensive,
-    })
+// Копирует лог текущего (или предыдущего) запуска в файл для отчёта об ошибке.
+#[tauri::command]
+fn export_session_log(output_path: String, previous: Option<bool>) -> Result<String, String> {
+    let source = logging::export_session_log(&PathBuf::from(output_path), previous.unwrap_or(false))?;
+    Ok(source.to_string_lossy().to_string())
 }
 
 #[tauri::command]
 fn get_g2g_config_status() -> Result<bool, String> {
-    match load_g2g_settings() {
+    match config::load_g2g_settings() {
         Ok(settings) => {
             settings.validate()
                 .map(|_| true)
@@ -975,159 +351,50 @@ async fn create_listing(
     skins_count: i32,
     personal_info: String,
     account_path: String,
+    offer_template: Option<OfferTemplateOverride>,
+    category: Option<String>,
     state: tauri::State<'_, AppState>
 ) -> Result<String, String> {
-    println!("📋 Creating listing - received personal_info:");
-    println!("   Length: {} chars", personal_info.len());
-
-    // Загружаем настройки динамически
-    let g2g_settings = load_g2g_settings()
-        .map_err(|e| format!("Не удалось загрузить настройки G2G: {}", e))?;
-
-    let screenshot_url = extract_screenshot_url(&personal_info);
-    if let Some(ref url) = screenshot_url {
-        println!("🖼️  Found screenshot URL: {}", url);
-    } else {
-        println!("⚠️  No screenshot URL found in personal info");
-    }
-
-    let csv_data = parse_account_to_csv(&personal_info)
-        .map_err(|e| format!("Failed to parse account data: {}", e))?;
-
-    println!("✅ Converted to CSV format");
-    println!("   CSV length: {} bytes", csv_data.len());
-
-    let tokens = G2GAuthTokens {
-        user_id: g2g_settings.user_id.clone(),
-        refresh_token: g2g_settings.refresh_token.clone(),
-        long_lived_token: g2g_settings.long_lived_token.clone(),
-        active_device_token: g2g_settings.active_device_token.clone(),
-    };
-
-    let mut client = state.g2g_client.lock().await;
-
-    let offer_id = client.create_full_offer_with_data(
-        &title,
-        &description,
+    let request = ListingRequest {
+        title,
+        description,
         price,
-        &server,
-        &rank,
+        server,
+        rank,
         champions_count,
         skins_count,
-        &csv_data,
-        screenshot_url.as_deref(),
-        &tokens,
-    ).await?;
-
-    println!("✅ Offer created! ID: {}", offer_id);
-
-    save_offer_id_to_file(&account_path, &offer_id)?;
-
-    // Имя аккаунта — это имя папки в account_path (для fallback Username).
-    let account_name = PathBuf::from(&account_path)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("")
-        .to_string();
-    sync_offer_to_sheet(&personal_info, &account_name, &account_path, &offer_id).await;
-
-    Ok(offer_id)
-}
-
-fn parse_account_to_csv(text: &str) -> Result<String, String> {
-    let start_marker = "Hi there,";
-    let start_idx = text.find(start_marker)
-        .ok_or("Could not find 'Hi there,' in account file")?;
-
-    let end_markers = [
-        "Thank you for buying from Accounterra,gl&hf!",
-        "Thank you for buying from Accounterra, gl&hf!",
-        "gl&hf!",
-    ];
-
-    let mut end_idx = None;
-    for marker in &end_markers {
-        if let Some(idx) = text[start_idx..].find(marker) {
-            end_idx = Some(start_idx + idx + marker.len());
-            break;
-        }
-    }
-
-    let description_block = if let Some(end) = end_idx {
-        &text[start_idx..end]
-    } else {
-        if let Some(screenshot_idx) = text[start_idx..].find("Screenshot URL") {
-            text[start_idx..start_idx + screenshot_idx].trim()
-        } else {
-            text[start_idx..].trim()
-        }
+        personal_info,
+        account_path,
+        offer_template,
+        category,
     };
-
-    let mut login = String::new();
-    let mut password = String::new();
-    let mut email = String::new();
-    let mut email_access = String::new();
-
-    for line in description_block.lines() {
-        let line = line.trim();
-        if line.starts_with("Login:") {
-            login = line.replace("Login:", "").trim().to_string();
-        } else if line.starts_with("Password:") {
-            password = line.replace("Password:", "").trim().to_string();
-        } else if line.starts_with("Email is") {
-            email = line.replace("Email is", "").trim().to_string();
-        } else if line.contains("[DOT]com/email/") && !line.starts_with("Email is") {
-            email_access = line.split_whitespace().next().unwrap_or("").to_string();
-        }
-    }
-
-    if login.is_empty() || password.is_empty() || email.is_empty() {
-        return Err(format!(
-            "Missing fields - Login: '{}', Password: '{}', Email: '{}'",
-            login, password, email
-        ));
-    }
-
-    let description_csv_safe = description_block
-        .replace("\"", "\"\"")
-        .replace("\r\n", "\n")
-        .replace("\r", "\n");
-
-    let csv_line = format!(
-        "{},{},,,,,,,,{},{},\"{}\"\r\n",
-        login,
-        password,
-        email,
-        email_access,
-        description_csv_safe
-    );
-
-    let comma_count = csv_line.matches(',').count();
-    println!("✅ CSV created with {} commas", comma_count);
-    println!("   First 200 chars: {}", &csv_line[..csv_line.len().min(200)]);
-
-    Ok(csv_line)
+    state.listing.create_listing(&request).await
 }
 
 // Команды настроек
 #[tauri::command]
 async fn load_settings() -> Result<AppSettings, String> {
-    println!("📖 Loading settings from file...");
+    info!("Loading settings from file...");
 
     match AppSettings::load() {
         Ok(settings) => {
-            println!("✅ Settings loaded successfully");
+            info!("Settings loaded successfully");
             Ok(settings)
         }
         Err(e) => {
-            println!("⚠️ Failed to load settings: {}", e);
+            warn!("Failed to load settings: {}", e);
 
             if let Some(g2g_settings) = config::load_from_env() {
-                println!("✅ Loaded G2G settings from .env (fallback)");
+                info!("Loaded G2G settings from .env (fallback)");
                 Ok(AppSettings {
                     g2g: g2g_settings,
                     theme: None,
                     sheets: None,
+                    offer_template: None,
+                    image_host: None,
+                    listing_text: None,
+                    usage: None,
+                    watcher: None,
                 })
             } else {
                 Err(e)
@@ -1138,20 +405,23 @@ async fn load_settings() -> Result<AppSettings, String> {
 
 #[tauri::command]
 async fn save_settings(settings: AppSettings) -> Result<(), String> {
-    println!("💾 Saving settings to file...");
-    println!("   User ID: {}", settings.g2g.user_id);
+    info!("Saving settings to file...");
+    settings.g2g.register_secrets();
+    if let Some(sheets) = &settings.sheets {
+        sheets.register_secrets();
+    }
 
     settings.save()?;
 
-    println!("✅ Settings saved successfully!");
+    info!("Settings saved successfully!");
     Ok(())
 }
 
 #[tauri::command]
 async fn clear_settings() -> Result<(), String> {
-    println!("🗑️ Clearing settings...");
+    info!("Clearing settings...");
     AppSettings::clear()?;
-    println!("✅ Settings cleared!");
+    info!("Settings cleared!");
     Ok(())
 }
 
@@ -1162,25 +432,46 @@ fn settings_exist() -> bool {
 
 #[tauri::command]
 fn cancel_price_calculation(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    println!("🛑 Cancelling price calculation...");
-    state.cancel_price_calc.store(true, Ordering::Relaxed);
+    state.prices.cancel();
     Ok(())
 }
 
 fn main() {
+    // Guard держим до выхода: он дописывает буфер логов в файл.
+    let _log_guard = match logging::init() {
+        Ok(guard) => Some(guard),
+        Err(e) => {
+            eprintln!("Логирование в файл недоступно: {}", e);
+            None
+        }
+    };
+
     // Не загружаем настройки при старте - они будут загружаться динамически
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
-        .manage(AppState {
-            g2g_client: Mutex::new(G2GApiClient::new()),
-            cancel_price_calc: Arc::new(AtomicBool::new(false)),
+        .manage({
+            let client = Arc::new(Mutex::new(G2GApiClient::new()));
+            AppState {
+                listing: ListingService::new(client.clone()),
+                prices: PriceService::new(client),
+                account_watcher: std::sync::Mutex::new(None),
+            }
+        })
+        .setup(|app| {
+            // Повтор записей в таблицу, не удавшихся раньше (в том числе в прошлых запусках)
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(sheet_outbox::retry_loop(Arc::new(move |result: OutboxFlushResult| {
+                let _ = handle.emit("sheet-outbox-changed", result);
+            })));
+            Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             greet,
             load_account_folders,
+            stop_account_watcher,
             get_account_files,
             read_account_file,
             read_text_file,
@@ -1191,6 +482,26 @@ fn main() {
             create_g2g_offer,
             create_listing,
             check_accounts_in_sheet,
+            update_sheet_row,
+            reconcile_accounts,
+            diagnose_sheet_webhook,
+            get_sheet_outbox,
+            flush_sheet_outbox,
+            list_categories,
+            refresh_category_mappings,
+            generate_listing_text,
+            preview_listing_text,
+            get_listing_templates,
+            save_listing_template,
+            track_title_usage,
+            rotate_title_champions,
+            import_champion_usage,
+            export_title_usage,
+            get_title_usage_stats,
+            clear_title_usage,
+            get_listing_history,
+            export_listing_history,
+            export_session_log,
             load_settings,
             save_settings,
             clear_settings,
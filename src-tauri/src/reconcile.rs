@@ -0,0 +1,401 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use tracing::{info, warn};
+
+use crate::accounts::{self, AccountRef, AccountStore};
+use crate::g2g_api::OfferStatus;
+use crate::listing::ListingService;
+use crate::sheet_sync::SheetSync;
+use crate::sheets::{SheetEntry, SheetRow, SheetUpdate};
+
+/// Статус, который сверка ставит в таблице офферу, пропавшему с G2G.
+pub const GONE_STATUS: &str = "Removed";
+
+// Заголовок колонки Offer ID (для исправления расхождения id).
+const OFFER_ID_COLUMN: &str = "Offer ID";
+
+/// Выставленный аккаунт из папки: логин и id из файла `G17*.txt`.
+#[derive(Debug, Clone)]
+pub struct LocalOffer {
+    pub account: AccountRef,
+    pub username: String,
+    pub offer_id: String,
+}
+
+/// Исправление, которое сверка может применить к таблице.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ReconcileFix {
+    AppendRow { row: SheetRow },
+    UpdateRow { update: SheetUpdate },
+}
+
+/// Расхождение между папками, таблицей и G2G.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconcileIssue {
+    /// missing_in_sheet — выставлен локально, в таблице нет;
+    /// offer_id_mismatch — Offer ID в таблице не совпадает с файлом;
+    /// offer_gone — оффера больше нет на G2G;
+    /// duplicate_local — один offer id в нескольких папках;
+    /// duplicate_in_sheet — один offer id в нескольких строках таблицы.
+    pub kind: String,
+    pub username: String,
+    /// Папка аккаунта (если расхождение связано с ней).
+    pub path: Option<String>,
+    pub local_offer_id: Option<String>,
+    pub sheet_offer_id: Option<String>,
+    pub sheet_status: Option<String>,
+    pub g2g_status: Option<String>,
+    /// None — исправить можно только вручную.
+    pub fix: Option<ReconcileFix>,
+    pub fixed: bool,
+    pub fix_error: Option<String>,
+}
+
+impl ReconcileIssue {
+    fn new(kind: &str, username: &str) -> Self {
+        Self { kind: kind.to_string(), username: username.to_string(), ..Default::default() }
+    }
+
+    fn local(kind: &str, local: &LocalOffer) -> Self {
+        Self {
+            path: Some(local.account.path.clone()),
+            local_offer_id: Some(local.offer_id.clone()),
+            ..Self::new(kind, &local.username)
+        }
+    }
+}
+
+/// Итог сверки.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconcileReport {
+    /// Выставленных аккаунтов в папке.
+    pub local_listed: usize,
+    pub sheet_rows: usize,
+    /// Сколько офферов проверено на G2G (None — G2G не проверялся).
+    pub g2g_checked: Option<usize>,
+    /// Офферы, которые не удалось проверить на G2G (ошибка запроса или
+    /// неожиданный ответ): о них сверка ничего не утверждает.
+    #[serde(default)]
+    pub g2g_unchecked: Vec<String>,
+    pub issues: Vec<ReconcileIssue>,
+    /// Сколько исправлений применено (с apply).
+    pub fixed: usize,
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn status_is(entry: &SheetEntry, status: &str) -> bool {
+    non_empty(&entry.status).is_some_and(|s| s.eq_ignore_ascii_case(status))
+}
+
+/// Офферы, которые стоит проверить на G2G: все локальные и строки таблицы,
+/// ещё не отмеченные как проданные или снятые.
+pub fn offers_to_check(local: &[LocalOffer], rows: &[SheetEntry]) -> Vec<String> {
+    let mut ids: BTreeSet<String> = local.iter().map(|l| l.offer_id.clone()).collect();
+    for row in rows {
+        if let Some(offer_id) = non_empty(&row.offer_id) {
+            if !status_is(row, "Sold") && !status_is(row, GONE_STATUS) {
+                ids.insert(offer_id.to_string());
+            }
+        }
+    }
+    ids.into_iter().collect()
+}
+
+/// Сопоставляет выставленные аккаунты, строки таблицы и состояние офферов на
+/// G2G (None — без проверки G2G). append_row строит строку для аккаунта,
+/// которого нет в таблице.
+pub fn analyze(
+    local: &[LocalOffer],
+    rows: &[SheetEntry],
+    g2g: Option<&HashMap<String, OfferStatus>>,
+    append_row: impl Fn(&LocalOffer) -> SheetRow,
+) -> Vec<ReconcileIssue> {
+    let mut issues = Vec::new();
+
+    let mut local_by_offer: HashMap<&str, Vec<&LocalOffer>> = HashMap::new();
+    for l in local {
+        local_by_offer.entry(l.offer_id.as_str()).or_default().push(l);
+    }
+    let mut rows_by_offer: HashMap<&str, Vec<&SheetEntry>> = HashMap::new();
+    let mut last_by_username: HashMap<String, &SheetEntry> = HashMap::new();
+    for row in rows {
+        if let Some(offer_id) = non_empty(&row.offer_id) {
+            rows_by_offer.entry(offer_id).or_default().push(row);
+        }
+        let key = row.username.trim().to_lowercase();
+        if !key.is_empty() {
+            last_by_username.insert(key, row);
+        }
+    }
+
+    for l in local {
+        if local_by_offer[l.offer_id.as_str()].len() > 1 {
+            issues.push(ReconcileIssue::local("duplicate_local", l));
+        }
+        if rows_by_offer.contains_key(l.offer_id.as_str()) {
+            continue;
+        }
+        let by_username = last_by_username.get(&l.username.trim().to_lowercase());
+        match by_username.and_then(|row| non_empty(&row.offer_id).map(|id| (row, id))) {
+            // Строка аккаунта есть, но с другим id (перевыставили, а таблицу не
+            // обновили). Строка ищется по её же Offer ID, а не по логину:
+            // поиск по логину мог бы попасть в другую строку (старую проданную).
+            Some((row, sheet_offer_id)) => {
+                let mut columns = HashMap::new();
+                columns.insert(OFFER_ID_COLUMN.to_string(), l.offer_id.clone());
+                issues.push(ReconcileIssue {
+                    sheet_offer_id: Some(sheet_offer_id.to_string()),
+                    sheet_status: row.status.clone(),
+                    fix: Some(ReconcileFix::UpdateRow {
+                        update: SheetUpdate {
+                            offer_id: Some(sheet_offer_id.to_string()),
+                            columns,
+                            ..Default::default()
+                        },
+                    }),
+                    ..ReconcileIssue::local("offer_id_mismatch", l)
+                });
+            }
+            // Строки нет или в ней пустой Offer ID — запись её заполнит
+            None => issues.push(ReconcileIssue {
+                sheet_status: by_username.and_then(|row| row.status.clone()),
+                fix: Some(ReconcileFix::AppendRow { row: append_row(l) }),
+                ..ReconcileIssue::local("missing_in_sheet", l)
+            }),
+        }
+    }
+
+    let mut duplicated: Vec<(&str, &Vec<&SheetEntry>)> =
+        rows_by_offer.iter().filter(|(_, rows)| rows.len() > 1).map(|(id, rows)| (*id, rows)).collect();
+    duplicated.sort_by_key(|(id, _)| *id);
+    for (offer_id, dup_rows) in duplicated {
+        let last = dup_rows[dup_rows.len() - 1];
+        issues.push(ReconcileIssue {
+            sheet_offer_id: Some(offer_id.to_string()),
+            sheet_status: last.status.clone(),
+            ..ReconcileIssue::new("duplicate_in_sheet", &last.username)
+        });
+    }
+
+    if let Some(statuses) = g2g {
+        let mut gone: Vec<&OfferStatus> = statuses.values().filter(|s| s.is_gone()).collect();
+        gone.sort_by(|a, b| a.offer_id.cmp(&b.offer_id));
+        for status in gone {
+            let offer_id = status.offer_id.as_str();
+            let local_offer = local_by_offer.get(offer_id).and_then(|l| l.first());
+            let row = rows_by_offer.get(offer_id).and_then(|rows| rows.last());
+            // Проданный или уже снятый оффер и не должен быть на G2G
+            let closed = row.is_some_and(|r| status_is(r, "Sold") || status_is(r, GONE_STATUS));
+            if closed || (local_offer.is_none() && row.is_none()) {
+                continue;
+            }
+
+            let mut issue = match local_offer {
+                Some(l) => ReconcileIssue::local("offer_gone", l),
+                None => ReconcileIssue::new("offer_gone", row.map(|r| r.username.as_str()).unwrap_or_default()),
+            };
+            issue.g2g_status = Some(status.status.clone().unwrap_or_else(|| "not_found".to_string()));
+            if let Some(row) = row {
+                issue.sheet_offer_id = Some(offer_id.to_string());
+                issue.sheet_status = row.status.clone();
+                issue.fix = Some(ReconcileFix::UpdateRow {
+                    update: SheetUpdate {
+                        offer_id: Some(offer_id.to_string()),
+                        status: Some(GONE_STATUS.to_string()),
+                        ..Default::default()
+                    },
+                });
+            }
+            issues.push(issue);
+        }
+    }
+
+    issues
+}
+
+/// Сверяет выставленные аккаунты папки с Google-таблицей и (если передан
+/// listing) с офферами на G2G. С apply применяет исправления: дописывает
+/// недостающие строки, исправляет Offer ID и отмечает пропавшие офферы.
+pub async fn reconcile(
+    store: &AccountStore,
+    sheet: &SheetSync,
+    listing: Option<&ListingService>,
+    apply: bool,
+) -> Result<ReconcileReport, String> {
+    let local: Vec<LocalOffer> = store
+        .listed()?
+        .into_iter()
+        .map(|(account, offer_id)| LocalOffer { username: accounts::read_account_login(&account), account, offer_id })
+        .collect();
+    let rows = sheet.backend().fetch_rows().await?;
+
+    let check = match listing {
+        Some(listing) => Some(listing.offer_statuses(&offers_to_check(&local, &rows)).await?),
+        None => None,
+    };
+    let statuses = check.as_ref().map(|c| &c.statuses);
+
+    let mut report = ReconcileReport {
+        local_listed: local.len(),
+        sheet_rows: rows.len(),
+        g2g_checked: statuses.map(|s| s.len()),
+        g2g_unchecked: check.as_ref().map(|c| c.unchecked.clone()).unwrap_or_default(),
+        issues: analyze(&local, &rows, statuses, |l| {
+            sheet.account_row(&l.account, l.username.clone(), &l.offer_id)
+        }),
+        fixed: 0,
+    };
+
+    if apply {
+        // Недостающие строки дописываются одной пачкой, исправления — по одному
+        let appends: Vec<(usize, SheetRow)> = report
+            .issues
+            .iter()
+            .enumerate()
+            .filter_map(|(i, issue)| match &issue.fix {
+                Some(ReconcileFix::AppendRow { row }) => Some((i, row.clone())),
+                _ => None,
+            })
+            .collect();
+        let mut outcomes = Vec::new();
+        if !appends.is_empty() {
+            let rows: Vec<SheetRow> = appends.iter().map(|(_, row)| row.clone()).collect();
+            match sheet.append_rows(&rows).await {
+                Ok(results) => outcomes.extend(appends.iter().zip(results).map(|((i, _), r)| (*i, r.into_result()))),
+                Err(e) => outcomes.extend(appends.iter().map(|(i, _)| (*i, Err(e.clone())))),
+            }
+        }
+        for (i, issue) in report.issues.iter().enumerate() {
+            if let Some(ReconcileFix::UpdateRow { update }) = &issue.fix {
+                let result = sheet.update_row(update).await.and_then(|r| {
+                    if r.found { Ok(()) } else { Err("Строка не найдена в таблице".to_string()) }
+                });
+                outcomes.push((i, result));
+            }
+        }
+
+        for (i, result) in outcomes {
+            let issue = &mut report.issues[i];
+            match result {
+                Ok(()) => {
+                    issue.fixed = true;
+                    report.fixed += 1;
+                }
+                Err(e) => {
+                    warn!("Не удалось исправить {} ({}): {}", issue.kind, issue.username, e);
+                    issue.fix_error = Some(e);
+                }
+            }
+        }
+    }
+
+    info!(
+        "Reconcile: {} listed, {} sheet row(s), {} issue(s), {} fixed, {} unchecked on G2G",
+        report.local_listed,
+        report.sheet_rows,
+        report.issues.len(),
+        report.fixed,
+        report.g2g_unchecked.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(name: &str, offer_id: &str) -> LocalOffer {
+        LocalOffer {
+            account: AccountRef { name: name.to_string(), path: format!("/accounts/{}", name) },
+            username: name.to_string(),
+            offer_id: offer_id.to_string(),
+        }
+    }
+
+    fn entry(username: &str, status: &str, offer_id: &str) -> SheetEntry {
+        SheetEntry {
+            username: username.to_string(),
+            status: Some(status.to_string()),
+            offer_id: Some(offer_id.to_string()).filter(|s| !s.is_empty()),
+        }
+    }
+
+    fn status(offer_id: &str, found: bool) -> OfferStatus {
+        OfferStatus { offer_id: offer_id.to_string(), found, status: found.then(|| "live".to_string()) }
+    }
+
+    #[test]
+    fn test_analyze() {
+        let local = vec![
+            local("ok1", "G1700000001"),
+            local("missing", "G1700000002"),
+            local("relisted", "G1700000003"),
+            local("copy1", "G1700000004"),
+            local("copy2", "G1700000004"),
+        ];
+        let rows = vec![
+            entry("ok1", "Active", "G1700000001"),
+            entry("relisted", "Active", "G1700000099"),
+            entry("copy1", "Active", "G1700000004"),
+            entry("old", "Active", "G1700000050"),
+            entry("old", "Active", "G1700000050"),
+            entry("sold", "Sold", "G1700000060"),
+        ];
+        let ids = offers_to_check(&local, &rows);
+        assert!(ids.contains(&"G1700000050".to_string()));
+        assert!(!ids.contains(&"G1700000060".to_string()));
+
+        let statuses: HashMap<String, OfferStatus> = [
+            status("G1700000001", true),
+            status("G1700000050", false),
+            status("G1700000060", false),
+        ]
+        .into_iter()
+        .map(|s| (s.offer_id.clone(), s))
+        .collect();
+
+        let issues = analyze(&local, &rows, Some(&statuses), |l| SheetRow {
+            username: l.username.clone(),
+            offer_id: l.offer_id.clone(),
+            listed_date: String::new(),
+            folder: "/accounts".to_string(),
+            status: "Active".to_string(),
+            columns: Default::default(),
+        });
+        let kinds: Vec<(&str, &str)> = issues.iter().map(|i| (i.kind.as_str(), i.username.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("missing_in_sheet", "missing"),
+                ("offer_id_mismatch", "relisted"),
+                ("duplicate_local", "copy1"),
+                ("duplicate_local", "copy2"),
+                ("duplicate_in_sheet", "old"),
+                ("offer_gone", "old"),
+            ]
+        );
+
+        assert!(matches!(&issues[0].fix, Some(ReconcileFix::AppendRow { row }) if row.offer_id == "G1700000002"));
+        match &issues[1].fix {
+            Some(ReconcileFix::UpdateRow { update }) => {
+                assert_eq!(update.username, None);
+                assert_eq!(update.offer_id.as_deref(), Some("G1700000099"));
+                assert_eq!(update.columns.get(OFFER_ID_COLUMN).map(String::as_str), Some("G1700000003"));
+            }
+            other => panic!("unexpected fix: {:?}", other),
+        }
+        assert!(issues[2].fix.is_none());
+        match &issues[5].fix {
+            Some(ReconcileFix::UpdateRow { update }) => assert_eq!(update.status.as_deref(), Some(GONE_STATUS)),
+            other => panic!("unexpected fix: {:?}", other),
+        }
+        assert_eq!(issues[5].g2g_status.as_deref(), Some("not_found"));
+
+        let json = serde_json::to_value(&issues[0]).unwrap();
+        assert_eq!(json["fix"]["action"], "append_row");
+    }
+}
@@ -0,0 +1,233 @@
+use regex::Regex;
+use std::io::{self, Write};
+use std::sync::{LazyLock, RwLock};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Чем заменяются замаскированные значения.
+pub const MASK: &str = "[REDACTED]";
+
+// Значения короче этого не регистрируются: маскирование "abc" испортило бы
+// половину лога, а угадать такой секрет всё равно нельзя по логу.
+const MIN_SECRET_LEN: usize = 4;
+
+// Сколько секретов держим одновременно: при превышении забываются самые
+// старые (аккаунты, выставленные давно в этой же сессии).
+const MAX_SECRETS: usize = 256;
+
+// Конкретные секреты текущей сессии: токены из настроек, логины/пароли/почты
+// аккаунтов, прошедших через parse_account_to_csv, содержимое softpin.
+static SECRETS: RwLock<SecretSet> = RwLock::new(SecretSet::new());
+
+// Секреты по видам: новое значение того же вида (обновлённый токен,
+// изменённые настройки) заменяет прежнее, а не добавляется к нему.
+struct SecretSet {
+    // (вид, значение) в порядке регистрации
+    entries: Vec<(String, String)>,
+    // Значения, длинные первыми — чтобы секрет, содержащий другой,
+    // маскировался целиком.
+    by_length: Vec<String>,
+}
+
+impl SecretSet {
+    const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            by_length: Vec::new(),
+        }
+    }
+
+    fn insert(&mut self, kind: &str, value: &str, cap: usize) {
+        self.entries.retain(|(k, _)| k != kind);
+        if value.chars().count() >= MIN_SECRET_LEN {
+            self.entries.push((kind.to_string(), value.to_string()));
+        }
+        if self.entries.len() > cap {
+            let excess = self.entries.len() - cap;
+            self.entries.drain(..excess);
+        }
+
+        self.by_length = self.entries.iter().map(|(_, v)| v.clone()).collect();
+        self.by_length.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        self.by_length.dedup();
+    }
+}
+
+// Значение после ключа вида "Password: x", "refresh_token=x", "\"softpin\":\"x\"".
+static KEY_VALUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)(\b(?:password|passwd|pwd|pass|login|softpin|secret|authorization|access_token|refresh_token|long_lived_token|active_device_token|token)\b"?\s*[:=]\s*"?)(?:bearer\s+)?[^\s",;}]+"#,
+    )
+    .expect("valid key-value regex")
+});
+
+static BEARER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bbearer\s+[A-Za-z0-9._~+/=-]+").expect("valid bearer regex"));
+
+static JWT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*").expect("valid jwt regex")
+});
+
+// Ссылки доступа к почте аккаунта ("...[DOT]com/email/...").
+static EMAIL_ACCESS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\S*\[DOT\]com/email/\S*").expect("valid email access regex"));
+
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Za-z0-9._%+-]+@((?:[A-Za-z0-9-]+\.)+[A-Za-z]{2,})").expect("valid email regex")
+});
+
+/// Регистрирует значение, которое никогда не должно попасть в лог как есть.
+/// kind — вид секрета ("g2g.refresh_token", "account:<логин>:password"):
+/// повторная регистрация того же вида заменяет прежнее значение.
+pub fn register_secret(kind: &str, value: &str) {
+    if let Ok(mut secrets) = SECRETS.write() {
+        secrets.insert(kind, value.trim(), MAX_SECRETS);
+    }
+}
+
+/// Маскирует пароли, почты, токены и зарегистрированные секреты в тексте.
+pub fn redact(text: &str) -> String {
+    let mut out = text.to_string();
+    if let Ok(secrets) = SECRETS.read() {
+        for secret in secrets.by_length.iter() {
+            if out.contains(secret.as_str()) {
+                out = out.replace(secret.as_str(), MASK);
+            }
+        }
+    }
+    let out = EMAIL_ACCESS.replace_all(&out, MASK);
+    let out = JWT.replace_all(&out, MASK);
+    let out = BEARER.replace_all(&out, MASK);
+    let out = KEY_VALUE.replace_all(&out, format!("${{1}}{}", MASK).as_str());
+    // У почты оставляем домен — по нему видно, о каком сервисе речь.
+    EMAIL.replace_all(&out, format!("{}@${{1}}", MASK).as_str()).into_owned()
+}
+
+/// Писатель для tracing: копит одно событие и пишет его уже замаскированным.
+pub struct RedactingWriter<W: Write> {
+    inner: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let text = String::from_utf8_lossy(&self.buffer);
+            self.inner.write_all(redact(&text).as_bytes())?;
+            self.buffer.clear();
+        }
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for RedactingWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Обёртка над MakeWriter: весь вывод слоя логов проходит через redact().
+pub struct RedactingMakeWriter<M> {
+    inner: M,
+}
+
+impl<M> RedactingMakeWriter<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            inner: self.inner.make_writer(),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::{debug, info, warn};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    const FIXTURE: &str = "Hi there,\n\
+        Login: fixture_login_42\n\
+        Password: S3cr3t-Pa55!\n\
+        Email is fixture.mail@example.com\n\
+        fixturemail[DOT]com/email/q8Zk2LpX access link\n\
+        Thank you for buying from Accounterra, gl&hf!";
+
+    #[test]
+    fn test_fixture_credentials_never_logged() {
+        let buffer = SharedBuffer::default();
+        let make_writer = {
+            let buffer = buffer.clone();
+            move || buffer.clone()
+        };
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .with_writer(RedactingMakeWriter::new(make_writer))
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let csv = crate::accounts::parse_account_to_csv(FIXTURE).unwrap();
+            // Нарочно небрежные сообщения: всё это должно быть замаскировано
+            debug!("CSV line: {}", csv);
+            debug!("{}", serde_json::json!({ "softpin": csv, "seller_id": "1" }));
+            info!("Password: S3cr3t-Pa55! for fixture_login_42");
+            info!("refresh_token=eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiIxIn0.c2ln Authorization: Bearer abc.def");
+
+            let missing = crate::accounts::parse_account_to_csv("Hi there,\nLogin: fixture_login_42\nPassword: S3cr3t-Pa55!\n");
+            warn!("{}", missing.unwrap_err());
+        });
+
+        let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        for secret in ["fixture_login_42", "S3cr3t-Pa55!", "fixture.mail", "q8Zk2LpX", "eyJhbGciOiJIUzI1NiJ9", "abc.def"] {
+            assert!(!logs.contains(secret), "secret {:?} leaked into logs:\n{}", secret, logs);
+        }
+        assert!(logs.contains(MASK));
+        // Незарегистрированная почта маскируется по шаблону, домен остаётся
+        assert_eq!(redact("reply to john.doe@gmail.com"), "reply to [REDACTED]@gmail.com");
+    }
+
+    #[test]
+    fn test_secret_set_replaces_by_kind_and_caps() {
+        let mut set = SecretSet::new();
+        set.insert("g2g.access_token", "token-one", 3);
+        set.insert("g2g.access_token", "token-two", 3);
+        assert_eq!(set.by_length, vec!["token-two"]);
+
+        // Короткое значение снимает прежнее и само не регистрируется
+        set.insert("g2g.access_token", "abc", 3);
+        assert!(set.by_length.is_empty());
+
+        for (kind, value) in [("a", "first"), ("b", "second"), ("c", "third-longest"), ("d", "fourth")] {
+            set.insert(kind, value, 3);
+        }
+        assert_eq!(set.entries.len(), 3);
+        assert_eq!(set.by_length, vec!["third-longest", "second", "fourth"]);
+    }
+}
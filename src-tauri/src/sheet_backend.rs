@@ -0,0 +1,173 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tracing::warn;
+
+use crate::config::{self, SheetsSettings};
+use crate::ledger::LocalLedgerBackend;
+use crate::sheets::{self, SheetAppendResult, SheetEntry, SheetRow, SheetUpdate, SheetUpdateResult};
+use crate::sheets_api::SheetsApiBackend;
+
+/// Future операции с таблицей; боксится, чтобы бэкенд можно было выбрать
+/// по настройкам и держать как `Arc<dyn SheetBackend>`.
+pub type BackendFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// Способ доступа к таблице: веб-хук Apps Script, Sheets API или локальный
+/// файл (CSV/XLSX). Все ищут вкладку по заголовкам колонок и ведут себя одинаково
+/// (см. docs/google-sheets-integration.md).
+pub trait SheetBackend: Send + Sync + std::fmt::Debug {
+    /// Короткое имя для логов и диагностики ("webhook", "api").
+    fn name(&self) -> &'static str;
+
+    /// Записывает строку о выставленном оффере: заполняет строку с тем же
+    /// Username и пустым Offer ID или добавляет новую в конец.
+    fn append_row<'a>(&'a self, row: &'a SheetRow) -> BackendFuture<'a, ()>;
+
+    /// Записывает несколько строк с результатом для каждой; Err — пачка не
+    /// записана целиком. По умолчанию — по одной через append_row.
+    fn append_rows<'a>(&'a self, rows: &'a [SheetRow]) -> BackendFuture<'a, Vec<SheetAppendResult>> {
+        Box::pin(async move {
+            let mut results = Vec::with_capacity(rows.len());
+            for row in rows {
+                results.push(SheetAppendResult::from_result(row, self.append_row(row).await));
+            }
+            Ok(results)
+        })
+    }
+
+    /// Все строки таблицы (username, status, offer_id).
+    fn fetch_rows(&self) -> BackendFuture<'_, Vec<SheetEntry>>;
+
+    /// Меняет поля существующей строки; found = false — строки нет.
+    fn update_row<'a>(&'a self, update: &'a SheetUpdate) -> BackendFuture<'a, SheetUpdateResult>;
+}
+
+/// Веб-хук Google Apps Script (скрипт из docs/google-sheets-integration.md).
+#[derive(Debug, Clone)]
+pub struct WebhookBackend {
+    url: String,
+    secret: Option<String>,
+    // Версия скрипта для пачек: запрашивается один раз на бэкенд
+    script_version: tokio::sync::OnceCell<u32>,
+}
+
+impl WebhookBackend {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), secret: None, script_version: tokio::sync::OnceCell::new() }
+    }
+
+    /// Подписывать запросы общим секретом (SheetsSettings::webhook_secret).
+    /// Пробелы по краям (при вставке в форму) в секрет не входят.
+    pub fn with_secret(mut self, secret: Option<String>) -> Self {
+        self.secret = secret.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl SheetBackend for WebhookBackend {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn append_row<'a>(&'a self, row: &'a SheetRow) -> BackendFuture<'a, ()> {
+        Box::pin(sheets::append_row(&self.url, self.secret.as_deref(), row))
+    }
+
+    fn append_rows<'a>(&'a self, rows: &'a [SheetRow]) -> BackendFuture<'a, Vec<SheetAppendResult>> {
+        Box::pin(async move {
+            if rows.is_empty() {
+                return Ok(Vec::new());
+            }
+            let version = *self.script_version.get_or_try_init(|| sheets::script_version(&self.url)).await?;
+            sheets::append_rows_for_version(&self.url, self.secret.as_deref(), version, rows).await
+        })
+    }
+
+    fn fetch_rows(&self) -> BackendFuture<'_, Vec<SheetEntry>> {
+        Box::pin(sheets::fetch_rows(&self.url, self.secret.as_deref()))
+    }
+
+    fn update_row<'a>(&'a self, update: &'a SheetUpdate) -> BackendFuture<'a, SheetUpdateResult> {
+        Box::pin(sheets::update_row(&self.url, self.secret.as_deref(), update))
+    }
+}
+
+/// Google-таблица и локальный файл-журнал одновременно: строки пишутся в оба,
+/// читаются из таблицы. Ошибка журнала только логируется — результат
+/// (и очередь повторов) определяет таблица.
+#[derive(Debug)]
+pub struct MirroredBackend {
+    primary: Arc<dyn SheetBackend>,
+    ledger: LocalLedgerBackend,
+}
+
+impl MirroredBackend {
+    pub fn new(primary: Arc<dyn SheetBackend>, ledger: LocalLedgerBackend) -> Self {
+        Self { primary, ledger }
+    }
+}
+
+impl SheetBackend for MirroredBackend {
+    fn name(&self) -> &'static str {
+        self.primary.name()
+    }
+
+    fn append_row<'a>(&'a self, row: &'a SheetRow) -> BackendFuture<'a, ()> {
+        Box::pin(async move {
+            // Повтор из очереди перезапишет ту же строку журнала (по Offer ID)
+            if let Err(e) = self.ledger.append_row(row).await {
+                warn!("Не удалось записать оффер {} в файл-журнал: {}", row.offer_id, e);
+            }
+            self.primary.append_row(row).await
+        })
+    }
+
+    fn append_rows<'a>(&'a self, rows: &'a [SheetRow]) -> BackendFuture<'a, Vec<SheetAppendResult>> {
+        Box::pin(async move {
+            if let Err(e) = self.ledger.append_rows(rows).await {
+                warn!("Не удалось записать {} строк(и) в файл-журнал: {}", rows.len(), e);
+            }
+            self.primary.append_rows(rows).await
+        })
+    }
+
+    fn fetch_rows(&self) -> BackendFuture<'_, Vec<SheetEntry>> {
+        self.primary.fetch_rows()
+    }
+
+    fn update_row<'a>(&'a self, update: &'a SheetUpdate) -> BackendFuture<'a, SheetUpdateResult> {
+        Box::pin(async move {
+            if let Err(e) = self.ledger.update_row(update).await {
+                warn!("Не удалось обновить строку файла-журнала: {}", e);
+            }
+            self.primary.update_row(update).await
+        })
+    }
+}
+
+/// Бэкенд по настройкам таблицы (SheetsSettings::backend); с ledger_path
+/// у "webhook" и "api" строки дублируются в локальный файл.
+pub fn from_settings(settings: &SheetsSettings) -> Result<Arc<dyn SheetBackend>, String> {
+    settings.validate()?;
+    let ledger_path = settings.ledger_path.as_deref().map(str::trim).filter(|p| !p.is_empty());
+    let primary: Arc<dyn SheetBackend> = match settings.backend.as_str() {
+        config::SHEETS_BACKEND_LOCAL => {
+            return Ok(Arc::new(LocalLedgerBackend::new(ledger_path.unwrap_or_default())?));
+        }
+        config::SHEETS_BACKEND_API => {
+            let spreadsheet_id = settings.spreadsheet_id.as_deref().unwrap_or_default();
+            let key_path = settings.service_account_key.as_deref().unwrap_or_default();
+            Arc::new(SheetsApiBackend::from_key_file(spreadsheet_id, key_path)?)
+        }
+        _ => Arc::new(WebhookBackend::new(settings.webhook_url.trim()).with_secret(settings.webhook_secret.clone())),
+    };
+    match ledger_path {
+        Some(path) => Ok(Arc::new(MirroredBackend::new(primary, LocalLedgerBackend::new(path)?))),
+        None => Ok(primary),
+    }
+}
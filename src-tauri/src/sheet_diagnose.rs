@@ -0,0 +1,587 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::config;
+use crate::sheets::{self, BATCH_SCRIPT_VERSION, SCRIPT_VERSION, UPSERT_SCRIPT_VERSION};
+
+/// Версия скрипта, в которой ?test=1&dry_run=1 ничего не записывает. Старый
+/// скрипт пропустил бы dry_run и дописал строку TEST.
+pub const DRY_RUN_SCRIPT_VERSION: u32 = 9;
+
+const MAX_REDIRECTS: usize = 5;
+
+/// Итог одной проверки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+    /// Не проверялось: не дошли (предыдущая проверка не прошла) или не нужно.
+    Skipped,
+}
+
+/// Пункт чек-листа диагностики веб-хука.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    /// url, access, response, content_type, json, script_version, list,
+    /// signature, headers или write_access.
+    pub id: String,
+    pub status: CheckStatus,
+    /// Что получилось.
+    pub message: String,
+    /// Что сделать, если проверка не прошла.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl DiagnosticCheck {
+    fn new(id: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self { id: id.to_string(), status, message: message.into(), hint: None }
+    }
+
+    fn ok(id: &str, message: impl Into<String>) -> Self {
+        Self::new(id, CheckStatus::Ok, message)
+    }
+
+    fn warning(id: &str, message: impl Into<String>, hint: &str) -> Self {
+        Self::new(id, CheckStatus::Warning, message).with_hint(hint)
+    }
+
+    fn error(id: &str, message: impl Into<String>, hint: &str) -> Self {
+        Self::new(id, CheckStatus::Error, message).with_hint(hint)
+    }
+
+    fn skipped(id: &str, message: impl Into<String>) -> Self {
+        Self::new(id, CheckStatus::Skipped, message)
+    }
+
+    fn with_hint(mut self, hint: &str) -> Self {
+        self.hint = Some(hint.to_string());
+        self
+    }
+}
+
+/// Результат диагностики: чек-лист по порядку, ok — ни одной ошибки.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookDiagnosis {
+    pub ok: bool,
+    pub script_version: Option<u32>,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+impl WebhookDiagnosis {
+    fn push(&mut self, check: DiagnosticCheck) -> CheckStatus {
+        let status = check.status;
+        self.checks.push(check);
+        status
+    }
+
+    // Проверки, до которых не дошли после ошибки.
+    fn skip_rest(&mut self, ids: &[&str]) {
+        for id in ids {
+            self.checks.push(DiagnosticCheck::skipped(id, "Не проверялось: исправь ошибку выше"));
+        }
+    }
+}
+
+// Ответ веб-хука на GET: без автоматических редиректов, чтобы видеть, куда
+// ведёт Apps Script (script.googleusercontent.com или страница входа Google).
+struct Fetched {
+    status: u16,
+    content_type: String,
+    body: String,
+    /// Хосты, через которые прошли редиректы.
+    redirects: Vec<String>,
+}
+
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<Fetched, String> {
+    let mut url = reqwest::Url::parse(url).map_err(|e| format!("Некорректный URL: {}", e))?;
+    let mut redirects = Vec::new();
+    loop {
+        let response = client.get(url.clone()).send().await.map_err(|e| format!("Запрос не прошёл: {}", e))?;
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|l| l.to_str().ok())
+                .ok_or_else(|| format!("Редирект {} без адреса", response.status()))?;
+            url = url.join(location).map_err(|e| format!("Некорректный адрес редиректа: {}", e))?;
+            redirects.push(url.host_str().unwrap_or_default().to_string());
+            if redirects.len() > MAX_REDIRECTS {
+                return Err(format!("Больше {} редиректов: {}", MAX_REDIRECTS, redirects.join(" → ")));
+            }
+            continue;
+        }
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|c| c.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let body = response.text().await.map_err(|e| format!("Не удалось прочитать ответ: {}", e))?;
+        return Ok(Fetched { status, content_type, body, redirects });
+    }
+}
+
+fn with_param(webhook_url: &str, param: &str) -> String {
+    let separator = if webhook_url.contains('?') { "&" } else { "?" };
+    format!("{}{}{}", webhook_url, separator, param)
+}
+
+/// Проверка формы URL: https, script.google.com, развёртывание /exec.
+pub fn check_url(webhook_url: &str) -> DiagnosticCheck {
+    let Ok(url) = reqwest::Url::parse(webhook_url.trim()) else {
+        return DiagnosticCheck::error(
+            "url",
+            "URL веб-хука пустой или некорректный",
+            "Скопируй Web app URL из Deploy → Manage deployments (вида https://script.google.com/macros/s/.../exec)",
+        );
+    };
+    let host = url.host_str().unwrap_or_default();
+    if url.path().ends_with("/dev") {
+        return DiagnosticCheck::error(
+            "url",
+            "URL заканчивается на /dev — это тестовое развёртывание, оно открывается только владельцу после входа в Google",
+            "Сделай Deploy → New deployment → Web app и возьми URL, который заканчивается на /exec",
+        );
+    }
+    if host != "script.google.com" {
+        return DiagnosticCheck::warning(
+            "url",
+            format!("Хост {} — не script.google.com", host),
+            "Обычно URL веб-хука вида https://script.google.com/macros/s/.../exec; другой адрес подходит только для своего прокси",
+        );
+    }
+    if url.scheme() != "https" || !url.path().ends_with("/exec") {
+        return DiagnosticCheck::warning(
+            "url",
+            "URL не похож на развёртывание веб-приложения (https://…/exec)",
+            "Скопируй Web app URL из Deploy → Manage deployments",
+        );
+    }
+    DiagnosticCheck::ok("url", "URL развёртывания веб-приложения")
+}
+
+// Куда привели редиректы: страница входа значит, что доступ не «Anyone».
+fn check_access(fetched: &Fetched) -> DiagnosticCheck {
+    let login = fetched.redirects.iter().any(|h| h == "accounts.google.com")
+        || (fetched.content_type.starts_with("text/html") && looks_like_login(&fetched.body));
+    if login {
+        return DiagnosticCheck::error(
+            "access",
+            "Google просит войти в аккаунт вместо ответа скрипта",
+            "В Deploy → Manage deployments → Edit поставь Execute as: Me и Who has access: Anyone, затем Deploy",
+        );
+    }
+    match fetched.redirects.as_slice() {
+        [] => DiagnosticCheck::ok("access", "Ответ без редиректов"),
+        hosts => DiagnosticCheck::ok("access", format!("Редирект: {}", hosts.join(" → "))),
+    }
+}
+
+fn looks_like_login(body: &str) -> bool {
+    body.contains("accounts.google.com") || body.contains("ServiceLogin")
+}
+
+// HTTP-статус, Content-Type и JSON; Some(json) — можно проверять дальше.
+fn check_response(fetched: &Fetched, diagnosis: &mut WebhookDiagnosis) -> Option<Value> {
+    let response = match fetched.status {
+        200..=299 => DiagnosticCheck::ok("response", format!("HTTP {}", fetched.status)),
+        404 => DiagnosticCheck::error(
+            "response",
+            "HTTP 404 — развёртывание не найдено",
+            "Развёртывание удалено или URL скопирован не полностью: возьми URL из Deploy → Manage deployments",
+        ),
+        status => DiagnosticCheck::error(
+            "response",
+            format!("HTTP {}", status),
+            "Открой URL в браузере и посмотри текст ошибки; проверь Executions в редакторе скрипта",
+        ),
+    };
+    if diagnosis.push(response) == CheckStatus::Error {
+        diagnosis.skip_rest(&["content_type", "json"]);
+        return None;
+    }
+
+    if !fetched.content_type.starts_with("application/json") {
+        let title = html_title(&fetched.body).map(|t| format!(" «{}»", t)).unwrap_or_default();
+        diagnosis.push(DiagnosticCheck::error(
+            "content_type",
+            format!("Ответ {}{} вместо JSON", or_unknown(&fetched.content_type), title),
+            "HTML вместо JSON — страница входа или ошибки Google. Проверь доступ Anyone; если скрипт падает — смотри Executions",
+        ));
+        diagnosis.skip_rest(&["json"]);
+        return None;
+    }
+    diagnosis.push(DiagnosticCheck::ok("content_type", "application/json"));
+
+    match serde_json::from_str::<Value>(&fetched.body) {
+        Ok(json) if json["ok"].is_boolean() => {
+            diagnosis.push(DiagnosticCheck::ok("json", "Ответ скрипта с полем ok"));
+            Some(json)
+        }
+        _ => {
+            diagnosis.push(DiagnosticCheck::error(
+                "json",
+                format!("Ответ не похож на ответ скрипта: {}", snippet(&fetched.body)),
+                "Вставь код скрипта из docs/google-sheets-integration.md и сделай Deploy → New version",
+            ));
+            None
+        }
+    }
+}
+
+fn html_title(body: &str) -> Option<String> {
+    let start = body.find("<title>")? + "<title>".len();
+    let end = body[start..].find("</title>")? + start;
+    Some(body[start..end].trim().to_string()).filter(|t| !t.is_empty())
+}
+
+fn or_unknown(content_type: &str) -> &str {
+    if content_type.is_empty() { "без Content-Type" } else { content_type }
+}
+
+fn snippet(body: &str) -> String {
+    body.chars().take(120).collect()
+}
+
+/// Что нет в скрипте версии version по сравнению с текущей.
+pub fn check_script_version(version: Option<u32>) -> DiagnosticCheck {
+    let Some(version) = version else {
+        return DiagnosticCheck::error(
+            "script_version",
+            "В ответе нет scriptVersion — задеплоен очень старый скрипт",
+            "Замени код скрипта на код из docs/google-sheets-integration.md и сделай Deploy → Manage deployments → Edit → New version",
+        );
+    };
+    if version >= SCRIPT_VERSION {
+        return DiagnosticCheck::ok("script_version", format!("Версия скрипта {}", version));
+    }
+    let missing: Vec<&str> = [
+        (5, "обновление строк"),
+        (7, "подпись запросов"),
+        (BATCH_SCRIPT_VERSION, "пакетная запись"),
+        (DRY_RUN_SCRIPT_VERSION, "проверка без записи"),
+        (UPSERT_SCRIPT_VERSION, "повтор записи без дублей"),
+    ]
+    .into_iter()
+    .filter(|(since, _)| version < *since)
+    .map(|(_, feature)| feature)
+    .collect();
+    DiagnosticCheck::warning(
+        "script_version",
+        format!("Версия скрипта {} из {}; нет: {}", version, SCRIPT_VERSION, missing.join(", ")),
+        "Обнови код скрипта и сделай Deploy → Manage deployments → Edit → New version (URL не меняется)",
+    )
+}
+
+fn is_unauthorized(json: &Value) -> bool {
+    json["error"].as_str().is_some_and(|e| e.starts_with("unauthorized"))
+}
+
+fn json_error(json: &Value) -> String {
+    json["error"].as_str().unwrap_or("ok=false без текста ошибки").to_string()
+}
+
+/// Прогоняет чек-лист для веб-хука: URL, доступ и редиректы, Content-Type,
+/// JSON, версию скрипта, ?list=1, подпись и (со скриптом версии 9) заголовки
+/// и право записи через ?test=1&dry_run=1 — без записи в таблицу.
+/// extra_columns — заголовки дополнительных колонок из настроек.
+pub async fn diagnose(webhook_url: &str, secret: Option<&str>, extra_columns: &[String]) -> WebhookDiagnosis {
+    let mut diagnosis = WebhookDiagnosis::default();
+    let webhook_url = webhook_url.trim();
+    let secret = secret.map(str::trim).filter(|s| !s.is_empty());
+
+    if diagnosis.push(check_url(webhook_url)) == CheckStatus::Error {
+        diagnosis.skip_rest(&["access", "response", "script_version", "list", "signature", "headers", "write_access"]);
+        return finish(diagnosis);
+    }
+    let client = match reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(20))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            diagnosis.push(DiagnosticCheck::error("access", format!("HTTP-клиент: {}", e), "Перезапусти приложение"));
+            return finish(diagnosis);
+        }
+    };
+
+    // 1. GET без параметров: доступ, формат ответа, версия
+    let alive = match fetch(&client, webhook_url).await {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            diagnosis.push(DiagnosticCheck::error("access", e, "Проверь интернет и URL веб-хука"));
+            diagnosis.skip_rest(&["response", "script_version", "list", "signature", "headers", "write_access"]);
+            return finish(diagnosis);
+        }
+    };
+    let access = diagnosis.push(check_access(&alive));
+    let json = if access == CheckStatus::Error { None } else { check_response(&alive, &mut diagnosis) };
+    let Some(json) = json else {
+        diagnosis.skip_rest(&["script_version", "list", "signature", "headers", "write_access"]);
+        return finish(diagnosis);
+    };
+    diagnosis.script_version = json["scriptVersion"].as_u64().map(|v| v as u32);
+    diagnosis.push(check_script_version(diagnosis.script_version));
+
+    // 2. ?list=1 с подписью (если задан секрет)
+    let list_url = with_param(webhook_url, "list=1");
+    let list = fetch(&client, &sheets::signed_url(&list_url, secret, "list")).await;
+    let list_json = list.as_ref().ok().and_then(|f| serde_json::from_str::<Value>(&f.body).ok());
+    let list_ok = match (&list, &list_json) {
+        (Err(e), _) => diagnosis.push(DiagnosticCheck::error("list", e.clone(), "Повтори проверку позже")),
+        (Ok(f), None) => diagnosis.push(DiagnosticCheck::error(
+            "list",
+            format!("?list=1 вернул не JSON: {}", snippet(&f.body)),
+            "Проверь Executions в редакторе скрипта",
+        )),
+        (_, Some(json)) if is_unauthorized(json) => {
+            diagnosis.push(DiagnosticCheck::skipped("list", "Не проверялось: запрос отклонён подписью"))
+        }
+        (_, Some(json)) if json["ok"] != true => diagnosis.push(DiagnosticCheck::error(
+            "list",
+            json_error(json),
+            "Проверь, что в одной из вкладок в первых 5 строках есть заголовки Username, Offer ID, Listed Date и Status",
+        )),
+        (_, Some(json)) => match json["rows"].as_array() {
+            Some(rows) => diagnosis.push(DiagnosticCheck::ok("list", format!("?list=1 вернул строк: {}", rows.len()))),
+            None => diagnosis.push(DiagnosticCheck::error(
+                "list",
+                "?list=1 ответил без rows — задеплоена старая версия скрипта",
+                "Обнови код скрипта и сделай Deploy → New version",
+            )),
+        },
+    };
+
+    // 3. Подпись: секрет приложения против свойства WEBHOOK_SECRET скрипта
+    let signature = match (secret, list_json.as_ref().is_some_and(is_unauthorized)) {
+        (Some(_), true) => DiagnosticCheck::error(
+            "signature",
+            format!("Подписанный запрос отклонён: {}", list_json.as_ref().map(json_error).unwrap_or_default()),
+            "Секрет в настройках и свойство WEBHOOK_SECRET скрипта должны совпадать; проверь и часы на компьютере",
+        ),
+        (None, true) => DiagnosticCheck::error(
+            "signature",
+            "Скрипт требует подпись, а секрет в приложении не задан",
+            "Вставь в настройки тот же секрет, что в свойстве WEBHOOK_SECRET скрипта",
+        ),
+        (None, false) => DiagnosticCheck::skipped("signature", "Секрет не задан — запросы без подписи"),
+        (Some(_), false) => match fetch(&client, &list_url).await {
+            Ok(f) if serde_json::from_str::<Value>(&f.body).is_ok_and(|j| is_unauthorized(&j)) => {
+                DiagnosticCheck::ok("signature", "Подпись принята, запрос без подписи отклонён")
+            }
+            Ok(_) => DiagnosticCheck::warning(
+                "signature",
+                "Скрипт принимает и запросы без подписи",
+                "Добавь в Project Settings → Script Properties свойство WEBHOOK_SECRET с тем же секретом (нужна версия скрипта 7+)",
+            ),
+            Err(e) => DiagnosticCheck::warning("signature", format!("Запрос без подписи не прошёл: {}", e), "Повтори проверку"),
+        },
+    };
+    if diagnosis.push(signature) == CheckStatus::Error {
+        for id in ["headers", "write_access"] {
+            diagnosis.push(DiagnosticCheck::skipped(id, "Не проверялось: запрос отклонён подписью"));
+        }
+        return finish(diagnosis);
+    }
+
+    // 4. Заголовки и право записи: пробный прогон без записи
+    if diagnosis.script_version.unwrap_or(0) < DRY_RUN_SCRIPT_VERSION {
+        let headers = if list_ok == CheckStatus::Ok {
+            DiagnosticCheck::ok("headers", "Вкладка с колонками Username, Offer ID, Listed Date, Status найдена")
+        } else {
+            DiagnosticCheck::skipped("headers", "Не проверялось: ?list=1 не прошёл")
+        };
+        diagnosis.push(headers);
+        diagnosis.push(DiagnosticCheck::skipped(
+            "write_access",
+            format!("Проверка без записи появилась в версии скрипта {}", DRY_RUN_SCRIPT_VERSION),
+        ));
+        return finish(diagnosis);
+    }
+    let dry_run_url = sheets::signed_url(&with_param(webhook_url, "test=1&dry_run=1"), secret, "test");
+    match fetch(&client, &dry_run_url).await.map(|f| serde_json::from_str::<DryRunResponse>(&f.body)) {
+        Ok(Ok(dry_run)) => {
+            for check in check_dry_run(&dry_run, extra_columns) {
+                diagnosis.push(check);
+            }
+        }
+        Ok(Err(e)) => {
+            diagnosis.push(DiagnosticCheck::error("headers", format!("Пробный прогон: {}", e), "Проверь Executions в редакторе скрипта"));
+            diagnosis.skip_rest(&["write_access"]);
+        }
+        Err(e) => {
+            diagnosis.push(DiagnosticCheck::error("headers", e, "Повтори проверку позже"));
+            diagnosis.skip_rest(&["write_access"]);
+        }
+    }
+    finish(diagnosis)
+}
+
+/// Диагностика веб-хука webhook_url с секретом secret (например, ещё не
+/// сохранённых в форме настроек); без webhook_url — веб-хука из настроек.
+/// Дополнительные колонки всегда берутся из настроек.
+pub async fn diagnose_configured(webhook_url: Option<&str>, secret: Option<&str>) -> Result<WebhookDiagnosis, String> {
+    let settings = config::load_sheets_settings();
+    let extra: Vec<String> = settings.iter().flat_map(|s| s.columns.iter().map(|c| c.header.clone())).collect();
+    if let Some(url) = webhook_url.filter(|u| !u.trim().is_empty()) {
+        return Ok(diagnose(url, secret, &extra).await);
+    }
+    let settings = settings.ok_or_else(|| "Google Sheets webhook не настроен".to_string())?;
+    if settings.backend != config::SHEETS_BACKEND_WEBHOOK {
+        return Err("Диагностика нужна только для веб-хука Apps Script".to_string());
+    }
+    Ok(diagnose(&settings.webhook_url, settings.webhook_secret.as_deref(), &extra).await)
+}
+
+fn finish(mut diagnosis: WebhookDiagnosis) -> WebhookDiagnosis {
+    diagnosis.ok = diagnosis.checks.iter().all(|c| c.status != CheckStatus::Error);
+    let problems: Vec<&str> = diagnosis
+        .checks
+        .iter()
+        .filter(|c| matches!(c.status, CheckStatus::Error | CheckStatus::Warning))
+        .map(|c| c.id.as_str())
+        .collect();
+    if diagnosis.ok {
+        info!("Sheet webhook diagnosis: ok, warnings: {:?}", problems);
+    } else {
+        warn!("Sheet webhook diagnosis: problems in {:?}", problems);
+    }
+    diagnosis
+}
+
+/// Ответ скрипта на ?test=1&dry_run=1.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunResponse {
+    pub ok: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// false — старый скрипт: пропустил dry_run и записал тестовую строку.
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub sheet: Option<String>,
+    #[serde(default)]
+    pub header_row: Option<u32>,
+    #[serde(default)]
+    pub headers: Vec<String>,
+    /// Необязательные колонки скрипта (Folder, Price, Sold Date), которых нет.
+    #[serde(default)]
+    pub missing_columns: Vec<String>,
+    #[serde(default)]
+    pub can_edit: bool,
+    #[serde(default)]
+    pub protected_ranges: Vec<String>,
+}
+
+/// Проверки headers и write_access по ответу пробного прогона.
+pub fn check_dry_run(dry_run: &DryRunResponse, extra_columns: &[String]) -> Vec<DiagnosticCheck> {
+    if !dry_run.ok {
+        let error = dry_run.error.clone().unwrap_or_else(|| "ok=false без текста ошибки".to_string());
+        return vec![
+            DiagnosticCheck::error(
+                "headers",
+                error,
+                "Скрипт должен быть создан из самой таблицы (Расширения → Apps Script), а в одной из вкладок в первых 5 строках — заголовки Username, Offer ID, Listed Date, Status",
+            ),
+            DiagnosticCheck::skipped("write_access", "Не проверялось: вкладка не найдена"),
+        ];
+    }
+    if !dry_run.dry_run {
+        return vec![
+            DiagnosticCheck::warning(
+                "headers",
+                "Скрипт не знает режим dry_run и записал тестовую строку TEST",
+                "Удали строку TEST и обнови код скрипта",
+            ),
+            DiagnosticCheck::skipped("write_access", "Не проверялось"),
+        ];
+    }
+
+    let sheet = dry_run.sheet.as_deref().unwrap_or("?");
+    let row = dry_run.header_row.map(|r| r.to_string()).unwrap_or_else(|| "?".to_string());
+    let known: Vec<String> = dry_run.headers.iter().map(|h| h.trim().to_lowercase()).collect();
+    let missing_extra: Vec<&str> = extra_columns
+        .iter()
+        .map(|c| c.trim())
+        .filter(|c| !c.is_empty() && !known.contains(&c.to_lowercase()))
+        .collect();
+    let found = format!("Вкладка «{}», заголовки в строке {}", sheet, row);
+    let headers = if !missing_extra.is_empty() {
+        DiagnosticCheck::warning(
+            "headers",
+            format!("{}; нет колонок из настроек: {}", found, missing_extra.join(", ")),
+            "Добавь колонки в строку заголовков или поправь header в настройках — иначе значения не запишутся",
+        )
+    } else if !dry_run.missing_columns.is_empty() {
+        DiagnosticCheck::warning(
+            "headers",
+            format!("{}; нет необязательных колонок: {}", found, dry_run.missing_columns.join(", ")),
+            "Не ошибка: без Folder не пишется папка, без Price и Sold Date — цена и дата продажи при обновлении",
+        )
+    } else {
+        DiagnosticCheck::ok("headers", found)
+    };
+
+    let write_access = if dry_run.can_edit {
+        DiagnosticCheck::ok("write_access", format!("Скрипт может писать во вкладку «{}»", sheet))
+    } else {
+        DiagnosticCheck::error(
+            "write_access",
+            format!("Защищённые диапазоны: {}", dry_run.protected_ranges.join(", ")),
+            "Сними защиту (Данные → Защищённые листы и диапазоны) или добавь владельца скрипта в редакторы",
+        )
+    };
+    vec![headers, write_access]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose_checks() {
+        assert_eq!(check_url("https://script.google.com/macros/s/AKfy/exec").status, CheckStatus::Ok);
+        assert_eq!(check_url("https://script.google.com/macros/s/AKfy/dev").status, CheckStatus::Error);
+        assert_eq!(check_url("script.google.com/macros").status, CheckStatus::Error);
+        assert_eq!(check_url("http://127.0.0.1:8080/exec").status, CheckStatus::Warning);
+
+        assert_eq!(check_script_version(None).status, CheckStatus::Error);
+        assert_eq!(check_script_version(Some(SCRIPT_VERSION)).status, CheckStatus::Ok);
+        let old = check_script_version(Some(6));
+        assert_eq!(old.status, CheckStatus::Warning);
+        assert!(old.message.contains("подпись запросов") && !old.message.contains("обновление строк"), "{}", old.message);
+
+        let login = Fetched {
+            status: 200,
+            content_type: "text/html; charset=utf-8".to_string(),
+            body: "<html><title>Sign in</title><a href=\"https://accounts.google.com/ServiceLogin\">".to_string(),
+            redirects: Vec::new(),
+        };
+        assert_eq!(check_access(&login).status, CheckStatus::Error);
+        let mut diagnosis = WebhookDiagnosis::default();
+        assert!(check_response(&login, &mut diagnosis).is_none());
+        assert_eq!(diagnosis.checks[1].message, "Ответ text/html; charset=utf-8 «Sign in» вместо JSON");
+
+        let dry_run = DryRunResponse {
+            ok: true,
+            dry_run: true,
+            sheet: Some("Accounts".to_string()),
+            header_row: Some(2),
+            headers: vec!["Username".to_string(), "Offer ID".to_string(), "Price".to_string()],
+            can_edit: false,
+            protected_ranges: vec!["A1:D2".to_string()],
+            ..Default::default()
+        };
+        let checks = check_dry_run(&dry_run, &["price".to_string(), "Server".to_string()]);
+        assert_eq!(checks[0].status, CheckStatus::Warning);
+        assert!(checks[0].message.ends_with("нет колонок из настроек: Server"), "{}", checks[0].message);
+        assert_eq!((checks[1].status, checks[1].message.as_str()), (CheckStatus::Error, "Защищённые диапазоны: A1:D2"));
+    }
+}
@@ -0,0 +1,346 @@
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+use crate::config;
+use crate::sheet_sync::SheetSync;
+use crate::sheets::SheetRow;
+use crate::ProgressCallback;
+
+const OUTBOX_FILE_NAME: &str = "sheet_outbox.json";
+
+/// Пауза перед первым повтором; дальше она удваивается до MAX_RETRY_DELAY_SECS.
+pub const BASE_RETRY_DELAY_SECS: i64 = 60;
+pub const MAX_RETRY_DELAY_SECS: i64 = 6 * 60 * 60;
+
+/// Как часто фоновая задача проверяет, не подошло ли время повтора.
+pub const RETRY_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Сколько ждать после выставления следующую строку в ту же пачку.
+pub const BATCH_WINDOW_SECS: u64 = 10;
+/// Дольше этого пачка не копится, даже если строки продолжают приходить.
+pub const MAX_BATCH_WAIT_SECS: u64 = 120;
+
+// Сериализует чтение-изменение-запись файла очереди внутри процесса.
+static OUTBOX_LOCK: Mutex<()> = Mutex::new(());
+
+// Отправка очереди уже идёт (таймер и команда пользователя не должны
+// отправить одну строку дважды).
+static FLUSHING: AtomicBool = AtomicBool::new(false);
+
+// Фоновая задача (retry_loop) запущена и собирает строки в пачки.
+static BATCHING: AtomicBool = AtomicBool::new(false);
+static BATCH_READY: Notify = Notify::const_new();
+
+/// Строка таблицы, которую не удалось записать; хранится до успешной отправки.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    pub id: String,
+    pub row: SheetRow,
+    pub queued_at: DateTime<Local>,
+    /// Число неудачных попыток, включая первую запись при выставлении;
+    /// 0 — строка ждёт отправки пачкой и ещё не отправлялась.
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    pub next_attempt: DateTime<Local>,
+}
+
+/// Итог отправки очереди.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutboxFlushResult {
+    pub sent: usize,
+    pub failed: usize,
+    /// Сколько строк осталось в очереди.
+    pub remaining: usize,
+    pub last_error: Option<String>,
+}
+
+/// Пауза перед следующей попыткой после `attempts` неудачных.
+pub fn retry_delay(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(16);
+    Duration::seconds((BASE_RETRY_DELAY_SECS << exponent).min(MAX_RETRY_DELAY_SECS))
+}
+
+// Сбрасывает FLUSHING при любом выходе из flush.
+struct FlushGuard;
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        FLUSHING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Очередь строк для Google-таблицы, которые не удалось записать сразу
+/// (таймаут веб-хука, Apps Script недоступен). Хранится на диске и
+/// переживает перезапуск; строки повторяются с нарастающей паузой.
+#[derive(Debug, Clone)]
+pub struct SheetOutbox {
+    path: PathBuf,
+}
+
+impl SheetOutbox {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Очередь в директории конфигурации приложения.
+    pub fn open() -> Result<Self, String> {
+        Ok(Self::new(config::config_dir()?.join(OUTBOX_FILE_NAME)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Строки в очереди, в порядке постановки.
+    pub fn pending(&self) -> Result<Vec<OutboxEntry>, String> {
+        let _guard = OUTBOX_LOCK.lock().map_err(|_| "Outbox lock poisoned".to_string())?;
+        self.read()
+    }
+
+    /// Ставит строку в очередь после неудачной записи.
+    pub fn enqueue(&self, row: SheetRow, error: &str) -> Result<OutboxEntry, String> {
+        let entry = self.push(row, 1, Some(crate::redact::redact(error)))?;
+        info!("Sheet row for offer {} queued for retry", entry.row.offer_id);
+        Ok(entry)
+    }
+
+    /// Ставит строку в очередь до отправки пачкой (см. notify_batch):
+    /// отправляется при следующем flush без паузы.
+    pub fn enqueue_pending(&self, row: SheetRow) -> Result<OutboxEntry, String> {
+        let entry = self.push(row, 0, None)?;
+        debug!("Sheet row for offer {} waiting for batch", entry.row.offer_id);
+        Ok(entry)
+    }
+
+    fn push(&self, row: SheetRow, attempts: u32, last_error: Option<String>) -> Result<OutboxEntry, String> {
+        let now = Local::now();
+        let next_attempt = if attempts == 0 { now } else { now + retry_delay(attempts) };
+        let entry = OutboxEntry {
+            id: format!("{}-{:04x}", now.format("%Y%m%d%H%M%S%3f"), rand::random::<u16>()),
+            row,
+            queued_at: now,
+            attempts,
+            last_error,
+            next_attempt,
+        };
+
+        let _guard = OUTBOX_LOCK.lock().map_err(|_| "Outbox lock poisoned".to_string())?;
+        let mut entries = self.read()?;
+        entries.push(entry.clone());
+        self.write(&entries)?;
+        debug!("Sheet outbox: {} pending", entries.len());
+        Ok(entry)
+    }
+
+    /// Отправляет строки, у которых подошло время повтора (с force — все),
+    /// одной пачкой (append_rows). Строка, которую бэкенд не записал, и вся
+    /// пачка, если запрос не дошёл, откладываются с нарастающей паузой.
+    pub async fn flush(&self, sync: &SheetSync, force: bool) -> Result<OutboxFlushResult, String> {
+        if FLUSHING.swap(true, Ordering::SeqCst) {
+            return Err("Очередь таблицы уже отправляется".to_string());
+        }
+        let _flushing = FlushGuard;
+
+        let now = Local::now();
+        let mut due: Vec<OutboxEntry> = self
+            .pending()?
+            .into_iter()
+            .filter(|e| force || e.next_attempt <= now)
+            .collect();
+        // Строка, которая падает постоянно, уходит в конец и не держит остальные
+        due.sort_by_key(|e| e.next_attempt);
+
+        let mut sent = Vec::new();
+        let mut failed = Vec::new();
+        if !due.is_empty() {
+            let rows: Vec<SheetRow> = due.iter().map(|e| e.row.clone()).collect();
+            match sync.append_rows(&rows).await {
+                Ok(results) => {
+                    for (entry, result) in due.into_iter().zip(results) {
+                        match result.into_result() {
+                            Ok(()) => sent.push(entry.id),
+                            Err(e) => {
+                                warn!("Запись в таблицу (оффер {}) не удалась: {}", entry.row.offer_id, e);
+                                failed.push((entry.id, e));
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Пачка из {} строк не записана в таблицу: {}", rows.len(), e);
+                    failed = due.into_iter().map(|entry| (entry.id, e.clone())).collect();
+                }
+            }
+        }
+
+        let result = self.apply_flush(&sent, &failed)?;
+        if result.sent > 0 || result.failed > 0 {
+            info!(
+                "Sheet outbox: {} sent, {} failed, {} remaining",
+                result.sent, result.failed, result.remaining
+            );
+        }
+        Ok(result)
+    }
+
+    // Убирает отправленные строки и откладывает упавшие. Файл перечитывается:
+    // пока шла отправка, в очередь могли добавиться новые строки.
+    fn apply_flush(&self, sent: &[String], failed: &[(String, String)]) -> Result<OutboxFlushResult, String> {
+        let _guard = OUTBOX_LOCK.lock().map_err(|_| "Outbox lock poisoned".to_string())?;
+        let mut entries = self.read()?;
+        entries.retain(|e| !sent.contains(&e.id));
+
+        let mut result = OutboxFlushResult { sent: sent.len(), ..Default::default() };
+        for (id, error) in failed {
+            if let Some(entry) = entries.iter_mut().find(|e| &e.id == id) {
+                entry.attempts += 1;
+                entry.last_error = Some(crate::redact::redact(error));
+                entry.next_attempt = Local::now() + retry_delay(entry.attempts);
+                result.failed += 1;
+                result.last_error = entry.last_error.clone();
+            }
+        }
+        if result.sent > 0 || result.failed > 0 {
+            self.write(&entries)?;
+        }
+        result.remaining = entries.len();
+        Ok(result)
+    }
+
+    fn read(&self) -> Result<Vec<OutboxEntry>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&self.path).map_err(|e| format!("Failed to read sheet outbox: {}", e))?;
+        if content.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse sheet outbox {:?}: {}", self.path, e))
+    }
+
+    // Через временный файл и rename: сбой посреди записи не теряет очередь.
+    fn write(&self, entries: &[OutboxEntry]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create outbox directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("Failed to serialize sheet outbox: {}", e))?;
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, content).map_err(|e| format!("Failed to write sheet outbox: {}", e))?;
+        fs::rename(&tmp, &self.path).map_err(|e| format!("Failed to replace sheet outbox: {}", e))
+    }
+}
+
+/// Строки о выставлении копятся в очереди и уходят пачкой (см. retry_loop).
+/// Без фоновой задачи (CLI) — false, и строка пишется сразу.
+pub fn batching_enabled() -> bool {
+    BATCHING.load(Ordering::SeqCst)
+}
+
+/// Сообщает фоновой задаче, что в очереди новая строка для пачки.
+pub fn notify_batch() {
+    BATCH_READY.notify_one();
+}
+
+// Ждёт, пока строки перестанут приходить (BATCH_WINDOW_SECS тишины),
+// но не дольше MAX_BATCH_WAIT_SECS.
+async fn collect_batch() {
+    let window = std::time::Duration::from_secs(BATCH_WINDOW_SECS);
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(MAX_BATCH_WAIT_SECS);
+    while tokio::time::timeout_at(deadline.min(tokio::time::Instant::now() + window), BATCH_READY.notified())
+        .await
+        .is_ok()
+    {}
+}
+
+/// Фоновый повтор очереди: сразу при запуске (все строки, без учёта паузы),
+/// затем каждые RETRY_CHECK_INTERVAL_SECS — строки, у которых подошло время.
+/// Новые строки о выставлении (notify_batch) отправляются пачкой, когда
+/// выставление затихло. on_flush вызывается, когда что-то было отправлено
+/// или снова не удалось.
+pub async fn retry_loop(on_flush: ProgressCallback<OutboxFlushResult>) {
+    BATCHING.store(true, Ordering::SeqCst);
+    let mut force = true;
+    loop {
+        match retry_once(force).await {
+            Ok(Some(result)) if result.sent > 0 || result.failed > 0 => on_flush(result),
+            Ok(_) => {}
+            Err(e) => debug!("Sheet outbox retry skipped: {}", e),
+        }
+        force = false;
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(RETRY_CHECK_INTERVAL_SECS)) => {}
+            _ = BATCH_READY.notified() => collect_batch().await,
+        }
+    }
+}
+
+async fn retry_once(force: bool) -> Result<Option<OutboxFlushResult>, String> {
+    let outbox = SheetOutbox::open()?;
+    if outbox.pending()?.is_empty() {
+        return Ok(None);
+    }
+    let sync = SheetSync::from_settings()?;
+    outbox.flush(&sync, force).await.map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(offer_id: &str) -> SheetRow {
+        SheetRow {
+            username: "smurf1".to_string(),
+            offer_id: offer_id.to_string(),
+            listed_date: "2026-01-16 12:00:00".to_string(),
+            folder: "/accounts/EUW".to_string(),
+            status: "Active".to_string(),
+            columns: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_outbox_enqueue_and_apply_flush() {
+        assert_eq!(retry_delay(1), Duration::seconds(60));
+        assert_eq!(retry_delay(3), Duration::seconds(240));
+        assert_eq!(retry_delay(40), Duration::seconds(MAX_RETRY_DELAY_SECS));
+
+        let path = std::env::temp_dir().join(format!("g2g-app-outbox-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let outbox = SheetOutbox::new(&path);
+        assert!(outbox.pending().unwrap().is_empty());
+
+        let first = outbox.enqueue(row("G1700000001"), "timed out").unwrap();
+        let second = outbox.enqueue(row("G1700000002"), "Sheet webhook returned 500").unwrap();
+        assert_eq!(first.attempts, 1);
+        assert!(first.next_attempt > first.queued_at);
+        // Строка для пачки отправляется сразу и ошибкой не считается
+        let batched = outbox.enqueue_pending(row("G1700000003")).unwrap();
+        assert_eq!((batched.attempts, batched.last_error.as_deref()), (0, None));
+        assert_eq!(batched.next_attempt, batched.queued_at);
+
+        let failed = [
+            (second.id.clone(), "still down".to_string()),
+            (batched.id.clone(), "Sheet not found".to_string()),
+        ];
+        let result = outbox.apply_flush(std::slice::from_ref(&first.id), &failed).unwrap();
+        assert_eq!((result.sent, result.failed, result.remaining), (1, 2, 2));
+
+        let pending = outbox.pending().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].row.offer_id, "G1700000002");
+        assert_eq!(pending[0].attempts, 2);
+        assert_eq!(pending[0].last_error.as_deref(), Some("still down"));
+        assert!(pending[0].next_attempt > second.next_attempt);
+        assert_eq!(pending[1].attempts, 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
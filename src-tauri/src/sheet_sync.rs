@@ -0,0 +1,555 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::account_info::{self, AccountSummary};
+use crate::accounts::{self, AccountRef, AccountStore};
+use crate::config::{self, SheetColumn, SheetsSettings};
+use crate::sheet_backend::{self, SheetBackend, WebhookBackend};
+use crate::sheet_outbox::{self, SheetOutbox};
+use crate::sheets;
+
+/// Синхронизация выставленных аккаунтов с Google-таблицей через веб-хук
+/// Apps Script или Sheets API (см. docs/google-sheets-integration.md).
+#[derive(Debug, Clone)]
+pub struct SheetSync {
+    backend: Arc<dyn SheetBackend>,
+    columns: Vec<SheetColumn>,
+}
+
+/// Поля выставления, доступные для дополнительных колонок (SheetColumn::field).
+pub const SHEET_FIELDS: &[&str] = &[
+    "title",
+    "price",
+    "server",
+    "rank",
+    "category",
+    "champions_count",
+    "skins_count",
+    "account_name",
+    "login",
+    "level",
+    "purchase_cost",
+    "profile",
+];
+
+/// Данные выставления для дополнительных колонок. Незаполненные поля
+/// (например, цена при дозаписи уже выставленных) в строку не попадают.
+#[derive(Debug, Clone, Default)]
+pub struct OfferFields {
+    pub account_name: String,
+    pub title: Option<String>,
+    pub price: Option<f64>,
+    pub server: Option<String>,
+    pub rank: Option<String>,
+    pub category: Option<String>,
+    pub champions_count: Option<i32>,
+    pub skins_count: Option<i32>,
+    /// Сводка по тексту аккаунта: логин, уровень, стоимость покупки, профиль.
+    pub summary: AccountSummary,
+}
+
+impl OfferFields {
+    /// Только по данным папки аккаунта — без цены и заголовка.
+    pub fn from_summary(account_name: &str, summary: AccountSummary) -> Self {
+        Self {
+            account_name: account_name.to_string(),
+            server: summary.server.clone(),
+            rank: summary.rank.clone(),
+            champions_count: summary.champions_count.map(|n| n as i32),
+            skins_count: summary.skins_count.map(|n| n as i32),
+            summary,
+            ..Default::default()
+        }
+    }
+
+    /// Из запроса на выставление: поля запроса и сводка по тексту файла
+    /// аккаунта (логин, уровень, стоимость покупки, профиль).
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_listing(
+        account_name: &str,
+        title: &str,
+        price: f64,
+        server: &str,
+        rank: &str,
+        category: Option<&str>,
+        champions_count: i32,
+        skins_count: i32,
+        account_text: &str,
+    ) -> Self {
+        Self {
+            account_name: account_name.to_string(),
+            title: Some(title.to_string()),
+            price: Some(price),
+            server: Some(server.to_string()),
+            rank: Some(rank.to_string()),
+            category: category.map(str::to_string),
+            champions_count: Some(champions_count),
+            skins_count: Some(skins_count),
+            summary: account_info::summarize_text(account_text),
+        }
+    }
+
+    /// Значение поля из SHEET_FIELDS как текст ячейки.
+    pub fn value(&self, field: &str) -> Option<String> {
+        match field {
+            "title" => self.title.clone(),
+            "price" => self.price.map(|p| format!("{:.2}", p)),
+            "server" => self.server.clone(),
+            "rank" => self.rank.clone(),
+            "category" => self.category.clone(),
+            "champions_count" => self.champions_count.map(|n| n.to_string()),
+            "skins_count" => self.skins_count.map(|n| n.to_string()),
+            "account_name" => Some(self.account_name.clone()),
+            "login" => self.summary.login.clone(),
+            "level" => self.summary.level.map(|n| n.to_string()),
+            "purchase_cost" => self.summary.purchase_cost.clone(),
+            "profile" => self.summary.profile.clone(),
+            _ => None,
+        }
+        .filter(|value| !value.is_empty())
+    }
+
+    /// Username строки: Login из текста аккаунта, иначе имя папки.
+    pub fn username(&self) -> String {
+        self.summary.login.clone().unwrap_or_else(|| self.account_name.clone())
+    }
+}
+
+// Строка таблицы о выставленном аккаунте; Folder — полный путь родительской
+// папки (где лежит папка аккаунта), дополнительные колонки — по columns.
+fn listed_row(
+    columns: &[SheetColumn],
+    username: String,
+    fields: &OfferFields,
+    offer_id: &str,
+    account_path: &str,
+) -> sheets::SheetRow {
+    sheets::SheetRow {
+        username,
+        offer_id: offer_id.to_string(),
+        listed_date: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        folder: PathBuf::from(account_path)
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        status: "Active".to_string(),
+        columns: columns
+            .iter()
+            .filter_map(|c| fields.value(&c.field).map(|value| (c.header.trim().to_string(), value)))
+            .collect(),
+    }
+}
+
+impl SheetSync {
+    pub fn new(backend: Arc<dyn SheetBackend>) -> Self {
+        Self { backend, columns: Vec::new() }
+    }
+
+    /// Дополнительные колонки, которые заполняются при записи оффера.
+    pub fn with_columns(mut self, columns: Vec<SheetColumn>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Через веб-хук Apps Script.
+    pub fn webhook(url: impl Into<String>) -> Self {
+        Self::new(Arc::new(WebhookBackend::new(url)))
+    }
+
+    /// Бэкенд из настроек; ошибка, если таблица не настроена.
+    pub fn from_settings() -> Result<Self, String> {
+        let settings = config::load_sheets_settings().ok_or_else(|| "Google Sheets webhook не настроен".to_string())?;
+        Self::from_sheets_settings(&settings)
+    }
+
+    pub fn from_sheets_settings(settings: &SheetsSettings) -> Result<Self, String> {
+        Ok(Self::new(sheet_backend::from_settings(settings)?).with_columns(settings.columns.clone()))
+    }
+
+    pub fn backend(&self) -> &dyn SheetBackend {
+        self.backend.as_ref()
+    }
+
+    /// Строка о выставленном оффере с дополнительными колонками.
+    pub fn offer_row(&self, fields: &OfferFields, account_path: &str, offer_id: &str) -> sheets::SheetRow {
+        listed_row(&self.columns, fields.username(), fields, offer_id, account_path)
+    }
+
+    /// Строка для уже выставленного аккаунта: поля берутся из его папки.
+    pub fn account_row(&self, account: &AccountRef, username: String, offer_id: &str) -> sheets::SheetRow {
+        let summary = account_info::summarize_folder(std::path::Path::new(&account.path));
+        let fields = OfferFields::from_summary(&account.name, summary);
+        listed_row(&self.columns, username, &fields, offer_id, &account.path)
+    }
+
+    pub async fn append_row(&self, row: &sheets::SheetRow) -> Result<(), String> {
+        self.backend.append_row(row).await
+    }
+
+    /// Дописывает несколько строк пачкой; результат — по строке на каждую.
+    pub async fn append_rows(&self, rows: &[sheets::SheetRow]) -> Result<Vec<sheets::SheetAppendResult>, String> {
+        self.backend.append_rows(rows).await
+    }
+
+    /// Дописывает строку о выставленном оффере (см. offer_row).
+    pub async fn record_offer(&self, fields: &OfferFields, account_path: &str, offer_id: &str) -> Result<(), String> {
+        self.append_row(&self.offer_row(fields, account_path, offer_id)).await
+    }
+
+    /// Меняет статус, цену, дату продажи или другие колонки существующей
+    /// строки (по offer_id или username); found = false — строки нет.
+    pub async fn update_row(&self, update: &sheets::SheetUpdate) -> Result<sheets::SheetUpdateResult, String> {
+        self.backend.update_row(update).await
+    }
+
+    /// Проверяет, какие из аккаунтов уже есть в таблице: по offer_id из файла
+    /// `G17*.txt`, иначе по логину. С fuzzy логин сравнивается и без пробелов,
+    /// и с заменой похожих символов (кириллица вместо латиницы, 0/O, 1/l).
+    pub async fn check(&self, accounts: &[AccountRef], fuzzy: bool) -> Result<Vec<SheetCheckResult>, String> {
+        let rows = self.backend.fetch_rows().await?;
+
+        let results = accounts
+            .iter()
+            .map(|account| {
+                let username = accounts::read_account_login(account);
+                let local_offer_id = accounts::find_offer_id(&account.path);
+                let found = match_rows(&username, local_offer_id.as_deref(), &rows, fuzzy);
+                let best = found.best.map(|i| &rows[i]);
+                SheetCheckResult {
+                    path: account.path.clone(),
+                    username,
+                    in_sheet: best.is_some(),
+                    sheet_status: best.and_then(|r| r.status.clone()),
+                    offer_id: best.and_then(|r| r.offer_id.clone()),
+                    sheet_username: best.map(|r| r.username.clone()),
+                    match_confidence: found.confidence,
+                    duplicate: found.rows.len() > 1,
+                    sheet_rows: found.rows.iter().map(|&i| rows[i].clone()).collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let found_count = results.iter().filter(|r| r.in_sheet).count();
+        let duplicates = results.iter().filter(|r| r.duplicate).count();
+        info!(
+            "Sheet check: {} of {} accounts found in sheet, {} with duplicate rows",
+            found_count,
+            results.len(),
+            duplicates
+        );
+
+        Ok(results)
+    }
+
+    /// Дописывает в таблицу выставленные аккаунты (есть файл `G17*.txt`),
+    /// которых там ещё нет. С dry_run только показывает, что было бы добавлено.
+    pub async fn sync_listed(&self, store: &AccountStore, dry_run: bool) -> Result<Vec<SheetSyncResult>, String> {
+        let listed = store.listed()?;
+        let refs: Vec<AccountRef> = listed.iter().map(|(a, _)| a.clone()).collect();
+        let checks = self.check(&refs, false).await?;
+
+        let mut results = Vec::with_capacity(listed.len());
+        // Недостающие строки уходят одной пачкой: (индекс в results, строка)
+        let mut missing = Vec::new();
+        for ((account, offer_id), check) in listed.iter().zip(checks) {
+            let mut result = SheetSyncResult {
+                path: account.path.clone(),
+                username: check.username.clone(),
+                offer_id: offer_id.clone(),
+                action: "already_in_sheet".to_string(),
+                error: None,
+            };
+            if !check.in_sheet {
+                if dry_run {
+                    result.action = "would_add".to_string();
+                } else {
+                    missing.push((results.len(), self.account_row(account, check.username, offer_id)));
+                }
+            }
+            results.push(result);
+        }
+
+        if !missing.is_empty() {
+            let rows: Vec<sheets::SheetRow> = missing.iter().map(|(_, row)| row.clone()).collect();
+            let appended = match self.append_rows(&rows).await {
+                Ok(appended) => appended.into_iter().map(|r| r.into_result()).collect(),
+                Err(e) => vec![Err(e); rows.len()],
+            };
+            for ((index, _), outcome) in missing.iter().zip(appended) {
+                let result = &mut results[*index];
+                match outcome {
+                    Ok(()) => result.action = "added".to_string(),
+                    Err(e) => {
+                        warn!("Не удалось записать {} в таблицу: {}", result.username, e);
+                        result.action = "failed".to_string();
+                        result.error = Some(e);
+                    }
+                }
+            }
+        }
+
+        let added = results.iter().filter(|r| r.action == "added").count();
+        info!("Sheet sync: {} listed account(s), {} added", results.len(), added);
+        Ok(results)
+    }
+}
+
+// Отправляет строку о выставленном оффере в Google-таблицу (best-effort).
+// Ошибка не прерывает выставление: строка уходит в очередь повторов
+// (sheet_outbox) и будет дописана позже. В приложении строка сразу ставится
+// в очередь, и выставленные подряд аккаунты записываются одной пачкой.
+pub async fn sync_offer_to_sheet(fields: &OfferFields, account_path: &str, offer_id: &str) {
+    let Some(settings) = config::load_sheets_settings() else {
+        info!("Google Sheets не настроена — пропускаем запись в таблицу");
+        return;
+    };
+
+    // Ошибка настроек (например, нет файла ключа) тоже ставит строку в очередь:
+    // её допишут, когда настройки исправят.
+    let row = listed_row(&settings.columns, fields.username(), fields, offer_id, account_path);
+    if sheet_outbox::batching_enabled() {
+        match SheetOutbox::open().and_then(|outbox| outbox.enqueue_pending(row.clone())) {
+            Ok(_) => {
+                info!("Listing queued for batch write to Google Sheet");
+                sheet_outbox::notify_batch();
+                return;
+            }
+            Err(e) => warn!("Не удалось поставить строку в очередь таблицы, пишем сразу: {}", e),
+        }
+    }
+    let result = match SheetSync::from_sheets_settings(&settings) {
+        Ok(sync) => sync.append_row(&row).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => info!("Listing written to Google Sheet"),
+        Err(e) => {
+            warn!("Не удалось записать в Google-таблицу, строка поставлена в очередь: {}", e);
+            if let Err(queue_error) = SheetOutbox::open().and_then(|outbox| outbox.enqueue(row, &e)) {
+                warn!("Не удалось сохранить строку в очередь таблицы: {}", queue_error);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SheetCheckResult {
+    pub path: String,
+    pub username: String,
+    pub in_sheet: bool,
+    pub sheet_status: Option<String>,
+    pub offer_id: Option<String>,
+    /// Логин так, как он записан в найденной строке таблицы.
+    #[serde(default)]
+    pub sheet_username: Option<String>,
+    #[serde(default)]
+    pub match_confidence: Option<MatchConfidence>,
+    /// Аккаунту соответствует больше одной строки таблицы.
+    #[serde(default)]
+    pub duplicate: bool,
+    /// Все подходящие строки в порядке таблицы; статус и offer_id берутся из
+    /// строки с тем же offer_id, иначе из последней с этим логином.
+    #[serde(default)]
+    pub sheet_rows: Vec<sheets::SheetEntry>,
+}
+
+/// Как строка таблицы сопоставлена с аккаунтом (от самого надёжного).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchConfidence {
+    /// Offer ID в таблице совпал с файлом `G17*.txt`.
+    OfferId,
+    /// Логин совпал без учёта регистра и пробелов по краям.
+    Exact,
+    /// Логин совпал без учёта всех пробелов и невидимых символов.
+    Normalized,
+    /// Логин совпал после замены похожих символов.
+    Confusable,
+}
+
+/// Строки таблицы, подходящие к одному аккаунту (индексы в rows).
+#[derive(Debug, Default, PartialEq)]
+pub struct SheetMatch {
+    pub rows: Vec<usize>,
+    pub best: Option<usize>,
+    pub confidence: Option<MatchConfidence>,
+}
+
+/// Сопоставляет аккаунт со строками таблицы. Строки с тем же offer_id и все
+/// строки с этим логином попадают в rows; логин сравнивается на самом строгом
+/// уровне, где нашлось совпадение (Normalized и Confusable — только с fuzzy).
+pub fn match_rows(username: &str, offer_id: Option<&str>, rows: &[sheets::SheetEntry], fuzzy: bool) -> SheetMatch {
+    let offer_id = offer_id.map(str::trim).filter(|id| !id.is_empty());
+    let by_offer: Vec<usize> = (0..rows.len())
+        .filter(|&i| offer_id.is_some() && rows[i].offer_id.as_deref().map(str::trim) == offer_id)
+        .collect();
+
+    let mut levels = vec![(MatchConfidence::Exact, exact_key as fn(&str) -> String)];
+    if fuzzy {
+        levels.push((MatchConfidence::Normalized, normalized_key));
+        levels.push((MatchConfidence::Confusable, confusable_key));
+    }
+    let (by_username, username_confidence) = levels
+        .into_iter()
+        .filter_map(|(confidence, key)| {
+            let want = key(username);
+            let found: Vec<usize> = (0..rows.len())
+                .filter(|&i| !want.is_empty() && key(&rows[i].username) == want)
+                .collect();
+            (!found.is_empty()).then_some((found, confidence))
+        })
+        .next()
+        .unzip();
+
+    let by_username = by_username.unwrap_or_default();
+    let (best, confidence) = match (by_offer.last(), by_username.last()) {
+        (Some(&i), _) => (Some(i), Some(MatchConfidence::OfferId)),
+        (None, Some(&i)) => (Some(i), username_confidence),
+        (None, None) => (None, None),
+    };
+    let mut matched = by_offer;
+    matched.extend(by_username);
+    matched.sort_unstable();
+    matched.dedup();
+    SheetMatch { rows: matched, best, confidence }
+}
+
+fn exact_key(username: &str) -> String {
+    username.trim().to_lowercase()
+}
+
+// Без пробелов и невидимых символов (неразрывный пробел, zero-width, BOM),
+// которые попадают в таблицу при копировании логина.
+fn normalized_key(username: &str) -> String {
+    username
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// Похожие символы сводятся к одному латинскому до смены регистра:
+// заглавная I похожа на строчную l, а после to_lowercase это уже i.
+fn confusable_key(username: &str) -> String {
+    let folded: String = username.chars().map(fold_confusable).collect();
+    normalized_key(&folded)
+}
+
+fn fold_confusable(c: char) -> char {
+    match c {
+        'А' | 'а' | 'Α' | 'α' => 'a',
+        'В' | 'Β' => 'b',
+        'Е' | 'е' | 'Ё' | 'ё' | 'Ε' => 'e',
+        'К' | 'к' | 'Κ' => 'k',
+        'М' | 'Μ' => 'm',
+        'Н' | 'Η' => 'h',
+        'О' | 'о' | 'Ο' | 'ο' | '0' => 'o',
+        'Р' | 'р' | 'Ρ' | 'ρ' => 'p',
+        'С' | 'с' => 'c',
+        'Т' | 'Τ' => 't',
+        'У' | 'у' | 'Υ' => 'y',
+        'Х' | 'х' | 'Χ' | 'χ' => 'x',
+        'І' | 'і' | 'Ι' => 'i',
+        'Ј' | 'ј' => 'j',
+        'Ѕ' | 'ѕ' => 's',
+        'Ζ' => 'z',
+        'Ν' => 'n',
+        'I' | '1' | '|' => 'l',
+        _ => c,
+    }
+}
+
+/// Результат синхронизации одного выставленного аккаунта с таблицей.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SheetSyncResult {
+    pub path: String,
+    pub username: String,
+    pub offer_id: String,
+    /// "already_in_sheet", "added", "would_add" (dry run) или "failed".
+    pub action: String,
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn entry(username: &str, offer_id: &str) -> sheets::SheetEntry {
+        sheets::SheetEntry {
+            username: username.to_string(),
+            status: Some("Active".to_string()),
+            offer_id: (!offer_id.is_empty()).then(|| offer_id.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_match_rows() {
+        let rows = [
+            entry("Smurf1", "G1700000001"),
+            entry(" smurf1 ", "G1700000002"),
+            entry("other", "G1700000003"),
+            entry("s m\u{200B}urf2", ""),
+            entry("ѕmurfОne", ""),
+        ];
+
+        // Оба повтора логина видны; offer_id из файла выбирает строку
+        let found = match_rows("SMURF1", Some("G1700000001"), &rows, false);
+        assert_eq!(found, SheetMatch { rows: vec![0, 1], best: Some(0), confidence: Some(MatchConfidence::OfferId) });
+        // Без offer_id — последняя строка с логином
+        let found = match_rows("smurf1", None, &rows, false);
+        assert_eq!((found.best, found.confidence), (Some(1), Some(MatchConfidence::Exact)));
+        // Аккаунт перевыставлен под другим логином в таблице — находится по offer_id
+        let found = match_rows("renamed", Some("G1700000003"), &rows, false);
+        assert_eq!((found.rows, found.best), (vec![2], Some(2)));
+
+        assert_eq!(match_rows("smurf2", None, &rows, false).best, None);
+        let found = match_rows("smurf2", None, &rows, true);
+        assert_eq!((found.best, found.confidence), (Some(3), Some(MatchConfidence::Normalized)));
+        let found = match_rows("smurfOne", None, &rows, true);
+        assert_eq!((found.best, found.confidence), (Some(4), Some(MatchConfidence::Confusable)));
+        assert_eq!(confusable_key("Iv0"), confusable_key("lvO"));
+
+        assert_eq!(match_rows("", None, &rows, true), SheetMatch::default());
+    }
+
+    #[test]
+    fn test_listed_row_columns() {
+        let text = "Login: smurf_login\nLevel - 31\nPurchase price: 12.50 USD\n";
+        let fields = OfferFields::from_listing("smurf", "EUW Gold", 25.0, "EUW", "Gold", None, 40, 0, text);
+        assert_eq!(fields.value("price").as_deref(), Some("25.00"));
+        assert_eq!(fields.value("login").as_deref(), Some("smurf_login"));
+        assert_eq!(fields.value("level").as_deref(), Some("31"));
+        assert_eq!(fields.value("purchase_cost").as_deref(), Some("12.50 USD"));
+        assert_eq!(fields.value("skins_count").as_deref(), Some("0"));
+        // Пустые и незаполненные поля в строку не попадают
+        assert_eq!(fields.value("category"), None);
+        assert_eq!(fields.value("profile"), None);
+        assert_eq!(fields.value("buyer"), None);
+        assert_eq!(fields.username(), "smurf_login");
+        // Каждое поле SHEET_FIELDS разбирается в value
+        let full = OfferFields::from_listing("smurf", "t", 1.0, "s", "r", Some("c"), 1, 1, "");
+        for field in ["title", "price", "server", "rank", "category", "champions_count", "skins_count", "account_name"] {
+            assert!(SHEET_FIELDS.contains(&field));
+            assert!(full.value(field).is_some(), "{}", field);
+        }
+
+        let columns = [
+            SheetColumn { field: "price".to_string(), header: " Price ".to_string() },
+            SheetColumn { field: "profile".to_string(), header: "Profile".to_string() },
+            SheetColumn { field: "purchase_cost".to_string(), header: "Purchase Cost".to_string() },
+        ];
+        let row = listed_row(&columns, fields.username(), &fields, "G1700000001", "/accounts/EUW/smurf");
+        assert_eq!((row.username.as_str(), row.offer_id.as_str()), ("smurf_login", "G1700000001"));
+        assert_eq!((row.folder.as_str(), row.status.as_str()), ("/accounts/EUW", "Active"));
+        assert_eq!(
+            row.columns,
+            HashMap::from([
+                ("Price".to_string(), "25.00".to_string()),
+                ("Purchase Cost".to_string(), "12.50 USD".to_string()),
+            ])
+        );
+    }
+}
@@ -1,32 +1,238 @@
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
 
 /// Одна строка, отправляемая в Google-таблицу через веб-хук Apps Script.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SheetRow {
     pub username: String,
     pub offer_id: String,
     pub listed_date: String,
     pub folder: String,
     pub status: String,
+    /// Дополнительные колонки: заголовок → значение (SheetsSettings::columns).
+    /// Колонки, которых нет в таблице, пропускаются.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub columns: HashMap<String, String>,
 }
 
 /// Отправляет строку в Google-таблицу через веб-хук Google Apps Script.
 ///
 /// Намеренно best-effort: вызывающая сторона решает, что делать с ошибкой.
 /// Запись в таблицу не должна ломать сам процесс выставления оффера.
-pub async fn append_row(webhook_url: &str, row: &SheetRow) -> Result<(), String> {
-    println!("📊 Sending listing data to Google Sheet webhook...");
-    println!("   Username: {}", row.username);
-    println!("   Offer ID: {}", row.offer_id);
+pub async fn append_row(webhook_url: &str, secret: Option<&str>, row: &SheetRow) -> Result<(), String> {
+    info!("Sending listing data to Google Sheet webhook...");
+    debug!("Username: {}", row.username);
+    debug!("Offer ID: {}", row.offer_id);
 
-    let client = reqwest::Client::builder()
+    let body = post_json(webhook_url, secret, row).await?;
+    // Скрипт отвечает ok=false на неверную подпись и ошибки записи;
+    // ответ не в JSON (старый скрипт) считается успехом, как раньше.
+    if let Ok(response) = serde_json::from_str::<serde_json::Value>(&body) {
+        if response["ok"] == false {
+            let error = response["error"].as_str().unwrap_or("Webhook returned ok=false");
+            return Err(error.to_string());
+        }
+    }
+    info!("Sheet updated. Webhook response: {}", body);
+    Ok(())
+}
+
+/// Результат записи одной строки пачки (append_rows).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SheetAppendResult {
+    pub offer_id: String,
+    pub ok: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Номер строки в таблице (1-based), если бэкенд его сообщает.
+    #[serde(default)]
+    pub row: Option<u32>,
+}
+
+impl SheetAppendResult {
+    pub fn from_result(row: &SheetRow, result: Result<(), String>) -> Self {
+        Self { offer_id: row.offer_id.clone(), ok: result.is_ok(), error: result.err(), row: None }
+    }
+
+    pub fn into_result(self) -> Result<(), String> {
+        if self.ok {
+            Ok(())
+        } else {
+            Err(self.error.unwrap_or_else(|| "Sheet write failed".to_string()))
+        }
+    }
+}
+
+/// Версия скрипта из docs/google-sheets-integration.md.
+pub const SCRIPT_VERSION: u32 = 10;
+
+/// Версия скрипта, в которой запись строки ищет её по Offer ID, и повтор
+/// из очереди не создаёт дубль.
+pub const UPSERT_SCRIPT_VERSION: u32 = 10;
+
+/// Версия скрипта, в которой появился режим append_batch.
+pub const BATCH_SCRIPT_VERSION: u32 = 8;
+
+/// Сколько строк отправляется одним запросом: Apps Script ограничивает
+/// выполнение 6 минутами, а одна строка — это несколько вызовов setValue.
+pub const MAX_BATCH_ROWS: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct BatchResponse {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    results: Option<Vec<BatchRowResult>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchRowResult {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    row: Option<u32>,
+}
+
+/// Записывает несколько строк одним запросом на пачку (action "append_batch"):
+/// одно выполнение Apps Script вместо одного на строку. Ok — по результату на
+/// каждую строку; Err — не удалось узнать версию скрипта (ничего не записано).
+/// Скрипт старше BATCH_SCRIPT_VERSION получает строки по одной.
+pub async fn append_rows(webhook_url: &str, secret: Option<&str>, rows: &[SheetRow]) -> Result<Vec<SheetAppendResult>, String> {
+    if rows.is_empty() {
+        return Ok(Vec::new());
+    }
+    // Старый скрипт принял бы пачку за одну строку и записал пустую строку
+    let version = script_version(webhook_url).await?;
+    append_rows_for_version(webhook_url, secret, version, rows).await
+}
+
+/// append_rows с уже известной версией скрипта (WebhookBackend запрашивает
+/// её один раз). Если пачка не дошла или скрипт её отклонил, результаты
+/// прежних пачек сохраняются, а строки этой и следующих пачек получают ошибку.
+pub async fn append_rows_for_version(
+    webhook_url: &str,
+    secret: Option<&str>,
+    version: u32,
+    rows: &[SheetRow],
+) -> Result<Vec<SheetAppendResult>, String> {
+    if version < BATCH_SCRIPT_VERSION {
+        info!("Sheet script version {} has no batch mode, sending {} row(s) one by one", version, rows.len());
+        let mut results = Vec::with_capacity(rows.len());
+        for row in rows {
+            results.push(SheetAppendResult::from_result(row, append_row(webhook_url, secret, row).await));
+        }
+        return Ok(results);
+    }
+
+    let mut results = Vec::with_capacity(rows.len());
+    for chunk in rows.chunks(MAX_BATCH_ROWS) {
+        info!("Sending {} row(s) to Google Sheet webhook in one batch...", chunk.len());
+        match append_chunk(webhook_url, secret, chunk).await {
+            Ok(chunk_results) => results.extend(chunk_results),
+            Err(e) => {
+                warn!("Sheet batch failed after {} row(s): {}", results.len(), e);
+                let rest = &rows[results.len()..];
+                results.extend(rest.iter().map(|row| SheetAppendResult::from_result(row, Err(e.clone()))));
+                break;
+            }
+        }
+    }
+    let failed = results.iter().filter(|r| !r.ok).count();
+    info!("Sheet batch: {} row(s) written, {} failed", results.len() - failed, failed);
+    Ok(results)
+}
+
+// Одна пачка; Err — запрос не дошёл или скрипт отклонил пачку целиком.
+async fn append_chunk(webhook_url: &str, secret: Option<&str>, chunk: &[SheetRow]) -> Result<Vec<SheetAppendResult>, String> {
+    #[derive(Serialize)]
+    struct Payload<'a> {
+        action: &'static str,
+        rows: &'a [SheetRow],
+    }
+    let body = post_json(webhook_url, secret, &Payload { action: "append_batch", rows: chunk }).await?;
+    let parsed: BatchResponse = serde_json::from_str(&body)
+        .map_err(|e| format!("Failed to parse webhook response: {} | Body: {}", e, body.chars().take(200).collect::<String>()))?;
+    if !parsed.ok {
+        return Err(parsed.error.unwrap_or_else(|| "Webhook returned ok=false".to_string()));
+    }
+    let chunk_results = parsed.results.filter(|r| r.len() == chunk.len()).ok_or_else(|| {
+        format!("Веб-хук вернул результаты не для всех {} строк пачки", chunk.len())
+    })?;
+    Ok(chunk
+        .iter()
+        .zip(chunk_results)
+        .map(|(row, result)| SheetAppendResult {
+            offer_id: row.offer_id.clone(),
+            ok: result.ok,
+            error: result.error.or_else(|| (!result.ok).then(|| "Webhook returned ok=false".to_string())),
+            row: result.row,
+        })
+        .collect())
+}
+
+/// scriptVersion из ответа веб-хука на GET без параметров (0 — поля нет).
+pub(crate) async fn script_version(webhook_url: &str) -> Result<u32, String> {
+    let response = http_client()?
+        .get(webhook_url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to request sheet webhook: {}", e))?;
+    let status = response.status();
+    let body = response.text().await.map_err(|e| format!("Failed to read response body: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("Sheet webhook returned {}: {}", status, body));
+    }
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+    Ok(json["scriptVersion"].as_u64().unwrap_or(0) as u32)
+}
+
+/// Подпись запроса к веб-хуку: hex HMAC-SHA256 от "{ts}\n{nonce}\n{body}".
+/// Для GET body — имя действия ("list"). Скрипт проверяет её и отклоняет
+/// устаревшие (старше 5 минут) и повторные (тот же nonce) запросы.
+pub fn webhook_signature(secret: &str, ts: i64, nonce: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}\n{}\n{}", ts, nonce, body).as_bytes());
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// URL с параметрами подписи; без секрета — как есть. Apps Script не отдаёт
+// скрипту заголовки запроса, поэтому подпись передаётся в query. Секрет
+// обрезается так же, как при проверке длины в настройках.
+pub(crate) fn signed_url(webhook_url: &str, secret: Option<&str>, body: &str) -> String {
+    let Some(secret) = secret.map(str::trim).filter(|s| !s.is_empty()) else {
+        return webhook_url.to_string();
+    };
+    let ts = chrono::Utc::now().timestamp();
+    let nonce: String = (0..16).map(|_| format!("{:02x}", rand::random::<u8>())).collect();
+    let separator = if webhook_url.contains('?') { "&" } else { "?" };
+    format!(
+        "{}{}ts={}&nonce={}&sig={}",
+        webhook_url,
+        separator,
+        ts,
+        nonce,
+        webhook_signature(secret, ts, &nonce, body)
+    )
+}
+
+pub(crate) fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(20))
         .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
 
-    let response = client
-        .post(webhook_url)
-        .json(row)
+// POST JSON на веб-хук (с подписью, если задан секрет); тело ответа при HTTP 2xx.
+async fn post_json<T: Serialize + ?Sized>(webhook_url: &str, secret: Option<&str>, payload: &T) -> Result<String, String> {
+    let body = serde_json::to_string(payload).map_err(|e| format!("Failed to serialize sheet payload: {}", e))?;
+    let response = http_client()?
+        .post(signed_url(webhook_url, secret, &body))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
         .send()
         .await
         .map_err(|e| format!("Failed to send request to sheet webhook: {}", e))?;
@@ -40,13 +246,106 @@ pub async fn append_row(webhook_url: &str, row: &SheetRow) -> Result<(), String>
     if !status.is_success() {
         return Err(format!("Sheet webhook returned {}: {}", status, body));
     }
+    Ok(body)
+}
 
-    println!("✅ Sheet updated. Webhook response: {}", body);
-    Ok(())
+/// Изменение существующей строки таблицы (POST с "action": "update").
+/// Строка ищется по offer_id, а если его нет — по username (последняя
+/// строка с этим логином). Незаданные поля не меняются.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SheetUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sold_date: Option<String>,
+    /// Любые другие колонки: заголовок → значение.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub columns: HashMap<String, String>,
+}
+
+impl SheetUpdate {
+    /// Строку нужно чем-то найти: offer_id или username.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.offer_id.is_none() && self.username.is_none() {
+            return Err("Для обновления строки нужен offer_id или username".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Результат update (веб-хук или Sheets API).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SheetUpdateResult {
+    /// Строка найдена (и обновлена).
+    pub found: bool,
+    #[serde(default)]
+    pub sheet: Option<String>,
+    #[serde(default)]
+    pub row: Option<u32>,
+    /// Заголовки из запроса, которых нет в таблице.
+    #[serde(default)]
+    pub missing_columns: Vec<String>,
 }
 
-/// Одна строка таблицы, возвращаемая веб-хуком в режиме ?list=1.
 #[derive(Debug, Deserialize)]
+struct UpdateResponse {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    // Option: скрипт без режима update отвечает на любой POST как на запись строки
+    found: Option<bool>,
+    #[serde(default)]
+    sheet: Option<String>,
+    #[serde(default)]
+    row: Option<u32>,
+    #[serde(default, rename = "missingColumns")]
+    missing_columns: Vec<String>,
+}
+
+/// Обновляет строку таблицы по offer_id или username. Ok(found = false),
+/// если такой строки нет — это не ошибка.
+pub async fn update_row(webhook_url: &str, secret: Option<&str>, update: &SheetUpdate) -> Result<SheetUpdateResult, String> {
+    update.validate()?;
+    info!("Updating Google Sheet row...");
+    debug!("Offer ID: {:?}, username: {:?}", update.offer_id, update.username);
+
+    #[derive(Serialize)]
+    struct Payload<'a> {
+        action: &'static str,
+        #[serde(flatten)]
+        update: &'a SheetUpdate,
+    }
+    let body = post_json(webhook_url, secret, &Payload { action: "update", update }).await?;
+
+    let parsed: UpdateResponse = serde_json::from_str(&body)
+        .map_err(|e| format!("Failed to parse webhook response: {} | Body: {}", e, body.chars().take(200).collect::<String>()))?;
+    if !parsed.ok {
+        return Err(parsed.error.unwrap_or_else(|| "Webhook returned ok=false".to_string()));
+    }
+    let found = parsed.found.ok_or_else(|| {
+        "Веб-хук не знает режим update — похоже, в Apps Script задеплоена старая \
+         версия скрипта. Обнови код скрипта и сделай Deploy → New version."
+            .to_string()
+    })?;
+
+    let result = SheetUpdateResult {
+        found,
+        sheet: parsed.sheet,
+        row: parsed.row,
+        missing_columns: parsed.missing_columns,
+    };
+    info!("Sheet row update: found = {}, row = {:?}", result.found, result.row);
+    Ok(result)
+}
+
+/// Одна строка таблицы (веб-хук отдаёт их в режиме ?list=1).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SheetEntry {
     pub username: String,
     #[serde(default)]
@@ -66,16 +365,13 @@ struct ListResponse {
 }
 
 /// Запрашивает у веб-хука все строки таблицы (GET ?list=1).
-pub async fn fetch_rows(webhook_url: &str) -> Result<Vec<SheetEntry>, String> {
-    println!("📊 Fetching existing rows from Google Sheet webhook...");
+pub async fn fetch_rows(webhook_url: &str, secret: Option<&str>) -> Result<Vec<SheetEntry>, String> {
+    info!("Fetching existing rows from Google Sheet webhook...");
 
     let separator = if webhook_url.contains('?') { "&" } else { "?" };
-    let url = format!("{}{}list=1", webhook_url, separator);
+    let url = signed_url(&format!("{}{}list=1", webhook_url, separator), secret, "list");
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(20))
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let client = http_client()?;
 
     // Apps Script отвечает редиректом на googleusercontent.com — reqwest
     // следует за ним по умолчанию.
@@ -96,7 +392,7 @@ pub async fn fetch_rows(webhook_url: &str) -> Result<Vec<SheetEntry>, String> {
     }
 
     let parsed: ListResponse = serde_json::from_str(&body)
-        .map_err(|e| format!("Failed to parse webhook response: {} | Body: {}", e, &body[..body.len().min(200)]))?;
+        .map_err(|e| format!("Failed to parse webhook response: {} | Body: {}", e, body.chars().take(200).collect::<String>()))?;
 
     if !parsed.ok {
         return Err(parsed.error.unwrap_or_else(|| "Webhook returned ok=false".to_string()));
@@ -108,6 +404,163 @@ pub async fn fetch_rows(webhook_url: &str) -> Result<Vec<SheetEntry>, String> {
             .to_string()
     })?;
 
-    println!("✅ Fetched {} rows from sheet", rows.len());
+    info!("Fetched {} rows from sheet", rows.len());
     Ok(rows)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    #[derive(Default)]
+    struct StandIn {
+        // Строки таблицы: (username, offer_id, status)
+        rows: Vec<(String, String, String)>,
+        version_requests: usize,
+    }
+
+    type Shared = Arc<StdMutex<StandIn>>;
+
+    fn stand_in(rows: &[(&str, &str)]) -> Shared {
+        let rows = rows.iter().map(|(u, o)| (u.to_string(), o.to_string(), "Active".to_string())).collect();
+        Arc::new(StdMutex::new(StandIn { rows, version_requests: 0 }))
+    }
+
+    fn route(method: &str, body: &str, state: &mut StandIn) -> (&'static str, String) {
+        if method == "GET" {
+            state.version_requests += 1;
+            return ("200 OK", json!({ "ok": true, "scriptVersion": SCRIPT_VERSION }).to_string());
+        }
+        let rows = &mut state.rows;
+        let payload: Value = serde_json::from_str(body).unwrap_or_default();
+        match payload["action"].as_str() {
+            // Пачка с G17-fail целиком отклоняется, как при таймауте скрипта
+            Some("append_batch") => {
+                let batch = payload["rows"].as_array().unwrap();
+                if batch.iter().any(|r| r["offer_id"] == "G17-fail") {
+                    return ("200 OK", json!({ "ok": false, "error": "Exceeded maximum execution time" }).to_string());
+                }
+                let results: Vec<Value> = batch
+                    .iter()
+                    .map(|r| {
+                        let field = |name: &str| r[name].as_str().unwrap_or_default().to_string();
+                        rows.push((field("username"), field("offer_id"), field("status")));
+                        json!({ "ok": true, "row": rows.len() + 1 })
+                    })
+                    .collect();
+                ("200 OK", json!({ "ok": true, "results": results }).to_string())
+            }
+            Some("update") if payload["offer_id"] != "G17-html" => {
+                let offer_id = payload["offer_id"].as_str().unwrap_or_default();
+                let found = rows.iter_mut().enumerate().find(|(_, r)| r.1 == offer_id);
+                let response = match found {
+                    Some((i, row)) => {
+                        if let Some(status) = payload["status"].as_str() {
+                            row.2 = status.to_string();
+                        }
+                        json!({ "ok": true, "found": true, "sheet": "Accounts", "row": i + 2, "missingColumns": [] })
+                    }
+                    None => json!({ "ok": true, "found": false }),
+                };
+                ("200 OK", response.to_string())
+            }
+            // HTML-страница ошибки Apps Script, не JSON и не ASCII
+            _ => ("200 OK", format!("<html>{}</html>", "Ошибка скрипта. ".repeat(20))),
+        }
+    }
+
+    // Локальная замена веб-хука Apps Script: разбирает POST и отвечает как скрипт.
+    async fn spawn_stand_in_webhook(state: Shared) -> String {
+        let base = crate::test_http::spawn(move |request| route(&request.method, &request.body, &mut state.lock().unwrap())).await;
+        format!("{}/exec", base)
+    }
+
+    #[tokio::test]
+    async fn test_update_row_against_stand_in() {
+        let state = stand_in(&[("alice", "G1700000001"), ("bob", "G1700000002")]);
+        let url = spawn_stand_in_webhook(state.clone()).await;
+
+        let update = SheetUpdate {
+            offer_id: Some("G1700000002".to_string()),
+            status: Some("Sold".to_string()),
+            ..Default::default()
+        };
+        let result = update_row(&url, None, &update).await.unwrap();
+        assert!(result.found);
+        assert_eq!(result.row, Some(3));
+        assert_eq!(state.lock().unwrap().rows[1].2, "Sold");
+
+        let missing = SheetUpdate { offer_id: Some("G17-missing".to_string()), ..Default::default() };
+        let result = update_row(&url, None, &missing).await.unwrap();
+        assert!(!result.found);
+        assert_eq!(state.lock().unwrap().rows[0].2, "Active");
+
+        // Не-JSON ответ с кириллицей обрезается по символам, а не по байтам
+        let html = SheetUpdate { offer_id: Some("G17-html".to_string()), ..Default::default() };
+        let error = update_row(&url, None, &html).await.unwrap_err();
+        assert!(error.contains("Body: <html>Ошибка скрипта"), "{}", error);
+
+        assert!(update_row(&url, None, &SheetUpdate::default()).await.is_err());
+    }
+
+    fn listed(username: &str, offer_id: &str) -> SheetRow {
+        SheetRow {
+            username: username.to_string(),
+            offer_id: offer_id.to_string(),
+            listed_date: "2026-01-16 12:00:00".to_string(),
+            folder: "/accounts".to_string(),
+            status: "Active".to_string(),
+            columns: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_rows_keeps_results_of_written_batches() {
+        use crate::sheet_backend::{SheetBackend, WebhookBackend};
+
+        let state = stand_in(&[]);
+        let backend = WebhookBackend::new(spawn_stand_in_webhook(state.clone()).await);
+
+        // Три пачки; вторая отклонена — первая записана, вторая и третья нет
+        let mut rows: Vec<SheetRow> =
+            (0..MAX_BATCH_ROWS * 2 + 3).map(|i| listed(&format!("smurf{}", i), &format!("G17{:08}", i))).collect();
+        rows[MAX_BATCH_ROWS + 1].offer_id = "G17-fail".to_string();
+        let results = backend.append_rows(&rows).await.unwrap();
+        assert_eq!(results.len(), rows.len());
+        assert!(results[..MAX_BATCH_ROWS].iter().all(|r| r.ok));
+        assert_eq!(results[MAX_BATCH_ROWS - 1].row, Some(MAX_BATCH_ROWS as u32 + 1));
+        assert!(results[MAX_BATCH_ROWS..].iter().all(|r| !r.ok));
+        assert_eq!(results[rows.len() - 1].error.as_deref(), Some("Exceeded maximum execution time"));
+        assert_eq!(results[rows.len() - 1].offer_id, rows[rows.len() - 1].offer_id);
+        assert_eq!(state.lock().unwrap().rows.len(), MAX_BATCH_ROWS);
+
+        // Версия скрипта запрошена один раз на бэкенд
+        let results = backend.append_rows(&[listed("retry", "G1700000999")]).await.unwrap();
+        assert!(results[0].ok);
+        assert_eq!(state.lock().unwrap().version_requests, 1);
+    }
+
+    #[test]
+    fn test_webhook_signature() {
+        // То же значение даёт Python: hmac.new(b"secret", b"1768564800\nab12\n{...}", sha256)
+        let sig = webhook_signature("secret", 1768564800, "ab12", r#"{"username":"smurf1"}"#);
+        assert_eq!(sig, "8e5575ec6e792dd8111be337acb48009f26893b95134be9081a0e54e5fd22879");
+        assert_ne!(sig, webhook_signature("secret", 1768564801, "ab12", r#"{"username":"smurf1"}"#));
+        assert_ne!(sig, webhook_signature("other", 1768564800, "ab12", r#"{"username":"smurf1"}"#));
+
+        assert_eq!(signed_url("https://example.com/exec", None, "list"), "https://example.com/exec");
+        let url = signed_url("https://example.com/exec?list=1", Some("secret"), "list");
+        assert!(url.starts_with("https://example.com/exec?list=1&ts="));
+        assert!(url.contains("&nonce=") && url.contains("&sig="));
+
+        // Секрет, вставленный с пробелами, подписывает так же, как без них
+        let url = signed_url("https://example.com/exec", Some("  secret\n"), "list");
+        let query: HashMap<&str, &str> =
+            url.split_once('?').unwrap().1.split('&').filter_map(|p| p.split_once('=')).collect();
+        let ts = query["ts"].parse().unwrap();
+        assert_eq!(query["sig"], webhook_signature("secret", ts, query["nonce"], "list"));
+        assert_eq!(signed_url("https://example.com/exec", Some("   "), "list"), "https://example.com/exec");
+    }
+}
@@ -0,0 +1,894 @@
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+use crate::sheet_backend::{BackendFuture, SheetBackend};
+use crate::sheets::{self, SheetAppendResult, SheetEntry, SheetRow, SheetUpdate, SheetUpdateResult};
+
+pub const SHEETS_API_BASE: &str = "https://sheets.googleapis.com";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+
+// Google выдаёт токен на час; берём новый за минуту до истечения.
+const TOKEN_LIFETIME_SECS: i64 = 3600;
+const TOKEN_REFRESH_MARGIN_SECS: u64 = 60;
+
+// Те же заголовки и правила поиска вкладки, что у скрипта Apps Script
+// (docs/google-sheets-integration.md). Регистр не важен.
+const COL_USERNAME: &[&str] = &["Username"];
+const COL_OFFER_ID: &[&str] = &["Offer ID"];
+const COL_LISTED_DATE: &[&str] = &["Listed Data", "Listed Date"];
+const COL_STATUS: &[&str] = &["Status"];
+const COL_FOLDER: &[&str] = &["Folder"];
+const COL_PRICE: &[&str] = &["Price"];
+const COL_SOLD_DATE: &[&str] = &["Sold Date", "Sold Data"];
+const HEADER_SCAN_ROWS: usize = 5;
+
+/// JSON-ключ сервисного аккаунта Google (скачивается в Cloud Console);
+/// используются только эти поля.
+#[derive(Clone, Deserialize)]
+pub struct ServiceAccountKey {
+    pub client_email: String,
+    pub private_key: String,
+    #[serde(default = "default_token_uri")]
+    pub token_uri: String,
+}
+
+fn default_token_uri() -> String {
+    DEFAULT_TOKEN_URI.to_string()
+}
+
+impl std::fmt::Debug for ServiceAccountKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceAccountKey")
+            .field("client_email", &self.client_email)
+            .field("token_uri", &self.token_uri)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct SpreadsheetMeta {
+    #[serde(default)]
+    sheets: Vec<SheetMeta>,
+}
+
+#[derive(Deserialize)]
+struct SheetMeta {
+    properties: SheetProperties,
+}
+
+#[derive(Deserialize)]
+struct SheetProperties {
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct AppendResponse {
+    #[serde(default)]
+    updates: AppendUpdates,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AppendUpdates {
+    #[serde(default)]
+    updated_range: Option<String>,
+}
+
+// Номер первой строки (с нуля) диапазона вида 'Accounts'!A6:F7
+fn first_row_of_range(range: &str) -> Option<usize> {
+    let cells = range.rsplit_once('!').map_or(range, |(_, cells)| cells);
+    let digits: String = cells
+        .chars()
+        .skip_while(|c| c.is_ascii_alphabetic())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse::<usize>().ok().filter(|&row| row > 0).map(|row| row - 1)
+}
+
+#[derive(Deserialize)]
+struct ValueRange {
+    // API не возвращает values для пустого диапазона
+    #[serde(default)]
+    values: Vec<Vec<Value>>,
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn pick_col(columns: &HashMap<String, usize>, names: &[&str]) -> Option<usize> {
+    names.iter().find_map(|name| columns.get(&name.trim().to_lowercase()).copied())
+}
+
+/// Буквы колонки по номеру с нуля: 0 → A, 26 → AA.
+fn column_letters(mut col: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(char::from(b'A' + (col % 26) as u8));
+        if col < 26 {
+            break;
+        }
+        col = col / 26 - 1;
+    }
+    letters.iter().rev().collect()
+}
+
+// Диапазон A1 c названием вкладки в кавычках ('It''s'!B3).
+fn sheet_range(title: &str, cells: Option<&str>) -> String {
+    let quoted = format!("'{}'", title.replace('\'', "''"));
+    match cells {
+        Some(cells) => format!("{}!{}", quoted, cells),
+        None => quoted,
+    }
+}
+
+// Вкладка со строкой заголовков, где есть все обязательные колонки.
+// Номера строк и колонок — с нуля.
+struct Target {
+    title: String,
+    values: Vec<Vec<String>>,
+    // Сколько строк было прочитано из таблицы: строки values дальше этого
+    // номера — новые, ещё не записанные (см. plan_offer_row).
+    loaded_rows: usize,
+    header_row: usize,
+    columns: HashMap<String, usize>,
+    user: usize,
+    offer: usize,
+    date: usize,
+    status: usize,
+    folder: Option<usize>,
+}
+
+impl Target {
+    // Ширина таблицы в колонках (по заголовкам)
+    fn width(&self) -> usize {
+        self.columns.values().max().map_or(0, |c| c + 1)
+    }
+
+    fn find(title: &str, header_rows: &[Vec<String>]) -> Option<(usize, HashMap<String, usize>)> {
+        header_rows.iter().take(HEADER_SCAN_ROWS).enumerate().find_map(|(row, headers)| {
+            let columns: HashMap<String, usize> = headers
+                .iter()
+                .enumerate()
+                .map(|(col, h)| (h.trim().to_lowercase(), col))
+                .filter(|(h, _)| !h.is_empty())
+                .collect();
+            let required = [COL_USERNAME, COL_OFFER_ID, COL_LISTED_DATE, COL_STATUS];
+            if required.iter().all(|names| pick_col(&columns, names).is_some()) {
+                debug!("Sheet tab {:?}: headers in row {}", title, row + 1);
+                Some((row, columns))
+            } else {
+                None
+            }
+        })
+    }
+
+    fn new(title: String, values: Vec<Vec<String>>, header_row: usize, columns: HashMap<String, usize>) -> Self {
+        let col = |names| pick_col(&columns, names).unwrap_or_default();
+        Self {
+            user: col(COL_USERNAME),
+            offer: col(COL_OFFER_ID),
+            date: col(COL_LISTED_DATE),
+            status: col(COL_STATUS),
+            folder: pick_col(&columns, COL_FOLDER),
+            title,
+            loaded_rows: values.len(),
+            values,
+            header_row,
+            columns,
+        }
+    }
+
+    fn cell(&self, row: usize, col: usize) -> &str {
+        self.values.get(row).and_then(|r| r.get(col)).map(|s| s.trim()).unwrap_or("")
+    }
+
+    fn data_rows(&self) -> std::ops::Range<usize> {
+        self.header_row + 1..self.values.len().max(self.header_row + 1)
+    }
+
+    fn last_row_where(&self, col: usize, matches: impl Fn(&str) -> bool) -> Option<usize> {
+        self.data_rows().rev().find(|&row| matches(self.cell(row, col)))
+    }
+
+    fn set(&mut self, row: usize, col: usize, value: &str) {
+        if self.values.len() <= row {
+            self.values.resize(row + 1, Vec::new());
+        }
+        let cells = &mut self.values[row];
+        if cells.len() <= col {
+            cells.resize(col + 1, String::new());
+        }
+        cells[col] = value.to_string();
+    }
+
+    // Ячейки строки о выставленном оффере. Строка с тем же Offer ID
+    // перезаписывается (повтор из очереди после таймаута, который на деле
+    // записал строку, не создаёт дубль); иначе заполняется строка с тем же
+    // Username и пустым Offer ID, иначе — новая в конце values (с номера
+    // loaded_rows; такие строки добавляются через values:append, см.
+    // write_planned). Значения сразу попадают в values, чтобы следующая
+    // строка пачки их видела.
+    fn plan_offer_row(&mut self, row: &SheetRow) -> (usize, Vec<(usize, usize, String)>) {
+        let want_offer = row.offer_id.trim();
+        let want = row.username.trim().to_lowercase();
+        let same_offer = (!want_offer.is_empty())
+            .then(|| self.last_row_where(self.offer, |v| v == want_offer))
+            .flatten();
+        let existing = same_offer.or_else(|| {
+            self.data_rows().find(|&r| {
+                let username = self.cell(r, self.user).to_lowercase();
+                !username.is_empty() && username == want && self.cell(r, self.offer).is_empty()
+            })
+        });
+        let mut cells = Vec::new();
+        let target_row = existing.unwrap_or_else(|| {
+            let new_row = self.data_rows().end;
+            cells.push((new_row, self.user, row.username.clone()));
+            new_row
+        });
+        cells.push((target_row, self.offer, row.offer_id.clone()));
+        cells.push((target_row, self.date, row.listed_date.clone()));
+        if let Some(folder) = self.folder.filter(|_| !row.folder.is_empty()) {
+            cells.push((target_row, folder, row.folder.clone()));
+        }
+        cells.push((target_row, self.status, row.status.clone()));
+        for (header, value) in &row.columns {
+            match pick_col(&self.columns, &[header.as_str()]) {
+                Some(col) => cells.push((target_row, col, value.clone())),
+                None => debug!("Sheet has no column {:?}, skipped", header),
+            }
+        }
+        for (r, c, value) in &cells {
+            self.set(*r, *c, value);
+        }
+        (target_row, cells)
+    }
+}
+
+/// Доступ к таблице через Google Sheets API v4 от имени сервисного аккаунта.
+/// Не зависит от задеплоенной версии Apps Script; таблицу нужно открыть
+/// на редактирование для client_email из ключа.
+pub struct SheetsApiBackend {
+    spreadsheet_id: String,
+    key: ServiceAccountKey,
+    api_base: String,
+    client: reqwest::Client,
+    // Токен доступа и момент, после которого его пора обновить.
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+impl std::fmt::Debug for SheetsApiBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SheetsApiBackend")
+            .field("spreadsheet_id", &self.spreadsheet_id)
+            .field("client_email", &self.key.client_email)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SheetsApiBackend {
+    pub fn new(spreadsheet_id: impl Into<String>, key: ServiceAccountKey) -> Result<Self, String> {
+        Ok(Self {
+            spreadsheet_id: spreadsheet_id.into().trim().to_string(),
+            key,
+            api_base: SHEETS_API_BASE.to_string(),
+            client: sheets::http_client()?,
+            token: Mutex::new(None),
+        })
+    }
+
+    pub fn from_key_file(spreadsheet_id: &str, key_path: impl AsRef<Path>) -> Result<Self, String> {
+        let key_path = key_path.as_ref();
+        let content = fs::read_to_string(key_path)
+            .map_err(|e| format!("Не удалось прочитать ключ сервисного аккаунта {:?}: {}", key_path, e))?;
+        let key: ServiceAccountKey = serde_json::from_str(&content)
+            .map_err(|e| format!("Некорректный ключ сервисного аккаунта {:?}: {}", key_path, e))?;
+        Self::new(spreadsheet_id, key)
+    }
+
+    /// Другой адрес API (для тестов с локальной заменой).
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn client_email(&self) -> &str {
+        &self.key.client_email
+    }
+
+    // Токен по JWT, подписанному ключом сервисного аккаунта (OAuth 2.0
+    // для серверных приложений); кешируется до истечения.
+    async fn access_token(&self) -> Result<String, String> {
+        let mut cached = self.token.lock().await;
+        if let Some((token, refresh_at)) = cached.as_ref() {
+            if Instant::now() < *refresh_at {
+                return Ok(token.clone());
+            }
+        }
+
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            iss: &self.key.client_email,
+            scope: SCOPE,
+            aud: &self.key.token_uri,
+            iat: now,
+            exp: now + TOKEN_LIFETIME_SECS,
+        };
+        let signing_key = EncodingKey::from_rsa_pem(self.key.private_key.as_bytes())
+            .map_err(|e| format!("Некорректный private_key в ключе сервисного аккаунта: {}", e))?;
+        let assertion = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &signing_key)
+            .map_err(|e| format!("Failed to sign service account token: {}", e))?;
+
+        let response = self
+            .client
+            .post(&self.key.token_uri)
+            .form(&[("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"), ("assertion", &assertion)])
+            .send()
+            .await
+            .map_err(|e| format!("Failed to request Google access token: {}", e))?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!("Google OAuth returned {}: {}", status, body));
+        }
+        let token: TokenResponse =
+            serde_json::from_str(&body).map_err(|e| format!("Failed to parse Google token response: {}", e))?;
+
+        let lifetime = token.expires_in.max(TOKEN_REFRESH_MARGIN_SECS) - TOKEN_REFRESH_MARGIN_SECS;
+        *cached = Some((token.access_token.clone(), Instant::now() + Duration::from_secs(lifetime)));
+        info!("Google Sheets API: access token for {}", self.key.client_email);
+        Ok(token.access_token)
+    }
+
+    // Понятный текст для частых ошибок настройки.
+    fn api_error(&self, status: StatusCode, body: &str) -> String {
+        let message = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|v| v["error"]["message"].as_str().map(String::from))
+            .unwrap_or_else(|| body.chars().take(200).collect());
+        match status {
+            StatusCode::FORBIDDEN => format!(
+                "Нет доступа к таблице: открой её на редактирование для {} ({})",
+                self.key.client_email, message
+            ),
+            StatusCode::NOT_FOUND => format!("Таблица не найдена — проверь spreadsheet_id ({})", message),
+            _ => format!("Google Sheets API returned {}: {}", status, message),
+        }
+    }
+
+    async fn request<T: DeserializeOwned>(&self, method: Method, url: &str, payload: Option<&Value>) -> Result<T, String> {
+        let token = self.access_token().await?;
+        let mut request = self.client.request(method, url).bearer_auth(token);
+        if let Some(payload) = payload {
+            request = request.json(payload);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request to Google Sheets API: {}", e))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read Google Sheets API response: {}", e))?;
+        if !status.is_success() {
+            return Err(self.api_error(status, &body));
+        }
+        serde_json::from_str(&body).map_err(|e| {
+            format!(
+                "Failed to parse Google Sheets API response: {} | Body: {}",
+                e,
+                body.chars().take(200).collect::<String>()
+            )
+        })
+    }
+
+    fn spreadsheet_url(&self) -> String {
+        format!("{}/v4/spreadsheets/{}", self.api_base, urlencoding::encode(&self.spreadsheet_id))
+    }
+
+    async fn read_values(&self, range: &str) -> Result<Vec<Vec<String>>, String> {
+        let url = format!("{}/values/{}", self.spreadsheet_url(), urlencoding::encode(range));
+        let range: ValueRange = self.request(Method::GET, &url, None).await?;
+        Ok(range.values.iter().map(|row| row.iter().map(cell_text).collect()).collect())
+    }
+
+    // Перебирает вкладки по порядку: сначала только строки заголовков,
+    // целиком читается лишь подошедшая вкладка.
+    async fn load_target(&self) -> Result<Target, String> {
+        let url = format!("{}?fields=sheets.properties.title", self.spreadsheet_url());
+        let meta: SpreadsheetMeta = self.request(Method::GET, &url, None).await?;
+        for sheet in meta.sheets {
+            let title = sheet.properties.title;
+            let header_range = format!("1:{}", HEADER_SCAN_ROWS);
+            let headers = self.read_values(&sheet_range(&title, Some(&header_range))).await?;
+            if let Some((header_row, columns)) = Target::find(&title, &headers) {
+                let values = self.read_values(&sheet_range(&title, None)).await?;
+                return Ok(Target::new(title, values, header_row, columns));
+            }
+        }
+        Err(format!(
+            "Не найдена вкладка со всеми колонками: {}, {}, {}, {}. Проверь, что заголовки \
+             написаны точно так же (в первых {} строках любой вкладки).",
+            COL_USERNAME[0], COL_OFFER_ID[0], COL_LISTED_DATE[0], COL_STATUS[0], HEADER_SCAN_ROWS
+        ))
+    }
+
+    // Записывает ячейки (строка, колонка, значение) одним запросом.
+    async fn write_cells(&self, target: &Target, cells: &[(usize, usize, String)]) -> Result<(), String> {
+        if cells.is_empty() {
+            return Ok(());
+        }
+        let data: Vec<Value> = cells
+            .iter()
+            .map(|(row, col, value)| {
+                let cell = format!("{}{}", column_letters(*col), row + 1);
+                json!({ "range": sheet_range(&target.title, Some(&cell)), "values": [[value]] })
+            })
+            .collect();
+        let payload = json!({ "valueInputOption": "USER_ENTERED", "data": data });
+        let url = format!("{}/values:batchUpdate", self.spreadsheet_url());
+        self.request::<Value>(Method::POST, &url, Some(&payload)).await?;
+        Ok(())
+    }
+
+    // Добавляет строки после последней строки таблицы (values:append с
+    // INSERT_ROWS): место выбирает Google в момент записи, поэтому два
+    // писателя (очередь и выставление, две машины) не попадут в одну строку.
+    // Возвращает номер первой добавленной строки (с нуля), если API его сообщил.
+    async fn append_values(&self, target: &Target, rows: &[Vec<String>]) -> Result<Option<usize>, String> {
+        let header = target.header_row + 1;
+        let cells = format!("A{}:{}{}", header, column_letters(target.width().max(1) - 1), header);
+        let url = format!(
+            "{}/values/{}:append?valueInputOption=USER_ENTERED&insertDataOption=INSERT_ROWS",
+            self.spreadsheet_url(),
+            urlencoding::encode(&sheet_range(&target.title, Some(&cells)))
+        );
+        let width = target.width();
+        let values: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                let mut row = row.clone();
+                row.resize(width.max(row.len()), String::new());
+                row
+            })
+            .collect();
+        let response: AppendResponse = self.request(Method::POST, &url, Some(&json!({ "values": values }))).await?;
+        Ok(response.updates.updated_range.as_deref().and_then(first_row_of_range))
+    }
+
+    // Пишет то, что подготовил plan_offer_row: ячейки существующих строк —
+    // batchUpdate, новые строки — append. Возвращает номер строки (с нуля)
+    // для каждого target_row из planned: для новых — по ответу append.
+    async fn write_planned(
+        &self,
+        target: &Target,
+        cells: &[(usize, usize, String)],
+        planned: &[usize],
+    ) -> Result<Vec<Option<usize>>, String> {
+        let existing: Vec<(usize, usize, String)> =
+            cells.iter().filter(|(row, _, _)| *row < target.loaded_rows).cloned().collect();
+        self.write_cells(target, &existing).await?;
+
+        let new_rows = &target.values[target.loaded_rows.min(target.values.len())..];
+        let first_new = if new_rows.is_empty() {
+            None
+        } else {
+            self.append_values(target, new_rows).await?
+        };
+        Ok(planned
+            .iter()
+            .map(|&row| {
+                if row < target.loaded_rows {
+                    Some(row)
+                } else {
+                    first_new.map(|first| first + row - target.loaded_rows)
+                }
+            })
+            .collect())
+    }
+
+    async fn write_offer_row(&self, row: &SheetRow) -> Result<(), String> {
+        info!("Writing listing to Google Sheet via Sheets API...");
+        debug!("Username: {}", row.username);
+        debug!("Offer ID: {}", row.offer_id);
+        let mut target = self.load_target().await?;
+        let (target_row, cells) = target.plan_offer_row(row);
+        let written = self.write_planned(&target, &cells, &[target_row]).await?;
+        match written[0] {
+            Some(written_row) => info!("Sheet updated: tab {:?}, row {}", target.title, written_row + 1),
+            None => info!("Sheet updated: tab {:?}", target.title),
+        }
+        Ok(())
+    }
+
+    // Пачка строк: одно чтение таблицы, один batchUpdate на существующие
+    // строки и один append на новые.
+    async fn write_offer_rows(&self, rows: &[SheetRow]) -> Result<Vec<SheetAppendResult>, String> {
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        info!("Writing {} listing(s) to Google Sheet via Sheets API...", rows.len());
+        let mut target = self.load_target().await?;
+        let mut cells = Vec::new();
+        let mut planned = Vec::with_capacity(rows.len());
+        for row in rows {
+            let (target_row, row_cells) = target.plan_offer_row(row);
+            cells.extend(row_cells);
+            planned.push(target_row);
+        }
+        let written = self.write_planned(&target, &cells, &planned).await?;
+        let results = rows
+            .iter()
+            .zip(written)
+            .map(|(row, written_row)| SheetAppendResult {
+                offer_id: row.offer_id.clone(),
+                ok: true,
+                error: None,
+                row: written_row.map(|r| r as u32 + 1),
+            })
+            .collect();
+        info!("Sheet updated: tab {:?}, {} row(s)", target.title, rows.len());
+        Ok(results)
+    }
+
+    async fn list_rows(&self) -> Result<Vec<SheetEntry>, String> {
+        info!("Fetching existing rows via Google Sheets API...");
+        let target = self.load_target().await?;
+        let rows: Vec<SheetEntry> = target
+            .data_rows()
+            .filter(|&r| !target.cell(r, target.user).is_empty())
+            .map(|r| SheetEntry {
+                username: target.cell(r, target.user).to_string(),
+                status: Some(target.cell(r, target.status).to_string()),
+                offer_id: Some(target.cell(r, target.offer).to_string()),
+            })
+            .collect();
+        info!("Fetched {} rows from sheet", rows.len());
+        Ok(rows)
+    }
+
+    async fn update_existing_row(&self, update: &SheetUpdate) -> Result<SheetUpdateResult, String> {
+        update.validate()?;
+        info!("Updating Google Sheet row via Sheets API...");
+        let target = self.load_target().await?;
+
+        let want_offer = update.offer_id.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let want_user = update.username.as_deref().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+        let found = want_offer
+            .and_then(|offer| target.last_row_where(target.offer, |v| v == offer))
+            .or_else(|| want_user.and_then(|user| target.last_row_where(target.user, |v| v.to_lowercase() == user)));
+        let Some(row) = found else {
+            info!("Sheet row update: row not found");
+            return Ok(SheetUpdateResult::default());
+        };
+
+        let mut cells = Vec::new();
+        let mut missing_columns = Vec::new();
+        if let Some(status) = &update.status {
+            cells.push((row, target.status, status.clone()));
+        }
+        let mut named: Vec<(Vec<&str>, &String)> = Vec::new();
+        if let Some(price) = &update.price {
+            named.push((COL_PRICE.to_vec(), price));
+        }
+        if let Some(sold_date) = &update.sold_date {
+            named.push((COL_SOLD_DATE.to_vec(), sold_date));
+        }
+        for (name, value) in &update.columns {
+            named.push((vec![name.as_str()], value));
+        }
+        for (names, value) in named {
+            match pick_col(&target.columns, &names) {
+                Some(col) => cells.push((row, col, value.clone())),
+                None => missing_columns.push(names[0].to_string()),
+            }
+        }
+        if !cells.is_empty() {
+            self.write_cells(&target, &cells).await?;
+        }
+
+        info!("Sheet row update: found = true, row = {}", row + 1);
+        Ok(SheetUpdateResult {
+            found: true,
+            sheet: Some(target.title.clone()),
+            row: Some(row as u32 + 1),
+            missing_columns,
+        })
+    }
+}
+
+impl SheetBackend for SheetsApiBackend {
+    fn name(&self) -> &'static str {
+        "api"
+    }
+
+    fn append_row<'a>(&'a self, row: &'a SheetRow) -> BackendFuture<'a, ()> {
+        Box::pin(self.write_offer_row(row))
+    }
+
+    fn append_rows<'a>(&'a self, rows: &'a [SheetRow]) -> BackendFuture<'a, Vec<SheetAppendResult>> {
+        Box::pin(self.write_offer_rows(rows))
+    }
+
+    fn fetch_rows(&self) -> BackendFuture<'_, Vec<SheetEntry>> {
+        Box::pin(self.list_rows())
+    }
+
+    fn update_row<'a>(&'a self, update: &'a SheetUpdate) -> BackendFuture<'a, SheetUpdateResult> {
+        Box::pin(self.update_existing_row(update))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    // Ключ сгенерирован только для тестов и нигде не зарегистрирован
+    // (см. testdata/README.md; путь в allowlist сканеров секретов).
+    const TEST_PRIVATE_KEY: &str = include_str!("testdata/TEST_ONLY_service_account_key.pem");
+    const SPREADSHEET_ID: &str = "test-spreadsheet";
+
+    #[derive(Default)]
+    struct StandIn {
+        tabs: Vec<(String, Vec<Vec<String>>)>,
+        token_requests: usize,
+        // Строки, которые «другой писатель» добавит перед следующим append
+        // (между чтением таблицы бэкендом и его записью)
+        concurrent_rows: Vec<Vec<String>>,
+    }
+
+    fn grid(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter().map(|r| r.iter().map(|c| c.to_string()).collect()).collect()
+    }
+
+    // "C5" → (строка 4, колонка 2)
+    fn parse_cell(cell: &str) -> (usize, usize) {
+        let split = cell.find(|c: char| c.is_ascii_digit()).unwrap();
+        let col = cell[..split].bytes().fold(0, |acc, b| acc * 26 + (b - b'A' + 1) as usize) - 1;
+        (cell[split..].parse::<usize>().unwrap() - 1, col)
+    }
+
+    fn route(method: &str, path: &str, head: &str, body: &str, state: &mut StandIn) -> (&'static str, Value) {
+        if path == "/token" {
+            assert!(body.contains("grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Ajwt-bearer"));
+            assert!(body.contains("assertion=ey"));
+            state.token_requests += 1;
+            return ("200 OK", json!({ "access_token": "stand-in-token", "expires_in": 3600 }));
+        }
+        if !head.to_lowercase().contains("authorization: bearer stand-in-token") {
+            return ("401 Unauthorized", json!({ "error": { "code": 401, "message": "Missing token" } }));
+        }
+        let prefix = format!("/v4/spreadsheets/{}", SPREADSHEET_ID);
+        let Some(rest) = path.strip_prefix(&prefix) else {
+            return ("404 Not Found", json!({ "error": { "code": 404, "message": "Requested entity was not found." } }));
+        };
+
+        if method == "GET" && rest.starts_with('?') {
+            let sheets: Vec<Value> = state.tabs.iter().map(|(t, _)| json!({ "properties": { "title": t } })).collect();
+            return ("200 OK", json!({ "sheets": sheets }));
+        }
+        if let Some(range) = rest.strip_prefix("/values/").filter(|_| method == "GET") {
+            let range = urlencoding::decode(range).unwrap().to_string();
+            let (title, rows) = match range.split_once('!') {
+                Some((title, rows)) => (title, rows.split_once(':').map(|(_, last)| last.parse::<usize>().unwrap())),
+                None => (range.as_str(), None),
+            };
+            let (_, values) = state.tabs.iter().find(|(t, _)| *t == title.trim_matches('\'')).unwrap();
+            let values: Vec<_> = values.iter().take(rows.unwrap_or(usize::MAX)).collect();
+            return ("200 OK", json!({ "range": range, "values": values }));
+        }
+        if let Some(range) = rest
+            .strip_prefix("/values/")
+            .and_then(|r| r.split_once(":append?"))
+            .filter(|_| method == "POST")
+        {
+            let (range, query) = range;
+            assert!(query.contains("insertDataOption=INSERT_ROWS"), "{}", query);
+            assert!(query.contains("valueInputOption=USER_ENTERED"), "{}", query);
+            let range = urlencoding::decode(range).unwrap().to_string();
+            let (title, _) = range.split_once('!').unwrap();
+            let concurrent = std::mem::take(&mut state.concurrent_rows);
+            let (_, values) = state.tabs.iter_mut().find(|(t, _)| *t == title.trim_matches('\'')).unwrap();
+            values.extend(concurrent);
+            let payload: Value = serde_json::from_str(body).unwrap();
+            let rows = payload["values"].as_array().unwrap();
+            let first = values.len() + 1;
+            for row in rows {
+                values.push(row.as_array().unwrap().iter().map(|c| c.as_str().unwrap().to_string()).collect());
+            }
+            let updated_range = format!("{}!A{}:F{}", title, first, first + rows.len() - 1);
+            return ("200 OK", json!({ "updates": { "updatedRange": updated_range, "updatedRows": rows.len() } }));
+        }
+        if method == "POST" && rest == "/values:batchUpdate" {
+            let payload: Value = serde_json::from_str(body).unwrap();
+            assert_eq!(payload["valueInputOption"], "USER_ENTERED");
+            for data in payload["data"].as_array().unwrap() {
+                let (title, cell) = data["range"].as_str().unwrap().split_once('!').unwrap();
+                let (row, col) = parse_cell(cell);
+                let (_, values) = state.tabs.iter_mut().find(|(t, _)| *t == title.trim_matches('\'')).unwrap();
+                if values.len() <= row {
+                    values.resize(row + 1, Vec::new());
+                }
+                if values[row].len() <= col {
+                    values[row].resize(col + 1, String::new());
+                }
+                values[row][col] = data["values"][0][0].as_str().unwrap().to_string();
+            }
+            return ("200 OK", json!({ "spreadsheetId": SPREADSHEET_ID }));
+        }
+        ("404 Not Found", json!({ "error": { "code": 404, "message": "Unknown route" } }))
+    }
+
+    // Локальная замена sheets.googleapis.com и oauth2.googleapis.com/token:
+    // только маршруты, которые использует бэкенд.
+    async fn spawn_stand_in_api(state: Arc<StdMutex<StandIn>>) -> String {
+        crate::test_http::spawn(move |request| {
+            let state = &mut state.lock().unwrap();
+            let (status, response) = route(&request.method, &request.path, &request.head, &request.body, state);
+            (status, response.to_string())
+        })
+        .await
+    }
+
+    fn sheet_row(username: &str, offer_id: &str) -> SheetRow {
+        SheetRow {
+            username: username.to_string(),
+            offer_id: offer_id.to_string(),
+            listed_date: "2026-01-16 12:00:00".to_string(),
+            folder: "/accounts/EUW".to_string(),
+            status: "Active".to_string(),
+            // Server в таблице нет — колонка пропускается
+            columns: HashMap::from([
+                ("price".to_string(), "25.00".to_string()),
+                ("Server".to_string(), "EUW".to_string()),
+            ]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sheets_api_backend_against_stand_in() {
+        assert_eq!(column_letters(0), "A");
+        assert_eq!(column_letters(27), "AB");
+
+        let state = Arc::new(StdMutex::new(StandIn {
+            tabs: vec![
+                ("Notes".to_string(), grid(&[&["just notes"]])),
+                (
+                    "Accounts".to_string(),
+                    grid(&[
+                        &["Inventory"],
+                        &["Username", "Offer ID", "Listed Date", "Status", "Folder", "Price"],
+                        &["alice", "", "", ""],
+                        &["bob", "G1700000001", "2026-01-01", "Active"],
+                    ]),
+                ),
+            ],
+            ..Default::default()
+        }));
+        let base = spawn_stand_in_api(state.clone()).await;
+        let key = ServiceAccountKey {
+            client_email: "sync@test-project.iam.gserviceaccount.com".to_string(),
+            private_key: TEST_PRIVATE_KEY.to_string(),
+            token_uri: format!("{}/token", base),
+        };
+        let backend = SheetsApiBackend::new(SPREADSHEET_ID, key.clone()).unwrap().with_api_base(&base);
+
+        // Alice уже есть без Offer ID — строка заполняется; Carol — новая в конце
+        backend.append_row(&sheet_row("Alice", "G1700000002")).await.unwrap();
+        backend.append_row(&sheet_row("carol", "G1700000003")).await.unwrap();
+        {
+            let state = state.lock().unwrap();
+            let accounts = &state.tabs[1].1;
+            assert_eq!(accounts[2], ["alice", "G1700000002", "2026-01-16 12:00:00", "Active", "/accounts/EUW", "25.00"]);
+            assert_eq!(accounts[4], ["carol", "G1700000003", "2026-01-16 12:00:00", "Active", "/accounts/EUW", "25.00"]);
+        }
+
+        let rows = backend.fetch_rows().await.unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1].username, "bob");
+        assert_eq!(rows[1].offer_id.as_deref(), Some("G1700000001"));
+
+        let update = SheetUpdate {
+            offer_id: Some("G1700000001".to_string()),
+            status: Some("Sold".to_string()),
+            price: Some("30".to_string()),
+            columns: HashMap::from([("Buyer".to_string(), "someone".to_string())]),
+            ..Default::default()
+        };
+        let result = backend.update_row(&update).await.unwrap();
+        assert!(result.found);
+        assert_eq!(result.sheet.as_deref(), Some("Accounts"));
+        assert_eq!(result.row, Some(4));
+        assert_eq!(result.missing_columns, ["Buyer"]);
+        {
+            let state = state.lock().unwrap();
+            assert_eq!(state.tabs[1].1[3][3], "Sold");
+            assert_eq!(state.tabs[1].1[3][5], "30");
+            // Токен получен один раз и переиспользуется
+            assert_eq!(state.token_requests, 1);
+        }
+
+        let by_user = SheetUpdate {
+            username: Some("CAROL".to_string()),
+            sold_date: Some("2026-02-01".to_string()),
+            ..Default::default()
+        };
+        let result = backend.update_row(&by_user).await.unwrap();
+        assert_eq!((result.found, result.row), (true, Some(5)));
+        assert_eq!(result.missing_columns, ["Sold Date"]);
+
+        let missing = SheetUpdate { offer_id: Some("G17-missing".to_string()), ..Default::default() };
+        assert!(!backend.update_row(&missing).await.unwrap().found);
+
+        // Повтор той же строки (таймаут у клиента, запись на сервере прошла)
+        // обновляет её, а не добавляет вторую
+        backend.append_row(&sheet_row("carol", "G1700000003")).await.unwrap();
+        backend.append_row(&sheet_row("carol", "G1700000003")).await.unwrap();
+        {
+            let state = state.lock().unwrap();
+            let accounts = &state.tabs[1].1;
+            assert_eq!(accounts.iter().filter(|r| r.get(1).map(String::as_str) == Some("G1700000003")).count(), 1);
+            assert_eq!(accounts.len(), 5);
+        }
+
+        // Пачка: новые строки идут подряд, а не в одну и ту же
+        let batch = [sheet_row("dave", "G1700000004"), sheet_row("erin", "G1700000005")];
+        let results = backend.append_rows(&batch).await.unwrap();
+        assert_eq!(results.iter().map(|r| (r.ok, r.row)).collect::<Vec<_>>(), [(true, Some(6)), (true, Some(7))]);
+        {
+            let state = state.lock().unwrap();
+            assert_eq!(state.tabs[1].1[5][..2], ["dave", "G1700000004"]);
+            assert_eq!(state.tabs[1].1[6][..2], ["erin", "G1700000005"]);
+        }
+
+        // Другой писатель добавил строку после того, как бэкенд прочитал
+        // таблицу: append кладёт новую строку после неё, ничего не затирая
+        state.lock().unwrap().concurrent_rows = grid(&[&["frank", "G1700000006", "2026-01-16", "Active"]]);
+        let results = backend.append_rows(&[sheet_row("gina", "G1700000007")]).await.unwrap();
+        assert_eq!(results[0].row, Some(9));
+        {
+            let state = state.lock().unwrap();
+            let accounts = &state.tabs[1].1;
+            assert_eq!(accounts[7][..2], ["frank", "G1700000006"]);
+            assert_eq!(accounts[8], ["gina", "G1700000007", "2026-01-16 12:00:00", "Active", "/accounts/EUW", "25.00"]);
+        }
+
+        let wrong_id = SheetsApiBackend::new("other-spreadsheet", key).unwrap().with_api_base(&base);
+        let error = wrong_id.fetch_rows().await.unwrap_err();
+        assert!(error.contains("spreadsheet_id"), "{}", error);
+    }
+}
@@ -0,0 +1,88 @@
+//! Локальный HTTP-сервер для тестов: заменяет внешние сервисы (image-хост,
+//! веб-хук Apps Script, Sheets API). Один запрос на соединение, ответ — JSON.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Разобранный запрос: метод, путь (с query), заголовки и тело как текст.
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub head: String,
+    pub body: String,
+}
+
+fn find_header_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n")
+}
+
+// Читает заголовки, затем тело по Content-Length. None — клиент закрыл
+// соединение, не прислав заголовки целиком.
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = socket.read(&mut chunk).await.unwrap_or(0);
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(header_end) = find_header_end(&buf) {
+            let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+            let length = head
+                .lines()
+                .find_map(|l| l.to_lowercase().strip_prefix("content-length:").and_then(|v| v.trim().parse::<usize>().ok()))
+                .unwrap_or(0);
+            if buf.len() >= header_end + 4 + length || n == 0 {
+                let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+                let method = request_line.next().unwrap_or_default().to_string();
+                let path = request_line.next().unwrap_or_default().to_string();
+                let body = String::from_utf8_lossy(&buf[header_end + 4..]).to_string();
+                return Some(Request { method, path, head, body });
+            }
+        }
+        if n == 0 {
+            return None;
+        }
+    }
+}
+
+/// Запускает сервер на свободном порту; route возвращает статус ("200 OK")
+/// и тело ответа. Возвращает "http://127.0.0.1:<порт>".
+pub async fn spawn(mut route: impl FnMut(Request) -> (&'static str, String) + Send + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let Some(request) = read_request(&mut socket).await else {
+                continue;
+            };
+            let (status, response) = route(request);
+            let reply = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                response.len(),
+                response
+            );
+            let _ = socket.write_all(reply.as_bytes()).await;
+        }
+    });
+    format!("http://{}", addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_client_closing_before_headers() {
+        let base = spawn(|request| ("200 OK", format!("{{\"path\":\"{}\"}}", request.path))).await;
+        let addr = base.strip_prefix("http://").unwrap();
+
+        // Оборванный запрос без конца заголовков не вешает сервер
+        let mut socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+        socket.write_all(b"GET /broken HTTP/1.1\r\nHost: x").await.unwrap();
+        drop(socket);
+
+        let body = reqwest::get(format!("{}/next", base)).await.unwrap().text().await.unwrap();
+        assert_eq!(body, "{\"path\":\"/next\"}");
+    }
+}
@@ -0,0 +1,296 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::{self, AppSettings};
+use tracing::info;
+
+/// Период полураспада счётчиков по умолчанию: чемпион, использованный
+/// 30 дней назад, весит вдвое меньше только что использованного.
+pub const DEFAULT_HALF_LIFE_DAYS: f64 = 30.0;
+
+const STORE_FILE_NAME: &str = "title_usage.json";
+
+/// Статистика использования одного чемпиона или скина в заголовках.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageEntry {
+    /// Затухающий вес (на момент updated_at).
+    pub score: f64,
+    /// Сколько раз использовался всего, без затухания.
+    pub total: u64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Хранилище использования чемпионов/скинов в заголовках офферов.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageStore {
+    #[serde(default)]
+    pub champions: HashMap<String, UsageEntry>,
+    #[serde(default)]
+    pub skins: HashMap<String, UsageEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageStat {
+    pub name: String,
+    pub kind: String,
+    pub score: f64,
+    pub total: u64,
+}
+
+impl UsageEntry {
+    // Вес с учётом затухания на момент now
+    pub fn decayed_score(&self, now: DateTime<Utc>, half_life_days: f64) -> f64 {
+        match self.updated_at {
+            Some(at) if half_life_days > 0.0 => {
+                let days = (now - at).num_seconds().max(0) as f64 / 86_400.0;
+                self.score * 0.5f64.powf(days / half_life_days)
+            }
+            _ => self.score,
+        }
+    }
+
+    fn add(&mut self, amount: f64, now: DateTime<Utc>, half_life_days: f64) {
+        self.score = self.decayed_score(now, half_life_days) + amount;
+        self.total += amount.max(0.0).round() as u64;
+        self.updated_at = Some(now);
+    }
+}
+
+impl UsageStore {
+    pub fn track(&mut self, champions: &[String], skins: &[String], now: DateTime<Utc>, half_life_days: f64) {
+        for name in champions {
+            self.champions.entry(name.clone()).or_default().add(1.0, now, half_life_days);
+        }
+        for name in skins {
+            self.skins.entry(name.clone()).or_default().add(1.0, now, half_life_days);
+        }
+    }
+
+    /// Сортирует чемпионов для заголовка: сначала реже использованные
+    /// (по затухающему весу), при равенстве — по алфавиту.
+    pub fn rotate(&self, champions: &[String], now: DateTime<Utc>, half_life_days: f64) -> Vec<String> {
+        let score = |name: &String| {
+            self.champions
+                .get(name)
+                .map(|e| e.decayed_score(now, half_life_days))
+                .unwrap_or(0.0)
+        };
+        let mut sorted = champions.to_vec();
+        sorted.sort_by(|a, b| score(a).total_cmp(&score(b)).then_with(|| a.cmp(b)));
+        sorted
+    }
+
+    /// Импорт счётчиков из localStorage (формат { "Ahri": 3, ... }):
+    /// значения добавляются к текущим. Возвращает число импортированных имён.
+    pub fn import_counts(&mut self, counts: &HashMap<String, f64>, now: DateTime<Utc>, half_life_days: f64) -> usize {
+        let mut imported = 0;
+        for (name, count) in counts {
+            if *count > 0.0 {
+                self.champions.entry(name.clone()).or_default().add(*count, now, half_life_days);
+                imported += 1;
+            }
+        }
+        imported
+    }
+
+    pub fn stats(&self, now: DateTime<Utc>, half_life_days: f64) -> Vec<UsageStat> {
+        let mut stats: Vec<UsageStat> = self
+            .champions
+            .iter()
+            .map(|(name, e)| (name, e, "champion"))
+            .chain(self.skins.iter().map(|(name, e)| (name, e, "skin")))
+            .map(|(name, e, kind)| UsageStat {
+                name: name.clone(),
+                kind: kind.to_string(),
+                score: e.decayed_score(now, half_life_days),
+                total: e.total,
+            })
+            .collect();
+        stats.sort_by(|a, b| a.score.total_cmp(&b.score).then_with(|| a.name.cmp(&b.name)));
+        stats
+    }
+}
+
+// Путь к файлу хранилища и период полураспада из настроек. Путь можно
+// указать на общей папке, чтобы статистика была общей для всей команды.
+fn store_settings() -> Result<(PathBuf, f64), String> {
+    let settings = AppSettings::load().ok().and_then(|s| s.usage);
+    let half_life = settings
+        .as_ref()
+        .map(|u| u.half_life_days)
+        .unwrap_or(DEFAULT_HALF_LIFE_DAYS);
+    let path = match settings.and_then(|u| u.store_path).filter(|p| !p.trim().is_empty()) {
+        Some(p) => PathBuf::from(p.trim()),
+        None => config::config_dir()?.join(STORE_FILE_NAME),
+    };
+    Ok((path, half_life))
+}
+
+fn read_store(path: &Path) -> Result<UsageStore, String> {
+    if !path.exists() {
+        return Ok(UsageStore::default());
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read usage store {:?}: {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse usage store {:?}: {}", path, e))
+}
+
+// Запись через временный файл + rename, чтобы параллельные читатели
+// (другие машины на общем диске) не увидели файл наполовину записанным.
+fn write_store(path: &Path, store: &UsageStore) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create usage store directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(store)
+        .map_err(|e| format!("Failed to serialize usage store: {}", e))?;
+    let temp_path = path.with_extension("json.tmp");
+    {
+        let mut file = fs::File::create(&temp_path)
+            .map_err(|e| format!("Failed to create usage store temp file: {}", e))?;
+        file.write_all(json.as_bytes())
+            .map_err(|e| format!("Failed to write usage store: {}", e))?;
+        file.sync_all().map_err(|e| format!("Failed to flush usage store: {}", e))?;
+    }
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to replace usage store: {}", e))
+}
+
+// Эксклюзивная блокировка ОС на файл {store}.lock рядом с хранилищем.
+// Хранилище может лежать на общей папке, поэтому внутрипроцессного мьютекса
+// мало: две машины одновременно прочитали бы файл и затёрли изменения друг
+// друга. Блокировка снимается при закрытии файла (drop).
+fn lock_store(path: &Path) -> Result<fs::File, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create usage store directory: {}", e))?;
+    }
+    let lock_path = path.with_extension("json.lock");
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| format!("Failed to open usage store lock {:?}: {}", lock_path, e))?;
+    file.lock()
+        .map_err(|e| format!("Failed to lock usage store {:?}: {}", lock_path, e))?;
+    Ok(file)
+}
+
+// Прочитать хранилище, изменить и записать под блокировкой файла
+fn update_store_at<T>(
+    path: &Path,
+    half_life: f64,
+    f: impl FnOnce(&mut UsageStore, DateTime<Utc>, f64) -> T,
+) -> Result<T, String> {
+    let _lock = lock_store(path)?;
+    let mut store = read_store(path)?;
+    let result = f(&mut store, Utc::now(), half_life);
+    write_store(path, &store)?;
+    Ok(result)
+}
+
+fn update_store<T>(f: impl FnOnce(&mut UsageStore, DateTime<Utc>, f64) -> T) -> Result<T, String> {
+    let (path, half_life) = store_settings()?;
+    update_store_at(&path, half_life, f)
+}
+
+/// Учесть чемпионов и скины, попавшие в заголовок выставленного оффера.
+pub fn track_usage(champions: &[String], skins: &[String]) -> Result<(), String> {
+    update_store(|store, now, half_life| store.track(champions, skins, now, half_life))?;
+    info!("Title usage tracked: {} champion(s), {} skin(s)", champions.len(), skins.len());
+    Ok(())
+}
+
+/// Порядок чемпионов для заголовка (ротация по частоте использования).
+pub fn rotate_champions(champions: &[String]) -> Result<Vec<String>, String> {
+    let (path, half_life) = store_settings()?;
+    let store = read_store(&path)?;
+    Ok(store.rotate(champions, Utc::now(), half_life))
+}
+
+/// Импорт JSON из localStorage (ключ g2g_champion_usage_count).
+pub fn import_local_storage(json: &str) -> Result<usize, String> {
+    let counts: HashMap<String, f64> = serde_json::from_str(json)
+        .map_err(|e| format!("Некорректный JSON статистики чемпионов: {}", e))?;
+    let imported = update_store(|store, now, half_life| store.import_counts(&counts, now, half_life))?;
+    info!("Imported usage for {} champion(s)", imported);
+    Ok(imported)
+}
+
+/// Полное содержимое хранилища в JSON (для переноса или бэкапа).
+pub fn export_json() -> Result<String, String> {
+    let (path, _) = store_settings()?;
+    let store = read_store(&path)?;
+    serde_json::to_string_pretty(&store).map_err(|e| format!("Failed to serialize usage store: {}", e))
+}
+
+pub fn stats() -> Result<Vec<UsageStat>, String> {
+    let (path, half_life) = store_settings()?;
+    Ok(read_store(&path)?.stats(Utc::now(), half_life))
+}
+
+pub fn clear() -> Result<(), String> {
+    update_store(|store, _, _| *store = UsageStore::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_with_decay() {
+        let mut store = UsageStore::default();
+        let start = Utc::now() - chrono::Duration::days(60);
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        // Ahri использовалась 4 раза два месяца назад, Zed — один раз сейчас
+        for _ in 0..4 {
+            store.track(&names(&["Ahri"]), &[], start, DEFAULT_HALF_LIFE_DAYS);
+        }
+        let now = Utc::now();
+        store.track(&names(&["Zed"]), &[], now, DEFAULT_HALF_LIFE_DAYS);
+
+        let order = store.rotate(&names(&["Zed", "Ahri", "Annie"]), now, DEFAULT_HALF_LIFE_DAYS);
+        assert_eq!(order, names(&["Annie", "Ahri", "Zed"]));
+
+        // Без затухания Ahri использовалась чаще всех
+        let order = store.rotate(&names(&["Zed", "Ahri", "Annie"]), now, 0.0);
+        assert_eq!(order, names(&["Annie", "Zed", "Ahri"]));
+        assert_eq!(store.champions["Ahri"].total, 4);
+
+        let mut counts = HashMap::new();
+        counts.insert("Annie".to_string(), 2.0);
+        assert_eq!(store.import_counts(&counts, now, DEFAULT_HALF_LIFE_DAYS), 1);
+        assert_eq!(store.champions["Annie"].total, 2);
+    }
+
+    #[test]
+    fn test_concurrent_updates_not_lost() {
+        let dir = std::env::temp_dir().join(format!("g2g-usage-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join(STORE_FILE_NAME);
+
+        // Каждый поток открывает свою блокировку, как отдельная машина
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        update_store_at(&path, DEFAULT_HALF_LIFE_DAYS, |store, now, half_life| {
+                            store.track(&["Ahri".to_string()], &[], now, half_life)
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert_eq!(read_store(&path).unwrap().champions["Ahri"].total, 40);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
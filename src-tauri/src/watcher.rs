@@ -0,0 +1,390 @@
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::account_info;
+use crate::accounts::{AccountFolder, AccountStore};
+use crate::config::AppSettings;
+use crate::ProgressCallback;
+
+/// Пауза после последнего события ФС перед пересканированием: копирование
+/// папки аккаунта или запись файла оффера — это десятки событий подряд.
+pub const DEBOUNCE_MS: u64 = 750;
+
+// Интервал опроса, если системные уведомления недоступны или ненадёжны
+// (сетевые диски).
+const POLL_INTERVAL_SECS: u64 = 5;
+
+// Типы файловых систем в /proc/mounts, изменения на которых с других машин
+// не приходят системными уведомлениями.
+const NETWORK_FS_TYPES: &[&str] = &["cifs", "smb3", "smbfs", "nfs", "nfs4", "9p", "afs", "fuse.sshfs", "davfs"];
+
+/// Как следить за папкой аккаунтов (настройка watcher.mode).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchMode {
+    /// Опрос для сетевых путей (UNC, SMB/NFS-монтирования), иначе уведомления ФС.
+    #[default]
+    Auto,
+    /// Системные уведомления (с опросом, если они не запускаются).
+    Native,
+    /// Всегда опрос раз в POLL_INTERVAL_SECS секунд.
+    Poll,
+}
+
+impl WatchMode {
+    pub fn from_settings() -> Self {
+        AppSettings::load()
+            .ok()
+            .and_then(|s| s.watcher)
+            .map(|w| w.mode)
+            .unwrap_or_default()
+    }
+}
+
+// Тип сетевой ФС, на которой лежит path, по содержимому /proc/mounts
+// (самая длинная точка монтирования, являющаяся префиксом пути).
+fn network_fs_type(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            // Пробелы в точке монтирования записаны как \040
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point).then(|| (mount_point.len(), fs_type.to_string()))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, fs_type)| fs_type)
+        .filter(|fs_type| NETWORK_FS_TYPES.contains(&fs_type.as_str()))
+}
+
+/// Лежит ли папка на сетевом диске: UNC-путь (\\server\share) или, в Linux,
+/// монтирование SMB/NFS. Подключённые буквы дисков Windows так не
+/// распознаются — для них нужен режим "poll" в настройках.
+pub fn is_network_path(path: &Path) -> bool {
+    if cfg!(windows) {
+        let text = path.to_string_lossy();
+        let unc = text.starts_with("\\\\") || text.starts_with("//");
+        return unc && (!text.starts_with("\\\\?\\") || text.starts_with("\\\\?\\UNC\\"));
+    }
+    if cfg!(target_os = "linux") {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if let Ok(mounts) = std::fs::read_to_string("/proc/mounts") {
+            return network_fs_type(&mounts, &path).is_some();
+        }
+    }
+    false
+}
+
+/// Изменение в папке аккаунтов относительно предыдущего сканирования.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AccountChange {
+    Added {
+        account: AccountFolder,
+    },
+    Removed {
+        path: String,
+    },
+    /// listed_changed — аккаунт выставлен или снят (появился/пропал `G17*.txt`).
+    Changed {
+        account: AccountFolder,
+        listed_changed: bool,
+    },
+}
+
+type Snapshot = BTreeMap<String, AccountFolder>;
+
+fn snapshot_of(accounts: &[AccountFolder]) -> Snapshot {
+    accounts.iter().map(|a| (a.path.clone(), a.clone())).collect()
+}
+
+fn differs(old: &AccountFolder, new: &AccountFolder) -> bool {
+    old.is_listed != new.is_listed
+        || old.group != new.group
+        || old.summary.modified != new.summary.modified
+        || old.summary.offer_id != new.summary.offer_id
+        || old.summary.offer_status != new.summary.offer_status
+}
+
+/// Изменения между двумя сканированиями, по пути аккаунта.
+pub fn diff_accounts(old: &[AccountFolder], new: &[AccountFolder]) -> Vec<AccountChange> {
+    diff_snapshots(&snapshot_of(old), &snapshot_of(new))
+}
+
+fn diff_snapshots(old: &Snapshot, new: &Snapshot) -> Vec<AccountChange> {
+    let mut changes = Vec::new();
+    for (path, account) in new {
+        match old.get(path) {
+            None => changes.push(AccountChange::Added { account: account.clone() }),
+            Some(prev) if differs(prev, account) => changes.push(AccountChange::Changed {
+                account: account.clone(),
+                listed_changed: prev.is_listed != account.is_listed,
+            }),
+            Some(_) => {}
+        }
+    }
+    for path in old.keys().filter(|path| !new.contains_key(*path)) {
+        changes.push(AccountChange::Removed { path: path.clone() });
+    }
+    changes
+}
+
+/// Следит за загруженной папкой аккаунтов и сообщает о добавленных, удалённых
+/// и изменённых аккаунтах (в том числе о выставлении с другой машины на общем
+/// диске). Наблюдение идёт, пока значение живо; drop его останавливает.
+pub struct AccountWatcher {
+    _watcher: Box<dyn Watcher + Send>,
+    root: PathBuf,
+}
+
+impl AccountWatcher {
+    /// Запускает наблюдение. initial — результат последнего сканирования,
+    /// от которого считаются изменения; on_change получает пачку изменений
+    /// после каждой серии событий (с паузой DEBOUNCE_MS). Режим берётся
+    /// из настроек (см. [`WatchMode`]).
+    pub fn start(
+        store: AccountStore,
+        initial: &[AccountFolder],
+        on_change: ProgressCallback<Vec<AccountChange>>,
+    ) -> Result<Self, String> {
+        Self::start_with_mode(store, initial, WatchMode::from_settings(), on_change)
+    }
+
+    pub fn start_with_mode(
+        store: AccountStore,
+        initial: &[AccountFolder],
+        mode: WatchMode,
+        on_change: ProgressCallback<Vec<AccountChange>>,
+    ) -> Result<Self, String> {
+        let root = store.root().to_path_buf();
+        let (tx, rx) = mpsc::channel::<Vec<PathBuf>>();
+        let rescan_root = root.clone();
+        let handler = move |result: notify::Result<notify::Event>| match result {
+            // Чтение файлов (в том числе нашим же сканированием) не считается изменением
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                // Без путей (или с флагом rescan) неизвестно, что изменилось —
+                // пересканируется вся папка
+                let paths = if event.paths.is_empty() || event.need_rescan() {
+                    vec![rescan_root.clone()]
+                } else {
+                    event.paths
+                };
+                let _ = tx.send(paths);
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Ошибка наблюдения за папкой аккаунтов: {}", e),
+        };
+
+        let poll = match mode {
+            WatchMode::Poll => true,
+            WatchMode::Native => false,
+            WatchMode::Auto => is_network_path(&root),
+        };
+        let watcher = if poll {
+            info!("Account folder {:?} is polled every {} s", root, POLL_INTERVAL_SECS);
+            poll_watcher(&root, handler)?
+        } else {
+            watch_path(&root, handler)?
+        };
+
+        let snapshot = snapshot_of(initial);
+        std::thread::Builder::new()
+            .name("account-watcher".to_string())
+            .spawn(move || watch_loop(store, rx, snapshot, on_change))
+            .map_err(|e| format!("Failed to start account watcher thread: {}", e))?;
+
+        info!("Watching account folder {:?}", root);
+        Ok(Self { _watcher: watcher, root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Drop for AccountWatcher {
+    fn drop(&mut self) {
+        info!("Stopped watching account folder {:?}", self.root);
+    }
+}
+
+// Системные уведомления, а если они недоступны — опрос папки.
+fn watch_path<F>(root: &Path, handler: F) -> Result<Box<dyn Watcher + Send>, String>
+where
+    F: notify::EventHandler + Clone,
+{
+    let native = notify::recommended_watcher(handler.clone()).and_then(|mut watcher| {
+        watcher.watch(root, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    });
+    match native {
+        Ok(watcher) => Ok(Box::new(watcher)),
+        Err(e) => {
+            warn!("Уведомления ФС недоступны ({}), папка аккаунтов будет опрашиваться", e);
+            poll_watcher(root, handler)
+        }
+    }
+}
+
+fn poll_watcher<F>(root: &Path, handler: F) -> Result<Box<dyn Watcher + Send>, String>
+where
+    F: notify::EventHandler,
+{
+    let config = notify::Config::default().with_poll_interval(Duration::from_secs(POLL_INTERVAL_SECS));
+    let mut watcher = notify::PollWatcher::new(handler, config)
+        .map_err(|e| format!("Failed to create account watcher: {}", e))?;
+    watcher
+        .watch(root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {:?}: {}", root, e))?;
+    Ok(Box::new(watcher))
+}
+
+// Новое сканирование: структура папок читается целиком (это дёшево), а
+// сводка по файлам пересчитывается только для новых аккаунтов и тех, внутри
+// которых (или выше которых) были события; остальные берутся из snapshot.
+fn rescan(store: &AccountStore, snapshot: &Snapshot, changed: &BTreeSet<PathBuf>) -> Result<Snapshot, String> {
+    let touched = |account: &AccountFolder| {
+        let path = Path::new(&account.path);
+        changed.iter().any(|c| c.starts_with(path) || path.starts_with(c))
+    };
+
+    let (mut fresh, kept): (Vec<AccountFolder>, Vec<AccountFolder>) = store
+        .scan()?
+        .into_iter()
+        .map(|mut account| {
+            if let Some(prev) = snapshot.get(&account.path) {
+                account.summary = prev.summary.clone();
+            }
+            account
+        })
+        .partition(|account| !snapshot.contains_key(&account.path) || touched(account));
+
+    debug!("Rescanning {} changed account folder(s)", fresh.len());
+    account_info::summarize_all(&mut fresh);
+
+    Ok(snapshot_of(&fresh).into_iter().chain(snapshot_of(&kept)).collect())
+}
+
+// Ждёт событий, выдерживает паузу и пересканирует папку. Завершается, когда
+// AccountWatcher удалён (вместе с ним закрывается канал событий).
+fn watch_loop(
+    store: AccountStore,
+    rx: Receiver<Vec<PathBuf>>,
+    mut snapshot: Snapshot,
+    on_change: ProgressCallback<Vec<AccountChange>>,
+) {
+    while let Ok(paths) = rx.recv() {
+        let mut changed: BTreeSet<PathBuf> = paths.into_iter().collect();
+        loop {
+            match rx.recv_timeout(Duration::from_millis(DEBOUNCE_MS)) {
+                Ok(paths) => changed.extend(paths),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        let current = match rescan(&store, &snapshot, &changed) {
+            Ok(current) => current,
+            Err(e) => {
+                warn!("Не удалось пересканировать папку аккаунтов: {}", e);
+                continue;
+            }
+        };
+        let changes = diff_snapshots(&snapshot, &current);
+        snapshot = current;
+
+        if changes.is_empty() {
+            debug!("Account folder events without account changes");
+        } else {
+            info!("Account folder changed: {} change(s)", changes.len());
+            on_change(changes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_info::AccountSummary;
+
+    fn folder(path: &str, is_listed: bool) -> AccountFolder {
+        AccountFolder {
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: path.to_string(),
+            is_listed,
+            group: String::new(),
+            summary: AccountSummary::default(),
+        }
+    }
+
+    #[test]
+    fn test_diff_accounts() {
+        let old = vec![folder("/a/acc1", false), folder("/a/acc2", false), folder("/a/acc3", true)];
+        let new = vec![folder("/a/acc1", false), folder("/a/acc2", true), folder("/a/acc4", false)];
+
+        let changes = diff_accounts(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(&changes[0], AccountChange::Changed { account, listed_changed: true } if account.path == "/a/acc2"));
+        assert!(matches!(&changes[1], AccountChange::Added { account } if account.path == "/a/acc4"));
+        assert!(matches!(&changes[2], AccountChange::Removed { path } if path == "/a/acc3"));
+
+        let json = serde_json::to_value(&changes[2]).unwrap();
+        assert_eq!(json["kind"], "removed");
+        assert!(diff_accounts(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_network_fs_type() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+            //nas/share /mnt/nas cifs rw 0 0\n\
+            /dev/sdb1 /mnt/nas/local ext4 rw 0 0\n\
+            nas:/export /mnt/team\\040drive nfs4 rw 0 0\n";
+        assert_eq!(network_fs_type(mounts, Path::new("/mnt/nas/RESELL")).as_deref(), Some("cifs"));
+        assert_eq!(network_fs_type(mounts, Path::new("/mnt/team drive/acc")).as_deref(), Some("nfs4"));
+        assert_eq!(network_fs_type(mounts, Path::new("/mnt/nas/local/acc")), None);
+        assert_eq!(network_fs_type(mounts, Path::new("/home/user")), None);
+    }
+
+    #[test]
+    fn test_rescan_only_changed_accounts() {
+        let root = std::env::temp_dir().join(format!("g2g-app-watcher-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for name in ["acc1", "acc2"] {
+            std::fs::create_dir_all(root.join(name)).unwrap();
+            std::fs::write(root.join(name).join(format!("{}.txt", name)), format!("Login: {}_login\n", name)).unwrap();
+        }
+        let store = AccountStore::new(&root);
+        let snapshot = snapshot_of(&store.folders().unwrap().accounts);
+
+        // Оба файла изменены, но событие пришло только из acc1
+        for name in ["acc1", "acc2"] {
+            std::fs::write(root.join(name).join(format!("{}.txt", name)), format!("Login: {}_new\n", name)).unwrap();
+        }
+        std::fs::create_dir_all(root.join("acc3")).unwrap();
+        std::fs::write(root.join("acc3").join("acc3.txt"), "Login: acc3_login\n").unwrap();
+        let changed: BTreeSet<PathBuf> = [root.join("acc1").join("acc1.txt"), root.join("acc3")].into();
+
+        let current = rescan(&store, &snapshot, &changed).unwrap();
+        let login = |name: &str| {
+            let path = root.join(name).to_string_lossy().to_string();
+            current[&path].summary.login.clone().unwrap()
+        };
+        assert_eq!(login("acc1"), "acc1_new");
+        assert_eq!(login("acc2"), "acc2_login");
+        assert_eq!(login("acc3"), "acc3_login");
+
+        // Событие на корне — пересчитываются все
+        let current = rescan(&store, &snapshot, &[root.clone()].into()).unwrap();
+        let path = root.join("acc2").to_string_lossy().to_string();
+        assert_eq!(current[&path].summary.login.as_deref(), Some("acc2_new"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,17 +1,45 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-dialog";
 import { homeDir } from "@tauri-apps/api/path";
 
 // Типы данных
+// Краткие данные аккаунта, собранные при сканировании папки
+export interface AccountSummary {
+  login: string | null;
+  server: string | null;
+  level: number | null;
+  rank: string | null;
+  champions_count: number | null;
+  skins_count: number | null;
+  has_screenshot: boolean;
+  profile: string | null;
+  purchase_cost: string | null;
+  offer_id: string | null;
+  offer_status: string | null;
+  offer_created: string | null;
+  modified: string | null;
+}
+
 export interface AccountFolder {
   name: string;
   path: string;
   is_listed: boolean;
+  group: string; // родительская папка относительно выбранной, "" — корень
+  summary: AccountSummary;
+}
+
+export interface AccountGroup {
+  name: string;
+  path: string;
+  total: number;
+  listed: number;
 }
 
 export interface AccountsData {
   accounts: AccountFolder[];
   base_path: string;
+  groups: AccountGroup[];
 }
 
 export interface Account {
@@ -19,11 +47,25 @@ export interface Account {
   name: string;
   path: string;
   status: "loaded" | "processing" | "listed" | "error";
+  group?: string;
+  summary?: AccountSummary;
   files?: string[];
   is_listed?: boolean;
   in_sheet?: boolean;
   sheet_status?: string;
   sheet_username?: string;
+  sheet_match?: SheetMatchConfidence;
+  sheet_duplicate?: boolean;
+}
+
+// Как строка таблицы найдена: по Offer ID, по логину точно, без пробелов
+// или с заменой похожих символов (последние два — только с fuzzy)
+export type SheetMatchConfidence = "offer_id" | "exact" | "normalized" | "confusable";
+
+export interface SheetEntry {
+  username: string;
+  status: string | null;
+  offer_id: string | null;
 }
 
 export interface SheetCheckResult {
@@ -32,8 +74,41 @@ export interface SheetCheckResult {
   in_sheet: boolean;
   sheet_status: string | null;
   offer_id: string | null;
+  sheet_username: string | null;
+  match_confidence: SheetMatchConfidence | null;
+  duplicate: boolean;
+  sheet_rows: SheetEntry[];
+}
+
+// Расхождение между папками, таблицей и G2G (команда reconcile_accounts)
+export interface ReconcileIssue {
+  kind: "missing_in_sheet" | "offer_id_mismatch" | "offer_gone" | "duplicate_local" | "duplicate_in_sheet";
+  username: string;
+  path: string | null;
+  local_offer_id: string | null;
+  sheet_offer_id: string | null;
+  sheet_status: string | null;
+  g2g_status: string | null;
+  fix: { action: "append_row" | "update_row" } | null;
+  fixed: boolean;
+  fix_error: string | null;
+}
+
+export interface ReconcileReport {
+  local_listed: number;
+  sheet_rows: number;
+  g2g_checked: number | null;
+  g2g_unchecked: string[];
+  issues: ReconcileIssue[];
+  fixed: number;
 }
 
+// Изменение в загруженной папке (событие "accounts-changed" от наблюдателя в Rust)
+export type AccountChange =
+  | { kind: "added"; account: AccountFolder }
+  | { kind: "removed"; path: string }
+  | { kind: "changed"; account: AccountFolder; listed_changed: boolean };
+
 export interface LoadAccountsResult {
   success: boolean;
   message: string;
@@ -48,6 +123,8 @@ export class AccountManager {
   private lastSelectedPath: string = "";
   private readonly STORAGE_KEY = "g2g_last_folder_path";
   private initialized: boolean = false;
+  private changeListeners = new Set<() => void>();
+  private unlistenWatcher: UnlistenFn | null = null;
 
   constructor() {
     // Синхронный конструктор - инициализацию делаем отдельно
@@ -137,13 +214,10 @@ export class AccountManager {
       this.basePath = result.base_path;
 
       // Преобразуем в формат Account, сохраняя is_listed
-      this.accounts = result.accounts.map((folder) => ({
-        id: this.nextId++,
-        name: folder.name,
-        path: folder.path,
-        status: folder.is_listed ? ("listed" as const) : ("loaded" as const),  // ← Автоматически ставим статус
-        is_listed: folder.is_listed,  // ← Сохраняем поле
-      }));
+      this.accounts = result.accounts.map((folder) => this.toAccount(folder));
+
+      // Бэкенд уже следит за папкой — подписываемся на его события
+      await this.startWatching();
 
       return {
         success: true,
@@ -160,6 +234,55 @@ export class AccountManager {
     }
   }
 
+  private toAccount(folder: AccountFolder): Account {
+    return {
+      id: this.nextId++,
+      name: folder.name,
+      path: folder.path,
+      group: folder.group,
+      summary: folder.summary,
+      status: folder.is_listed ? "listed" : "loaded", // ← Автоматически ставим статус
+      is_listed: folder.is_listed, // ← Сохраняем поле
+    };
+  }
+
+  private async startWatching(): Promise<void> {
+    if (this.unlistenWatcher) return;
+    this.unlistenWatcher = await listen<AccountChange[]>("accounts-changed", (event) => {
+      this.applyChanges(event.payload);
+    });
+  }
+
+  // Подписка на изменения списка аккаунтов; возвращает функцию отписки
+  onChange(listener: () => void): () => void {
+    this.changeListeners.add(listener);
+    return () => this.changeListeners.delete(listener);
+  }
+
+  // Применить изменения папки: новые аккаунты, удалённые, смена статуса выставления
+  applyChanges(changes: AccountChange[]): void {
+    for (const change of changes) {
+      if (change.kind === "removed") {
+        this.accounts = this.accounts.filter((a) => a.path !== change.path);
+        continue;
+      }
+      const existing = this.accounts.find((a) => a.path === change.account.path);
+      if (!existing) {
+        this.accounts.push(this.toAccount(change.account));
+        continue;
+      }
+      existing.group = change.account.group;
+      existing.summary = change.account.summary;
+      existing.is_listed = change.account.is_listed;
+      // Статус "processing" не трогаем — выставление ещё идёт в этом окне
+      if (existing.status !== "processing") {
+        existing.status = change.account.is_listed ? "listed" : existing.status === "listed" ? "loaded" : existing.status;
+      }
+    }
+    console.log(`📂 Изменения в папке аккаунтов: ${changes.length}`);
+    this.changeListeners.forEach((listener) => listener());
+  }
+
   // Получить список файлов для конкретного аккаунта
   async getAccountFiles(accountId: number): Promise<string[]> {
     const account = this.accounts.find((acc) => acc.id === accountId);
@@ -188,13 +311,14 @@ export class AccountManager {
     }
   }
 
-  // Проверить по логину, какие аккаунты уже есть в Google-таблице.
+  // Проверить по Offer ID и логину, какие аккаунты уже есть в Google-таблице.
   // Возвращает количество найденных. Бросает ошибку, если веб-хук не настроен.
-  async checkAccountsInSheet(): Promise<number> {
+  async checkAccountsInSheet(fuzzy = true): Promise<number> {
     if (this.accounts.length === 0) return 0;
 
     const results = await invoke<SheetCheckResult[]>("check_accounts_in_sheet", {
       accounts: this.accounts.map((a) => ({ name: a.name, path: a.path })),
+      fuzzy,
     });
 
     let found = 0;
@@ -203,7 +327,9 @@ export class AccountManager {
       if (!account) continue;
       account.in_sheet = result.in_sheet;
       account.sheet_status = result.sheet_status ?? undefined;
-      account.sheet_username = result.username;
+      account.sheet_username = result.sheet_username ?? result.username;
+      account.sheet_match = result.match_confidence ?? undefined;
+      account.sheet_duplicate = result.duplicate;
       if (result.in_sheet) found++;
     }
 
@@ -211,6 +337,17 @@ export class AccountManager {
     return found;
   }
 
+  // Сверить выставленные аккаунты загруженной папки с таблицей и офферами на G2G.
+  // С apply исправления сразу применяются к таблице.
+  async reconcile(apply: boolean, checkG2g = true): Promise<ReconcileReport> {
+    if (!this.basePath) throw new Error("Папка аккаунтов не загружена");
+    return invoke<ReconcileReport>("reconcile_accounts", {
+      folderPath: this.basePath,
+      checkG2g,
+      apply,
+    });
+  }
+
   // Получить все аккаунты
   getAccounts(): Account[] {
     return [...this.accounts];
@@ -241,6 +378,7 @@ export class AccountManager {
 
   // Очистить все аккаунты
   clearAccounts(): void {
+    invoke("stop_account_watcher").catch((e) => console.warn("Не удалось остановить наблюдение:", e));
     this.accounts = [];
     this.basePath = "";
     this.nextId = 1;
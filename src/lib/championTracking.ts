@@ -1,78 +1,63 @@
 // src/lib/championTracking.ts
-// Система отслеживания использования чемпионов в заголовках
+// Статистика использования чемпионов и скинов в заголовках.
+// Хранится на стороне Rust (title_usage.json в папке конфигурации или путь
+// из настроек usage.store_path), а не в localStorage.
 
-const STORAGE_KEY = 'g2g_champion_usage_count';
+import { invoke } from "@tauri-apps/api/core";
 
-export interface ChampionUsage {
-  [championName: string]: number;
+// Ключ старой статистики в localStorage — импортируется один раз
+const LEGACY_STORAGE_KEY = 'g2g_champion_usage_count';
+
+export interface UsageStat {
+  name: string;
+  kind: 'champion' | 'skin';
+  score: number;
+  total: number;
 }
 
-// Загрузить статистику использования
-export function loadChampionUsage(): ChampionUsage {
+// Перенести статистику из localStorage в хранилище приложения
+export async function migrateLocalChampionUsage(): Promise<void> {
+  let stored: string | null = null;
   try {
-    const stored = localStorage.getItem(STORAGE_KEY);
-    if (stored) {
-      return JSON.parse(stored);
-    }
+    stored = localStorage.getItem(LEGACY_STORAGE_KEY);
   } catch (error) {
-    console.warn('Не удалось загрузить статистику чемпионов:', error);
+    console.warn('Не удалось прочитать localStorage:', error);
+    return;
   }
-  return {};
-}
+  if (!stored) return;
 
-// Сохранить статистику использования
-function saveChampionUsage(usage: ChampionUsage): void {
   try {
-    localStorage.setItem(STORAGE_KEY, JSON.stringify(usage));
+    const imported = await invoke<number>('import_champion_usage', { json: stored });
+    localStorage.removeItem(LEGACY_STORAGE_KEY);
+    console.log(`📥 Статистика ${imported} чемпионов перенесена из localStorage`);
   } catch (error) {
-    console.warn('Не удалось сохранить статистику чемпионов:', error);
+    console.warn('Не удалось перенести статистику чемпионов:', error);
   }
 }
 
-// Увеличить счетчик использования для списка чемпионов
-export function trackChampionUsage(championNames: string[]): void {
-  const usage = loadChampionUsage();
-  
-  for (const name of championNames) {
-    usage[name] = (usage[name] || 0) + 1;
-  }
-  
-  saveChampionUsage(usage);
+// Учесть чемпионов и скины, попавшие в заголовок
+export async function trackChampionUsage(championNames: string[], skinNames: string[] = []): Promise<void> {
+  await invoke('track_title_usage', { champions: championNames, skins: skinNames });
   console.log('📊 Обновлена статистика использования чемпионов:', championNames);
 }
 
 // Отсортировать список чемпионов по частоте использования (от меньшего к большему)
-export function sortChampionsByUsage(champions: string[]): string[] {
-  const usage = loadChampionUsage();
-  
-  return [...champions].sort((a, b) => {
-    const usageA = usage[a] || 0;
-    const usageB = usage[b] || 0;
-    
-    // Сначала те, что использовались реже
-    if (usageA !== usageB) {
-      return usageA - usageB;
-    }
-    
-    // Если одинаково - сохраняем исходный порядок (алфавитный)
-    return a.localeCompare(b);
-  });
+export async function sortChampionsByUsage(champions: string[]): Promise<string[]> {
+  return invoke<string[]>('rotate_title_champions', { champions });
 }
 
-// Получить статистику для отладки
-export function getChampionUsageStats(): { champion: string; count: number }[] {
-  const usage = loadChampionUsage();
-  return Object.entries(usage)
-    .map(([champion, count]) => ({ champion, count }))
-    .sort((a, b) => a.count - b.count);
+// Получить статистику (от редко используемых к частым)
+export async function getChampionUsageStats(): Promise<UsageStat[]> {
+  return invoke<UsageStat[]>('get_title_usage_stats');
 }
 
-// Очистить всю статистику (для отладки)
-export function clearChampionUsage(): void {
-  try {
-    localStorage.removeItem(STORAGE_KEY);
-    console.log('🗑️ Статистика чемпионов очищена');
-  } catch (error) {
-    console.warn('Не удалось очистить статистику:', error);
-  }
+// Экспорт всей статистики в JSON
+export async function exportChampionUsage(): Promise<string> {
+  return invoke<string>('export_title_usage');
+}
+
+// Очистить всю статистику
+export async function clearChampionUsage(): Promise<void> {
+  await invoke('clear_title_usage');
+  console.log('🗑️ Статистика чемпионов очищена');
 }
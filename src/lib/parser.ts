@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import { sortChampionsByUsage } from "./championTracking";
+import { migrateLocalChampionUsage } from "./championTracking";
 
 // Типы данных
 export interface AccountData {
@@ -24,6 +24,7 @@ export interface ParsedForm {
   title: string;
   description: string;
   usedChampions: string[];
+  usedSkins: string[];
 }
 
 // Чтение файла из папки аккаунта
@@ -276,117 +277,14 @@ export async function parseAccountData(accountPath: string, files: string[]): Pr
   return data;
 }
 
-// Генерация заголовка
-export function generateTitle(data: AccountData): { title: string; usedChampions: string[] } {
-  const MAX_LENGTH = 128;
-
-  // Базовая часть
-  const baseTitle = `[${data.server} ⍜] - [${data.level} LVL | ${data.championsCount} Champions`;
-  const endTitle = " | Handleveled | Full Access ⍜]";
-
-  // Доступное пространство для скинов/чемпионов
-  const availableSpace = MAX_LENGTH - baseTitle.length - endTitle.length;
-
-  console.log(`Доступно символов для заголовка: ${availableSpace}`);
-
-  // Если есть скины, добавляем их в приоритете
-  const items: string[] = [];
-  const usedChampions: string[] = []; // 👈 Отслеживаем использованные чемпионы
-  let currentSpace = availableSpace;
-
-  // Сначала добавляем скины
-  if (data.skinsList.length > 0) {
-    for (const skin of data.skinsList) {
-      const itemLength = skin.length + 3; // +3 для " | "
-      if (itemLength <= currentSpace) {
-        items.push(skin);
-        currentSpace -= itemLength;
-      } else {
-        break;
-      }
-    }
-  }
-
-  // Если осталось место, добавляем чемпионов
-  // 👇 ИЗМЕНЕНИЕ: Сортируем чемпионов по частоте использования
-  if (currentSpace > 0 && data.championsList.length > 0) {
-    const sortedChampions = sortChampionsByUsage(data.championsList);
-    console.log('📋 Чемпионы отсортированы по частоте использования');
-
-    for (const champion of sortedChampions) {
-      const itemLength = champion.length + 3; // +3 для " | "
-      if (itemLength <= currentSpace) {
-        items.push(champion);
-        usedChampions.push(champion); // 👈 Запоминаем использованного чемпиона
-        currentSpace -= itemLength;
-      } else {
-        break;
-      }
-
-      // Не добавляем слишком много чемпионов
-      if (items.length >= 10) break;
-    }
-  }
-
-  // Формируем итоговый заголовок
-  const title = items.length > 0
-    ? baseTitle + " | " + items.join(" | ") + endTitle
-    : baseTitle + endTitle;
-
-  console.log(`✅ Заголовок создан. Использовано чемпионов: ${usedChampions.length}`);
-
-  return { title, usedChampions }; // 👈 Возвращаем и заголовок, и список чемпионов
-}
-
-// Генерация описания
-export function generateDescription(data: AccountData): string {
-  const lines: string[] = [
-    "▸ Instant Auto-Delivery 24/7",
-    "⤱ You must play 10 Quickplay or Draft games to unlock Ranked.",
-    `⤱ Last Rank: ${(data.lastRank && data.lastRank.toLowerCase() !== "unknown") ? data.lastRank : "The Account has never been ranked, but MMR is random."}`,
-    "⤱ Current Rank: Unranked",
-    `⤱ Last Play / Inactive From - ${data.lastPlayDate}`,
-    "",
-    `◉ Level - ${data.level}`,
-    `◉ Honor level is ${data.honorLevel}`,
-    `◉ Champions - ${data.championsCount}`,
-    `◉ Skins - ${data.skinsCount}`,
-    `◉ Riot Points - ${data.riotPoints}`,
-    `◉ Blue Essence - ${data.blueEssence}`,
-    `◉ Mythic Essence - ${data.mythicEssence}`,
-    `◉ Orange Essence - ${data.orangeEssence}`,
-    "",
-    "✓ Full Access [You can change the email, password, etc.]",
-    "⍜ Completely Safe with 0% Banrate",
-    "⮸ Hand-Leveled",
-    "✫ Positive Reviews",
-  ];
-
-  // Добавляем список чемпионов
-  if (data.championsList.length > 0) {
-    lines.push("");
-    lines.push("◉ List of Champions:");
-    lines.push("");
-    lines.push(data.championsList.join(", ") + ".");
-  }
-
-  // Добавляем список скинов
-  if (data.skinsList.length > 0) {
-    lines.push("");
-    lines.push("◉ List of Skins:");
-    lines.push("");
-    lines.push(data.skinsList.join(", ") + ".");
-  }
-
-  // Добавляем список иконок
-  if (data.iconsList.length > 0) {
-    lines.push("");
-    lines.push("◉ List of Icons:");
-    lines.push("");
-    lines.push(data.iconsList.join(", ") + ".");
-  }
-
-  return lines.join("\n");
+// Результат генерации текста оффера на стороне Rust (generate_listing_text)
+interface RenderedListing {
+  title: string;
+  description: string;
+  used_champions: string[];
+  used_skins: string[];
+  title_length: number;
+  max_length: number;
 }
 
 // Главная функция автозаполнения
@@ -397,9 +295,15 @@ export async function autofillListing(accountPath: string, files: string[]): Pro
     // Парсим данные из файлов
     const data = await parseAccountData(accountPath, files);
 
-    // 👇 ИЗМЕНЕНИЕ: Теперь получаем и заголовок, и список чемпионов
-    const { title, usedChampions } = generateTitle(data);
-    const description = generateDescription(data);
+    // Старая статистика из localStorage переносится в Rust до первой ротации
+    await migrateLocalChampionUsage();
+
+    // Заголовок и описание рендерятся в Rust по шаблонам из папки конфигурации.
+    // Порядок чемпионов — ротация по статистике использования на стороне Rust.
+    const rendered = await invoke<RenderedListing>("generate_listing_text", { data });
+    const { title, description } = rendered;
+    const usedChampions = rendered.used_champions;
+    const usedSkins = rendered.used_skins;
 
     console.log("=== Автозаполнение завершено ===");
     console.log("Длина заголовка:", title.length);
@@ -407,7 +311,7 @@ export async function autofillListing(accountPath: string, files: string[]): Pro
     console.log("Использовано чемпионов:", usedChampions);
 
     // 👇 Возвращаем список использованных чемпионов
-    return { title, description, usedChampions };
+    return { title, description, usedChampions, usedSkins };
   } catch (error) {
     console.error("=== ОШИБКА автозаполнения ===", error);
     throw error;
@@ -9,13 +9,90 @@ export interface G2GSettings {
 }
 
 export interface SheetsSettings {
+  // "webhook" — Apps Script, "api" — Google Sheets API с сервисным аккаунтом,
+  // "local" — только файл ledger_path
+  backend?: "webhook" | "api" | "local";
   webhook_url: string;
+  // Общий секрет для подписи запросов к веб-хуку (свойство скрипта WEBHOOK_SECRET)
+  webhook_secret?: string;
+  spreadsheet_id?: string;
+  service_account_key?: string;
+  // Файл .csv/.xlsx: вместо таблицы ("local") или копия строк параллельно с ней
+  ledger_path?: string;
+  // Дополнительные колонки: поле выставления → заголовок в таблице
+  columns?: SheetColumn[];
+}
+
+export interface SheetColumn {
+  field: string;
+  header: string;
+}
+
+// Пункт чек-листа диагностики веб-хука (команда diagnose_sheet_webhook)
+export interface DiagnosticCheck {
+  id: string;
+  status: "ok" | "warning" | "error" | "skipped";
+  message: string;
+  hint?: string;
+}
+
+export interface WebhookDiagnosis {
+  ok: boolean;
+  script_version: number | null;
+  checks: DiagnosticCheck[];
+}
+
+export interface DeliverySpeed {
+  min: number;
+  max: number;
+  delivery_time: number;
+}
+
+export interface SalesTerritory {
+  settings_type: string;
+  countries: string[];
+}
+
+export interface OfferTemplateSettings {
+  delivery_speed: "instant" | "manual";
+  delivery_speed_details: DeliverySpeed[];
+  qty: number;
+  min_qty: number;
+  low_stock_alert_qty: number;
+  sales_territory: SalesTerritory;
+  wholesale_details: unknown[];
+}
+
+export interface ImageHostSettings {
+  upload_url: string;
+  file_field?: string;
+  auth_header?: string;
+  url_pointer?: string;
+}
+
+export interface ListingTextSettings {
+  title_variant?: string;
+  title_max_length: number;
+}
+
+export interface UsageSettings {
+  store_path?: string;
+  half_life_days: number;
+}
+
+export interface WatcherSettings {
+  mode: "auto" | "native" | "poll";
 }
 
 export interface AppSettings {
   g2g: G2GSettings;
   theme?: "dark" | "light";
   sheets?: SheetsSettings;
+  offer_template?: OfferTemplateSettings;
+  image_host?: ImageHostSettings;
+  listing_text?: ListingTextSettings;
+  usage?: UsageSettings;
+  watcher?: WatcherSettings;
 }
 
 // Класс для управления настройками
@@ -96,6 +173,14 @@ export class SettingsManager {
     return errors;
   }
 
+  // Проверить веб-хук Apps Script из формы (без сохранения настроек)
+  async diagnoseSheetWebhook(webhookUrl: string, webhookSecret: string): Promise<WebhookDiagnosis> {
+    return invoke<WebhookDiagnosis>("diagnose_sheet_webhook", {
+      webhookUrl,
+      webhookSecret: webhookSecret || null,
+    });
+  }
+
   // Очистить настройки
   async clearSettings(): Promise<void> {
     try {